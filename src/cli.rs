@@ -1,28 +1,313 @@
 /// cli.rs
 
 use clap::{Parser, Subcommand};
-use std::{error::Error, io::{self, stdout}};
-use crate::model::store::TaskStore;
+use std::{error::Error, fmt, fs::File, io::{stdout, BufWriter, IsTerminal, Write}, path::PathBuf};
+use crate::model::store::{CsvLayout, StoreError, TaskStore};
+use crate::model::task::{parse_tags, EvalPeriod, Task, TaskFilter, TaskTy};
+use crate::model::progress::{progress_all, tag_rollups};
+use crate::model::clock::{Clock, ClockError};
+use crate::config::{Config, ConfigError};
+use crate::server::ApiServer;
+use crate::model::mirror::{ConflictPolicy, Mirror, MirrorReport};
+use crate::model::sync::{self, Conflict, Peer, Side, SyncPolicy};
+use crate::model::pause::Pause;
+use crate::model::order::TaskOrder;
+use crate::model::task_entry::TaskEntry;
+use crate::model::convert::Storable;
+use crate::model::when::{parse_when, WhenError};
+use crate::model::search;
+use crate::model::ics::{write_ics, IcsKind};
+use crate::model::journal::{write_journal, JournalGroup};
+use crate::model::report::write_report;
+use crate::model::query::{parse_date, Clause, Query, QueryError};
+use crate::model::import::{self, ColumnMap, Format, ImportError, MergePolicy};
+use chrono::{NaiveDate, Utc};
 use crate::ui::view;
 use crossterm::{
     execute,
     terminal::{enable_raw_mode,disable_raw_mode},
     terminal::{EnterAlternateScreen, LeaveAlternateScreen},
     cursor::{Show, Hide},
+    style::Stylize,
 };
 ///////////////////////////////////////////////////////////
 
+/// Exit codes, clap itself exits with 2 on a bad command line
+pub const EXIT_FAILURE: u8 = 1;
+pub const EXIT_INVALID: u8 = 2;     // input that could not be understood
+pub const EXIT_NOT_FOUND: u8 = 3;   // no task or entry matched
+pub const EXIT_AMBIGUOUS: u8 = 4;   // several tasks or entries matched
+
+/// Input that is well formed but not acceptable
+#[derive(Debug)]
+pub struct InvalidInput(pub String);
+
+impl fmt::Display for InvalidInput {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl Error for InvalidInput {}
+
+/// The exit code for an error returned by `Mode::run`
+pub fn exit_code(err: &(dyn Error + 'static)) -> u8 {
+    if let Some(e) = err.downcast_ref::<StoreError>() {
+        return match e {
+            StoreError::NotFound(..) => EXIT_NOT_FOUND,
+            StoreError::Ambiguous(..) => EXIT_AMBIGUOUS,
+            _ => EXIT_FAILURE,
+        };
+    }
+    let invalid = err.is::<InvalidInput>()
+        || err.is::<WhenError>()
+        || err.is::<QueryError>()
+        || err.is::<ImportError>()
+        || err.is::<ClockError>()
+        || err.is::<ConfigError>();
+    match invalid {
+        true => EXIT_INVALID,
+        false => EXIT_FAILURE,
+    }
+}
+
 #[derive(Subcommand, Debug)]
 pub enum Mode {
     // Start packrat as normal
     Regular,
     // Export the database
     Export {
-        #[arg(value_parser = ["CSV", "JSON"], help = "export format.")]
+        #[arg(value_parser = ["CSV", "JSON", "ICS", "MD", "MARKDOWN", "HTML"], ignore_case = true,
+              help = "export format.")]
         export_type: String,
 
-        #[arg(help = "Output file.")]
+        #[arg(help = "Output file, - writes to stdout.")]
+        file: String,
+
+        #[arg(long, default_value = "wide",
+              help = "CSV layout, wide for a column per task or long for a row per entry.")]
+        layout: CsvLayout,
+
+        #[arg(long, default_value = "event", help = "iCalendar entries as event or todo.")]
+        ics_as: IcsKind,
+
+        #[arg(long, help = "iCalendar also gets a repeating reminder for each task's period.")]
+        recurrence: bool,
+
+        #[arg(long, default_value = "day", help = "Markdown journal sections, a day or a task each.")]
+        group: JournalGroup,
+
+        #[arg(long, value_name = "DATE", help = "Only entries on or after this day, e.g. 2026-01-01 or 30d.")]
+        since: Option<String>,
+
+        #[arg(long, value_name = "DATE", help = "Only entries on or before this day.")]
+        until: Option<String>,
+
+        #[arg(long = "task", value_name = "TASK", help = "Only this task, name or ID prefix. May be repeated.")]
+        tasks: Vec<String>,
+
+        #[arg(long, help = "Only export tasks with this tag.")]
+        tag: Option<String>,
+
+        #[arg(long, help = "Include archived tasks, JSON always does.")]
+        archived: bool,
+
+        #[arg(long, short, default_value = "",
+              help = "Only export what matches, e.g. 'task:run after:2026-01-01 value>5'.")]
+        query: String,
+    },
+    // Import a packrat export, or another tracker's
+    Import {
+        #[arg(help = "File to import, or the folder of an unzipped Loop export.")]
         file: String,
+
+        #[arg(long, help = "json, csv, loop, todotxt or generic-csv. Guessed from the file if omitted.")]
+        from: Option<Format>,
+
+        #[arg(long, default_value = "",
+              help = "Columns of a generic CSV, e.g. 'date=Day,task=Habit,content=Amount,time=Time,period=Freq'.")]
+        map: ColumnMap,
+
+        #[arg(long, help = "Format of the date column of a generic CSV, e.g. '%d/%m/%Y'.")]
+        date_format: Option<String>,
+
+        #[arg(long, default_value = "skip",
+              help = "What to do with items already stored: skip, overwrite or newest.")]
+        policy: MergePolicy,
+
+        #[arg(long, help = "Show what would change without writing anything.")]
+        dry_run: bool,
+    },
+    // List tasks and how they are doing
+    List {
+        #[arg(help = "Only list what matches, e.g. task:run after:7d 'value>5' tag:health.")]
+        query: Vec<String>,
+
+        #[arg(long, help = "Only list tasks with this tag.")]
+        tag: Option<String>,
+
+        #[arg(long, help = "Include archived tasks.")]
+        archived: bool,
+
+        #[arg(long, help = "Also list the matching entries of each task.")]
+        entries: bool,
+    },
+    // Create a task
+    New {
+        #[arg(help = "Task name.")]
+        name: String,
+
+        #[arg(long, default_value = "", help = "What the task is about.")]
+        desc: String,
+
+        #[arg(long, default_value = "daily", help = "any, daily, weekly, biweekly or monthly.")]
+        period: EvalPeriod,
+
+        #[arg(long = "type", default_value = "raw", help = "raw, numeric, json, markdown or toml.")]
+        task_type: TaskTy,
+
+        #[arg(long, default_value_t = 1, help = "Completions wanted per period.")]
+        target: u32,
+
+        #[arg(long, default_value = "", help = "Comma separated tags.")]
+        tags: String,
+    },
+    // Show a task and its latest entries
+    Show {
+        #[arg(help = "Task name or ID prefix.")]
+        task: String,
+
+        #[arg(long, default_value_t = 10, help = "How many of the latest entries to show.")]
+        entries: usize,
+    },
+    // Change a task, only the given fields
+    Edit {
+        #[arg(help = "Task name or ID prefix.")]
+        task: String,
+
+        #[arg(long, help = "New name.")]
+        name: Option<String>,
+
+        #[arg(long, help = "New description.")]
+        desc: Option<String>,
+
+        #[arg(long, help = "any, daily, weekly, biweekly or monthly.")]
+        period: Option<EvalPeriod>,
+
+        #[arg(long = "type", help = "raw, numeric, json, markdown or toml.")]
+        task_type: Option<TaskTy>,
+
+        #[arg(long, help = "Completions wanted per period.")]
+        target: Option<u32>,
+
+        #[arg(long, help = "Comma separated tags, replacing the current ones.")]
+        tags: Option<String>,
+    },
+    // Delete a task and all of its entries
+    Delete {
+        #[arg(help = "Task name or ID prefix.")]
+        task: String,
+    },
+    // Change the content or time of an entry
+    EditEntry {
+        #[arg(help = "Entry ID prefix.")]
+        entry: String,
+
+        #[arg(long, help = "New content.")]
+        content: Option<String>,
+
+        #[arg(long, value_name = "WHEN", help = "New time, e.g. '2026-03-04 19:30' or 'yesterday 7pm'.")]
+        at: Option<String>,
+    },
+    // Delete an entry
+    DeleteEntry {
+        #[arg(help = "Entry ID prefix.")]
+        entry: String,
+    },
+    // Archive a task, hiding it while keeping its entries
+    Archive {
+        #[arg(help = "Task name or ID prefix.")]
+        task: String,
+
+        #[arg(long, help = "Bring an archived task back.")]
+        undo: bool,
+    },
+    // Search the content of every entry
+    Search {
+        #[arg(default_value = "", help = "Words to look for, the last may be partial.")]
+        query: String,
+
+        #[arg(long, default_value_t = 20, help = "Show at most this many hits.")]
+        limit: usize,
+
+        #[arg(long, help = "Rebuild the search index first.")]
+        reindex: bool,
+    },
+    // Log an entry for a task
+    Log {
+        #[arg(help = "Task name or ID prefix.")]
+        task: String,
+
+        #[arg(default_value = "", help = "Entry content.")]
+        content: String,
+
+        #[arg(long, value_name = "WHEN",
+              help = "When it was done, e.g. '2026-03-04 19:30', 'yesterday 7pm' or '2 days ago'.")]
+        at: Option<String>,
+    },
+    // Pause a task, or every task, for a range of days
+    Pause {
+        #[arg(long, help = "Task name or ID prefix. Pauses every task if omitted.")]
+        task: Option<String>,
+
+        #[arg(long, help = "First paused day (YYYY-MM-DD), defaults to today.")]
+        from: Option<NaiveDate>,
+
+        #[arg(long, help = "Last paused day (YYYY-MM-DD).",
+              required_unless_present_any = ["list", "remove"])]
+        to: Option<NaiveDate>,
+
+        #[arg(long, default_value = "", help = "Why the pause was taken.")]
+        reason: String,
+
+        #[arg(long, help = "List pauses instead of adding one.")]
+        list: bool,
+
+        #[arg(long, value_name = "ID", help = "Remove the pause whose ID starts with this.")]
+        remove: Option<String>,
+    },
+    // Serve tasks and entries as a JSON API for other local programs
+    Serve {
+        #[arg(long, default_value = "127.0.0.1:7878", help = "Address to listen on.")]
+        bind: String,
+
+        #[arg(long, env = "PACKRAT_TOKEN", hide_env_values = true,
+              help = "Token writes must send as 'Authorization: Bearer <token>'. A random one is made if omitted.")]
+        token: Option<String>,
+    },
+    // Merge another packrat store or JSON export into this one
+    Sync {
+        #[arg(help = "Database directory of the other store, or a 'packrat export json' file.")]
+        other: PathBuf,
+
+        #[arg(long, default_value = "newest",
+              help = "How to settle records changed on both sides: newest or ask.")]
+        prefer: SyncPolicy,
+
+        #[arg(long, help = "Show what would change without writing anything.")]
+        dry_run: bool,
+    },
+    // Sync the store with a directory of plain text files
+    Mirror {
+        #[arg(help = "Directory to mirror into. Defaults to mirror_dir from the config.")]
+        dir: Option<PathBuf>,
+
+        #[arg(long, default_value = "report",
+              help = "Who wins when a file and its record both changed: report, store or files.")]
+        prefer: ConflictPolicy,
+
+        #[arg(long, help = "Show what would change without writing anything.")]
+        dry_run: bool,
     },
 }
 
@@ -35,7 +320,22 @@ pub struct CLI {
     
     /// sub command
     #[command(subcommand)]
-    pub mode: Option<Mode>
+    pub mode: Option<Mode>,
+
+    /// timezone used for display and period boundaries
+    #[arg(long, global = true, env = "PACKRAT_TZ",
+          help = "IANA timezone, e.g. America/Vancouver. Defaults to the system zone.")]
+    pub tz: Option<String>,
+
+    /// hour at which a new day begins
+    #[arg(long, global = true, env = "PACKRAT_DAY_START",
+          help = "Hour (0-23) at which a new day begins, e.g. 4 for night owls.")]
+    pub day_start: Option<u32>,
+
+    /// settings file read at startup
+    #[arg(long, global = true, env = "PACKRAT_CONFIG",
+          help = "Config file to read. Defaults to ~/.config/packrat/config.toml.")]
+    pub config: Option<PathBuf>,
 }
 
 impl CLI {
@@ -48,6 +348,23 @@ impl CLI {
         }
         cli
     }
+
+    /// The config file's settings, with the timezone options on top
+    pub fn config(&self) -> Result<Config, Box<dyn Error>> {
+        let mut config = match (&self.config, Config::default_path()) {
+            (Some(path), _) => Config::load(path)?,
+            (None, Some(path)) => Config::load_or_default(&path)?,
+            (None, None) => Config::default(),
+        };
+        if self.tz.is_some() {
+            config.timezone = self.tz.clone();
+        }
+        if self.day_start.is_some() {
+            config.day_start = self.day_start;
+        }
+        config.clock()?;
+        Ok(config)
+    }
 }
 
 impl Mode {
@@ -55,6 +372,16 @@ impl Mode {
     pub fn run(&self) -> Result<(), Box<dyn Error>> { 
         match self {
             Mode::Regular => {
+                // take in what was edited in the mirror while we were away
+                let mirror = Config::get().mirror_dir.clone()
+                    .map(|dir| Mirror::new(dir, ConflictPolicy::Report));
+                if let Some(mirror) = &mirror {
+                    let report = mirror.sync(TaskStore::instance(), false)?;
+                    if !report.conflicts.is_empty() || !report.errors.is_empty() {
+                        eprintln!("{}", report);
+                    }
+                }
+
                 // enter raw mode
                 enable_raw_mode()?;
 
@@ -78,20 +405,414 @@ impl Mode {
                     LeaveAlternateScreen,
                     Show
                 )?;
+
+                if let Some(mirror) = &mirror {
+                    let mut report = MirrorReport::default();
+                    mirror.write(TaskStore::instance(), false, &mut report)?;
+                    TaskStore::instance().flush()?;
+                    if !report.conflicts.is_empty() {
+                        eprintln!("{}", report);
+                    }
+                }
             }
             
-            Mode::Export {export_type, file} => {
-                match TaskStore::instance().to_csv(file) {
-                    Ok(_) => {
-                        println!("CSV written to: {}", file);
+            Mode::Export {
+                export_type, file, layout, ics_as, recurrence, group, since, until, tasks, tag, archived, query
+            } => {
+                let store = TaskStore::instance();
+                let today = Clock::get().today();
+                let mut query = Query::parse(query, today)?;
+                if let Some(since) = since {
+                    query.clauses.push(Clause::After(parse_date(since, today)?));
+                }
+                if let Some(until) = until {
+                    let end = parse_date(until, today)?.succ_opt()
+                        .ok_or_else(|| QueryError(format!("'{}' is too far ahead", until)))?;
+                    query.clauses.push(Clause::Before(end));
+                }
+                if !tasks.is_empty() {
+                    let ids = tasks.iter()
+                        .map(|t| store.find_task(t).map(|t| t.id.to_string()))
+                        .collect::<Result<_, _>>()?;
+                    query.clauses.push(Clause::Id(ids));
+                }
+                let filter = TaskFilter { tag: tag.clone(), include_archived: *archived, query };
+
+                let out: Box<dyn Write> = match file.as_str() {
+                    "-" => Box::new(stdout().lock()),
+                    _ => Box::new(BufWriter::new(File::create(file)?)),
+                };
+                let written = match export_type.to_uppercase().as_str() {
+                    "JSON" => {
+                        store.write_json(out, &filter)?;
+                        "JSON"
+                    }
+                    "ICS" => {
+                        write_ics(store, out, &filter, *ics_as, *recurrence)?;
+                        "iCalendar"
+                    }
+                    "MD" | "MARKDOWN" => {
+                        write_journal(store, out, &filter, *group)?;
+                        "Journal"
+                    }
+                    "HTML" => {
+                        write_report(store, out, &filter)?;
+                        "Report"
+                    }
+                    _ => {
+                        store.write_csv(out, &filter, *layout)?;
+                        "CSV"
+                    }
+                };
+                if file != "-" {
+                    println!("{} written to: {}", written, file);
+                }
+            }
+
+            Mode::Import { file, from, map, date_format, policy, dry_run } => {
+                let store = TaskStore::instance();
+                let path = std::path::Path::new(file);
+                let format = from.or_else(|| Format::of_path(path)).ok_or_else(|| {
+                    InvalidInput(format!("can't tell the format of '{}', use --from", file))
+                })?;
+                let map = ColumnMap { date_format: date_format.clone(), ..map.clone() };
+                let dump = import::read_file(store, path, format, &map)?;
+                let summary = import::apply(store, &dump, *policy, *dry_run)?;
+                store.flush()?;
+                if *dry_run {
+                    println!("Dry run, nothing was written");
+                }
+                println!("{}", summary);
+            }
+
+            Mode::List { query, tag, archived, entries } => {
+                let store = TaskStore::instance();
+                let clock = Clock::get();
+                let tasks: Vec<Task> = store.get_prefix::<Task>(Task::key_all())?
+                    .into_iter()
+                    .filter(|t| *archived || !t.archived)
+                    .collect();
+                let progress = progress_all(store, &tasks, clock, Utc::now())?;
+
+                let tag = tag.as_deref().map(|t| t.trim_start_matches('#').to_lowercase());
+                let filter = TaskFilter {
+                    tag: tag.clone(),
+                    include_archived: *archived,
+                    query: Query::parse(&query.join(" "), clock.today())?,
+                };
+                for i in TaskOrder::load(store)?.sort(&tasks, &progress) {
+                    let (task, progress) = (&tasks[i], &progress[i]);
+                    let task_entries: Vec<TaskEntry> = store.get_prefix(TaskEntry::key_task(task.id))?;
+                    let Some(kept) = filter.select(task, &task_entries, clock) else {
+                        continue;
+                    };
+                    let tags: Vec<String> = task.tags.iter().map(|t| format!("#{}", t)).collect();
+                    println!(
+                        "{:<24} {:<20} {:<12} {}",
+                        task.name, progress.to_string(), progress.streak_label(), tags.join(" ")
+                    );
+                    if *entries {
+                        for entry in kept {
+                            let content = entry.get_content();
+                            println!("    {}  {}", entry.get_timestamp(), content.lines().next().unwrap_or(""));
+                        }
+                    }
+                }
+
+                let rollups = tag_rollups(&tasks, &progress);
+                if !rollups.is_empty() {
+                    println!();
+                }
+                for rollup in rollups.iter().filter(|r| tag.as_deref().is_none_or(|t| r.tag == t)) {
+                    println!("{} (best streak {})", rollup, rollup.streak);
+                }
+            }
+
+            Mode::New { name, desc, period, task_type, target, tags } => {
+                let store = TaskStore::instance();
+                if name.trim().is_empty() {
+                    return Err(Box::new(InvalidInput(String::from("the task needs a name"))));
+                }
+                if store.name_taken(name.trim(), None)? {
+                    return Err(Box::new(InvalidInput(format!("a task named '{}' already exists", name))));
+                }
+                let mut task = Task::new(name.trim(), desc.as_str());
+                task.eval_period = period.clone();
+                task.task_type = task_type.clone();
+                task.target = *target;
+                task.tags = parse_tags(tags);
+                let task = store.put(task)?;
+                store.flush()?;
+                println!("Created {} {}", task.name, task.id);
+            }
+
+            Mode::Show { task, entries } => {
+                let store = TaskStore::instance();
+                let task = store.find_task(task)?;
+                let task_entries: Vec<TaskEntry> = store.get_prefix(TaskEntry::key_task(task.id))?;
+                let progress = progress_all(store, std::slice::from_ref(&task), Clock::get(), Utc::now())?
+                    .remove(0);
+
+                println!("{}", task.name);
+                println!("  id:       {}", task.id);
+                println!("  desc:     {}", task.desc);
+                println!("  type:     {:?}", task.task_type);
+                println!("  target:   {} per {}", task.target, task.eval_period.unit());
+                println!("  progress: {}, streak {}", progress, progress.streak_label());
+                println!("  tags:     {}", task.tags.join(", "));
+                println!("  created:  {}", task.get_timestamp());
+                if task.archived {
+                    println!("  archived");
+                }
+                println!("  entries:  {}", task_entries.len());
+                for entry in task_entries.iter().take(*entries) {
+                    let content = entry.get_content();
+                    println!(
+                        "    {}  {}  {}",
+                        &entry.id.to_string()[..8],
+                        entry.get_timestamp(),
+                        content.lines().next().unwrap_or("")
+                    );
+                }
+            }
+
+            Mode::Edit { task, name, desc, period, task_type, target, tags } => {
+                let store = TaskStore::instance();
+                let mut task = store.find_task(task)?;
+                if let Some(name) = name {
+                    if name.trim().is_empty() {
+                        return Err(Box::new(InvalidInput(String::from("the task needs a name"))));
                     }
-                    Err(e) => {
-                        eprintln!("Failed to create CSV: {}", e);
+                    if store.name_taken(name.trim(), Some(task.id))? {
+                        return Err(Box::new(InvalidInput(format!("a task named '{}' already exists", name))));
                     }
+                    task.name = name.trim().to_string();
+                }
+                if let Some(desc) = desc {
+                    task.desc = desc.clone();
+                }
+                if let Some(period) = period {
+                    task.eval_period = period.clone();
+                }
+                if let Some(task_type) = task_type {
+                    task.task_type = task_type.clone();
                 }
+                if let Some(target) = target {
+                    task.target = *target;
+                }
+                if let Some(tags) = tags {
+                    task.tags = parse_tags(tags);
+                }
+                let task = store.put(task)?;
+                store.flush()?;
+                println!("Updated {}", task.name);
+            }
+
+            Mode::Delete { task } => {
+                let store = TaskStore::instance();
+                let task = store.find_task(task)?;
+                let n = store.delete_task(&task)?;
+                store.flush()?;
+                println!("Deleted {} and {} entries", task.name, n);
+            }
+
+            Mode::EditEntry { entry, content, at } => {
+                let store = TaskStore::instance();
+                let mut entry = store.find_entry(entry)?;
+                if let Some(content) = content {
+                    entry.content = content.as_bytes().to_vec();
+                }
+                if let Some(when) = at {
+                    entry.timestamp = parse_when(when, Clock::get(), Utc::now())?;
+                }
+                let entry = store.put(entry)?;
+                store.flush()?;
+                println!("Updated {} at {}", entry.id, entry.get_timestamp());
+            }
+
+            Mode::DeleteEntry { entry } => {
+                let store = TaskStore::instance();
+                let entry = store.find_entry(entry)?;
+                store.delete_item(&entry)?;
+                store.flush()?;
+                println!("Deleted {}", entry.id);
+            }
+
+            Mode::Archive { task, undo } => {
+                let store = TaskStore::instance();
+                let mut task = store.find_task(task)?;
+                task.archived = !*undo;
+                let task = store.put(task)?;
+                store.flush()?;
+                match task.archived {
+                    true => println!("Archived {}", task.name),
+                    false => println!("Restored {}", task.name),
+                }
+            }
+
+            Mode::Search { query, limit, reindex } => {
+                let store = TaskStore::instance();
+                if *reindex {
+                    let n = search::reindex(store)?;
+                    store.flush()?;
+                    println!("Indexed {} entries", n);
+                }
+
+                // only highlight for a person, not for a pipe
+                let color = stdout().is_terminal();
+                let hits = search::search(store, query)?;
+                for hit in hits.iter().take(*limit) {
+                    let snippet: String = search::snippet(&hit.entry.get_content(), query, 80)
+                        .into_iter()
+                        .map(|(text, matched)| match matched && color {
+                            true => text.yellow().bold().to_string(),
+                            false => text,
+                        })
+                        .collect();
+                    println!("{}  {}  {}", hit.task.name, hit.entry.get_timestamp(), hit.entry.id);
+                    println!("    {}", snippet);
+                }
+                if hits.len() > *limit {
+                    println!("... and {} more", hits.len() - limit);
+                }
+            }
+
+            Mode::Log { task, content, at } => {
+                let store = TaskStore::instance();
+                let task = store.find_task(task)?;
+
+                let now = Utc::now();
+                let timestamp = match at {
+                    Some(when) => parse_when(when, Clock::get(), now)?,
+                    None => now,
+                };
+                let entry = store.put(TaskEntry::new_at(task.id, content.as_str(), timestamp))?;
+                store.flush()?;
+                println!("Logged {} for {} at {}", entry.id, task.name, entry.get_timestamp());
+            }
+
+            Mode::Pause { task, from, to, reason, list, remove } => {
+                let store = TaskStore::instance();
+                let pauses: Vec<Pause> = store.get_prefix(Pause::key_all())?;
+
+                if *list {
+                    for pause in &pauses {
+                        let target = match pause.task_id {
+                            Some(id) => store.get::<Task>(Task::key_task(id))?
+                                .map_or(id.to_string(), |t| t.name),
+                            None => String::from("all tasks"),
+                        };
+                        println!("{}  {}: {}", pause.id, target, pause);
+                    }
+                } else if let Some(prefix) = remove {
+                    let matches: Vec<&Pause> = pauses.iter()
+                        .filter(|p| p.id.to_string().starts_with(prefix.as_str()))
+                        .collect();
+                    match matches.as_slice() {
+                        [pause] => {
+                            store.delete_item(*pause)?;
+                            println!("Removed {}", pause);
+                        }
+                        [] => return Err(Box::new(StoreError::NotFound("pause", prefix.clone()))),
+                        many => {
+                            return Err(Box::new(StoreError::Ambiguous("pause", prefix.clone(), many.len())))
+                        }
+                    }
+                } else {
+                    let task_id = match task {
+                        Some(query) => Some(store.find_task(query)?.id),
+                        None => None,
+                    };
+                    let start = from.unwrap_or_else(|| Clock::get().today());
+                    let end = to.expect("clap requires --to when adding a pause");
+                    if end < start {
+                        return Err(Box::new(InvalidInput(
+                            String::from("the pause must end on or after its first day")
+                        )));
+                    }
+                    let pause = store.put(Pause::new(task_id, start, end, reason.as_str()))?;
+                    println!("Added {}: {}", pause.id, pause);
+                }
+                store.flush()?;
+            }
+
+            Mode::Serve { bind, token } => {
+                let server = match token {
+                    Some(token) => ApiServer::bind(bind, token)?,
+                    None => {
+                        let token = uuid::Uuid::new_v4().simple().to_string();
+                        println!("Token for writes: {}", token);
+                        ApiServer::bind(bind, &token)?
+                    }
+                };
+                let addr = server.addr().map_or(bind.clone(), |a| a.to_string());
+                println!("Serving on http://{}", addr);
+                server.run(TaskStore::instance());
+            }
+
+            Mode::Sync { other, prefer, dry_run } => {
+                if !other.exists() {
+                    return Err(Box::new(InvalidInput(format!("no store or export at {}", other.display()))));
+                }
+                if other.canonicalize().ok() == Config::get().db_path.canonicalize().ok() {
+                    return Err(Box::new(InvalidInput(String::from("can't sync a store with itself"))));
+                }
+                if *prefer == SyncPolicy::Ask && !std::io::stdin().is_terminal() {
+                    return Err(Box::new(InvalidInput(String::from("--prefer ask needs a terminal"))));
+                }
+                let store = TaskStore::instance();
+                let opened;
+                let peer = match other.is_dir() {
+                    true => {
+                        opened = TaskStore::open(&other.to_string_lossy())?;
+                        Peer::Store(&opened)
+                    }
+                    false => Peer::Export(import::native::read_json(File::open(other)?)?),
+                };
+                let report = match prefer {
+                    SyncPolicy::Newest => sync::sync(store, &peer, &mut sync::newest, *dry_run)?,
+                    SyncPolicy::Ask => sync::sync(store, &peer, &mut ask, *dry_run)?,
+                };
+                store.flush()?;
+                if *dry_run {
+                    println!("Dry run, nothing was written");
+                }
+                println!("{}", report);
+            }
+
+            Mode::Mirror { dir, prefer, dry_run } => {
+                let dir = dir.clone().or_else(|| Config::get().mirror_dir.clone()).ok_or_else(|| {
+                    InvalidInput(String::from("no directory given and no mirror_dir in the config"))
+                })?;
+                let store = TaskStore::instance();
+                let report = Mirror::new(dir, *prefer).sync(store, *dry_run)?;
+                store.flush()?;
+                if *dry_run {
+                    println!("Dry run, nothing was written");
+                }
+                println!("{}", report);
             }
         }
         Ok(())
     }
 }
 
+/// Settle a sync conflict by asking on the terminal, the newest side wins
+/// once input runs out
+fn ask(conflict: &Conflict) -> Side {
+    println!("{}", conflict);
+    loop {
+        print!("Keep [h]ere or [t]here? ");
+        let _ = stdout().flush();
+        let mut line = String::new();
+        match std::io::stdin().read_line(&mut line) {
+            Ok(0) | Err(_) => return sync::newest(conflict),
+            Ok(_) => {}
+        }
+        match line.trim().to_lowercase().as_str() {
+            "h" | "here" => return Side::Local,
+            "t" | "there" => return Side::Remote,
+            _ => {}
+        }
+    }
+}
@@ -0,0 +1,345 @@
+// config.rs
+
+use std::fmt;
+use std::error::Error;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use chrono::format::{Item, StrftimeItems};
+use crossterm::event::KeyCode;
+use serde::Deserialize;
+use tui::style::Color;
+use crate::model::clock::{Clock, DEFAULT_TIMESTAMP_FORMAT};
+use crate::model::hooks::Hooks;
+
+///////////////////////////////////////////////////////////
+
+static CONFIG: OnceLock<Config> = OnceLock::new();
+
+/// Where the config lives unless `--config` says otherwise
+pub const CONFIG_FILE: &str = "packrat/config.toml";
+
+/// Settings read from `~/.config/packrat/config.toml`. Every key is
+/// optional, unknown keys are an error so typos don't go unnoticed.
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(deny_unknown_fields, default)]
+pub struct Config {
+    pub editor: Option<String>,     // command line of the editor, e.g. "code --wait"
+    pub db_path: PathBuf,
+    pub mirror_dir: Option<PathBuf>,    // plain text copy of the store, see `Mirror`
+    pub timestamp_format: String,   // chrono format for showing timestamps
+    pub poll_interval_ms: u64,      // how often views refresh while idle
+    pub timezone: Option<String>,   // IANA name, the system zone if unset
+    pub day_start: Option<u32>,     // hour a new day begins
+    pub colors: Colors,
+    pub keys: Keys,
+    pub hooks: Hooks,
+}
+
+/// The few colors the interface is drawn in
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(deny_unknown_fields, default)]
+pub struct Colors {
+    pub accent: ColorName,  // selections, active inputs and matches
+    pub good: ColorName,    // things done, charts
+    pub muted: ColorName,   // skipped steps, hints
+}
+
+/// A color by name, e.g. "yellow", "darkgray" or "#ff8800"
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(try_from = "String")]
+pub struct ColorName(pub Color);
+
+/// Keys of the list views, each a single character
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(deny_unknown_fields, default)]
+pub struct Keys {
+    pub select: char,
+    pub back: char,
+    pub quit: char,
+    pub down: char,
+    pub up: char,
+    pub new: char,
+    pub edit: char,
+    pub delete: char,
+    pub stats: char,
+    pub retime: char,
+    pub tag: char,
+    pub pause: char,
+    pub pause_all: char,
+    pub archive: char,
+    pub show_archived: char,
+    pub pin: char,
+    pub move_down: char,
+    pub move_up: char,
+    pub sort: char,
+    pub routines: char,
+    pub run: char,
+    pub search: char,
+    pub filter: char,
+}
+
+/// The views that read bound keys, actions of different ones may share a
+/// key as `tag` and `retime` do
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum KeyView {
+    Tasks,
+    Task,
+    Routines,
+    Stats,
+}
+
+#[derive(Debug)]
+pub struct ConfigError {
+    pub path: PathBuf,
+    pub message: String,
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "bad config {}: {}", self.path.display(), self.message.trim_end())
+    }
+}
+
+impl Error for ConfigError {}
+
+///////////////////////////////////////////////////////////
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            editor: None,
+            db_path: PathBuf::from("./scratch/patrack.db"),
+            mirror_dir: None,
+            timestamp_format: String::from(DEFAULT_TIMESTAMP_FORMAT),
+            poll_interval_ms: 100,
+            timezone: None,
+            day_start: None,
+            colors: Colors::default(),
+            keys: Keys::default(),
+            hooks: Hooks::default(),
+        }
+    }
+}
+
+impl Default for Colors {
+    fn default() -> Self {
+        Colors {
+            accent: ColorName(Color::Yellow),
+            good: ColorName(Color::Green),
+            muted: ColorName(Color::DarkGray),
+        }
+    }
+}
+
+impl Default for Keys {
+    fn default() -> Self {
+        Keys {
+            select: 's',
+            back: 'b',
+            quit: 'q',
+            down: 'j',
+            up: 'k',
+            new: 'n',
+            edit: 'e',
+            delete: 'd',
+            stats: 'S',
+            retime: 't',
+            tag: 't',
+            pause: 'p',
+            pause_all: 'P',
+            archive: 'a',
+            show_archived: 'A',
+            pin: 'f',
+            move_down: 'J',
+            move_up: 'K',
+            sort: 'o',
+            routines: 'r',
+            run: 's',
+            search: '/',
+            filter: 'F',
+        }
+    }
+}
+
+impl TryFrom<String> for ColorName {
+    type Error = String;
+
+    fn try_from(name: String) -> Result<Self, Self::Error> {
+        let color = match name.to_lowercase().replace(['_', '-', ' '], "").as_str() {
+            "black" => Color::Black,
+            "red" => Color::Red,
+            "green" => Color::Green,
+            "yellow" => Color::Yellow,
+            "blue" => Color::Blue,
+            "magenta" => Color::Magenta,
+            "cyan" => Color::Cyan,
+            "gray" | "grey" => Color::Gray,
+            "darkgray" | "darkgrey" => Color::DarkGray,
+            "lightred" => Color::LightRed,
+            "lightgreen" => Color::LightGreen,
+            "lightyellow" => Color::LightYellow,
+            "lightblue" => Color::LightBlue,
+            "lightmagenta" => Color::LightMagenta,
+            "lightcyan" => Color::LightCyan,
+            "white" => Color::White,
+            hex if hex.len() == 7 && hex.starts_with('#') => {
+                let channel = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16);
+                match (channel(1), channel(3), channel(5)) {
+                    (Ok(r), Ok(g), Ok(b)) => Color::Rgb(r, g, b),
+                    _ => return Err(format!("'{}' is not a #rrggbb color", name)),
+                }
+            }
+            _ => return Err(format!("unknown color '{}', try a name like yellow or #rrggbb", name)),
+        };
+        Ok(ColorName(color))
+    }
+}
+
+impl Config {
+
+    /// `$XDG_CONFIG_HOME/packrat/config.toml`, or under `~/.config`
+    pub fn default_path() -> Option<PathBuf> {
+        let base = match std::env::var_os("XDG_CONFIG_HOME").filter(|d| !d.is_empty()) {
+            Some(dir) => PathBuf::from(dir),
+            None => PathBuf::from(std::env::var_os("HOME")?).join(".config"),
+        };
+        Some(base.join(CONFIG_FILE))
+    }
+
+    /// Read and check a config file
+    pub fn load(path: &Path) -> Result<Self, ConfigError> {
+        let err = |message: String| ConfigError { path: path.to_path_buf(), message };
+        let text = std::fs::read_to_string(path).map_err(|e| err(e.to_string()))?;
+        Config::parse(&text).map_err(err)
+    }
+
+    /// `load`, but a missing file gives the defaults. Only for the
+    /// `default_path`, a file asked for by name has to be there.
+    pub fn load_or_default(path: &Path) -> Result<Self, ConfigError> {
+        match path.exists() {
+            true => Config::load(path),
+            false => Ok(Config::default()),
+        }
+    }
+
+    /// Parse and check the text of a config file
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let mut config: Config = toml::from_str(text).map_err(|e| e.to_string())?;
+        config.db_path = expand_home(&config.db_path);
+        config.mirror_dir = config.mirror_dir.as_deref().map(expand_home);
+        config.validate()?;
+        Ok(config)
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        if StrftimeItems::new(&self.timestamp_format).any(|i| matches!(i, Item::Error)) {
+            return Err(format!("timestamp_format '{}' is not a valid date format", self.timestamp_format));
+        }
+        if self.poll_interval_ms == 0 {
+            return Err(String::from("poll_interval_ms must be at least 1"));
+        }
+        if self.hooks.timeout_ms == 0 {
+            return Err(String::from("hooks.timeout_ms must be at least 1"));
+        }
+        if self.editor.as_deref().is_some_and(|e| e.trim().is_empty()) {
+            return Err(String::from("editor must not be empty, leave it out to use $VISUAL or $EDITOR"));
+        }
+        self.clock().map_err(|e| e.to_string())?;
+        self.keys.validate()
+    }
+
+    /// The clock described by the timezone settings
+    pub fn clock(&self) -> Result<Clock, crate::model::clock::ClockError> {
+        Clock::from_settings(self.timezone.as_deref(), self.day_start)
+    }
+
+    pub fn poll_interval(&self) -> std::time::Duration {
+        std::time::Duration::from_millis(self.poll_interval_ms)
+    }
+
+    /// Install the process wide config, only the first call has an effect
+    pub fn init(config: Config) {
+        let _ = CONFIG.set(config);
+    }
+
+    /// Process wide config, the defaults if none was installed
+    pub fn get() -> &'static Config {
+        CONFIG.get_or_init(Config::default)
+    }
+}
+
+impl Keys {
+
+    /// (name, built in key, bound key, views it acts in) of every action
+    fn bindings(&self) -> Vec<(&'static str, char, char, &'static [KeyView])> {
+        use KeyView::*;
+        let d = Keys::default();
+        vec![
+            ("select", d.select, self.select, &[Tasks, Task]),
+            ("back", d.back, self.back, &[Tasks, Task, Routines, Stats]),
+            ("quit", d.quit, self.quit, &[Tasks, Task, Routines, Stats]),
+            ("down", d.down, self.down, &[Tasks, Task, Routines]),
+            ("up", d.up, self.up, &[Tasks, Task, Routines]),
+            ("new", d.new, self.new, &[Tasks, Task, Routines]),
+            ("edit", d.edit, self.edit, &[Tasks, Routines]),
+            ("delete", d.delete, self.delete, &[Tasks, Task, Routines]),
+            ("stats", d.stats, self.stats, &[Task]),
+            ("retime", d.retime, self.retime, &[Task]),
+            ("tag", d.tag, self.tag, &[Tasks]),
+            ("pause", d.pause, self.pause, &[Tasks]),
+            ("pause_all", d.pause_all, self.pause_all, &[Tasks]),
+            ("archive", d.archive, self.archive, &[Tasks]),
+            ("show_archived", d.show_archived, self.show_archived, &[Tasks]),
+            ("pin", d.pin, self.pin, &[Tasks]),
+            ("move_down", d.move_down, self.move_down, &[Tasks]),
+            ("move_up", d.move_up, self.move_up, &[Tasks]),
+            ("sort", d.sort, self.sort, &[Tasks]),
+            ("routines", d.routines, self.routines, &[Tasks]),
+            ("run", d.run, self.run, &[Routines]),
+            ("search", d.search, self.search, &[Tasks]),
+            ("filter", d.filter, self.filter, &[Tasks]),
+        ]
+    }
+
+    /// Actions acting in the same view may not share a key
+    fn validate(&self) -> Result<(), String> {
+        let bindings = self.bindings();
+        for (i, (name, _, key, views)) in bindings.iter().enumerate() {
+            let clash = bindings[i + 1..].iter()
+                .find(|(_, _, k, v)| k == key && v.iter().any(|view| views.contains(view)));
+            if let Some((other, ..)) = clash {
+                return Err(format!("keys: '{}' is bound to both {} and {}", key, name, other));
+            }
+        }
+        Ok(())
+    }
+
+    /// Turn a key pressed in `view` into the built in key of the action it
+    /// is bound to there. A built in key that was bound elsewhere does nothing.
+    pub fn translate(&self, code: KeyCode, view: KeyView) -> KeyCode {
+        let KeyCode::Char(c) = code else {
+            return code;
+        };
+        let bindings: Vec<(char, char)> = self.bindings().into_iter()
+            .filter(|(.., views)| views.contains(&view))
+            .map(|(_, d, k, _)| (d, k))
+            .collect();
+        if let Some((default, _)) = bindings.iter().find(|(d, k)| *k == c && *d != c) {
+            return KeyCode::Char(*default);
+        }
+        let moved = bindings.iter().any(|(d, k)| *d == c && *k != c);
+        let kept = bindings.iter().any(|(d, k)| *d == c && *k == c);
+        match moved && !kept {
+            true => KeyCode::Null,
+            false => code,
+        }
+    }
+}
+
+/// `~/x` to `$HOME/x`
+fn expand_home(path: &Path) -> PathBuf {
+    match (path.strip_prefix("~"), std::env::var_os("HOME")) {
+        (Ok(rest), Some(home)) => PathBuf::from(home).join(rest),
+        _ => path.to_path_buf(),
+    }
+}
@@ -1,4 +1,6 @@
 pub mod cli;
+pub mod config;
 pub mod log;
 pub mod model;
+pub mod server;
 pub mod ui;
@@ -1,17 +1,40 @@
 /// main.rs
 
-use std::{error::Error, io::stdout};
-use packrat::cli::{CLI, Mode};
-use clap::Parser;
+use std::process::ExitCode;
+use packrat::cli::{exit_code, CLI, EXIT_INVALID};
+use packrat::config::Config;
+use packrat::model::clock::Clock;
+use packrat::model::hooks::wait_for_hooks;
 
 ///////////////////////////////////////////////////////////
 
-fn main() -> Result<(), Box<dyn Error>> {
+fn main() -> ExitCode {
    
     let cli = CLI::parse_with_default();   
     
-    cli.mode.unwrap().run()?; 
+    // settings come before anything that reads them, the store included
+    match cli.config() {
+        Ok(config) => {
+            Clock::init(config.clock().unwrap());
+            Config::init(config);
+        }
+        Err(e) => {
+            eprintln!("packrat: {}", e);
+            return ExitCode::from(EXIT_INVALID);
+        }
+    }
 
-    Ok(())
-}
+    let result = cli.mode.unwrap().run();
 
+    // hooks still running finish before we go, failures are told last
+    for failure in wait_for_hooks() {
+        eprintln!("packrat: {}", failure);
+    }
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("packrat: {}", e);
+            ExitCode::from(exit_code(e.as_ref()))
+        }
+    }
+}
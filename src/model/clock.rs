@@ -0,0 +1,141 @@
+// clock.rs
+
+use std::fmt;
+use std::error::Error;
+use std::sync::OnceLock;
+use chrono::{
+    DateTime, Duration, Local, LocalResult, NaiveDate, NaiveDateTime,
+    TimeZone, Utc,
+};
+use chrono_tz::Tz;
+
+///////////////////////////////////////////////////////////
+
+/// Default format used to display timestamps
+pub const DEFAULT_TIMESTAMP_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
+
+static CLOCK: OnceLock<Clock> = OnceLock::new();
+
+/// The timezone the user lives in
+#[derive(Debug, Clone, PartialEq)]
+pub enum Zone {
+    System,     // whatever the OS reports
+    Named(Tz),  // an IANA zone such as America/Vancouver
+}
+
+/// Converts stored UTC instants into the user's local calendar. Days end at
+/// `day_start` o'clock local time, so with `day_start = 4` an entry logged
+/// at 1am still counts for the previous day.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Clock {
+    pub zone: Zone,
+    pub day_start: u32,
+}
+
+#[derive(Debug)]
+pub enum ClockError {
+    UnknownZone(String),
+    InvalidDayStart(u32),
+}
+
+impl fmt::Display for ClockError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ClockError::UnknownZone(z) => write!(f, "unknown timezone '{}'", z),
+            ClockError::InvalidDayStart(h) => {
+                write!(f, "day start hour must be between 0 and 23, got {}", h)
+            }
+        }
+    }
+}
+
+impl Error for ClockError {}
+
+///////////////////////////////////////////////////////////
+
+impl Default for Clock {
+    fn default() -> Self {
+        Clock { zone: Zone::System, day_start: 0 }
+    }
+}
+
+impl Clock {
+
+    pub fn new(zone: Zone, day_start: u32) -> Result<Self, ClockError> {
+        if day_start > 23 {
+            return Err(ClockError::InvalidDayStart(day_start));
+        }
+        Ok(Clock { zone, day_start })
+    }
+
+    /// Build a clock from user supplied settings, an absent zone means the
+    /// system zone
+    pub fn from_settings(zone: Option<&str>, day_start: Option<u32>)
+        -> Result<Self, ClockError>
+    {
+        let zone = match zone {
+            None | Some("") | Some("local") => Zone::System,
+            Some(name) => Zone::Named(
+                name.parse::<Tz>()
+                    .map_err(|_| ClockError::UnknownZone(name.to_string()))?
+            ),
+        };
+        Clock::new(zone, day_start.unwrap_or(0))
+    }
+
+    /// Install the process wide clock, only the first call has an effect
+    pub fn init(clock: Clock) {
+        let _ = CLOCK.set(clock);
+    }
+
+    /// Process wide clock, the system zone if none was installed
+    pub fn get() -> &'static Clock {
+        CLOCK.get_or_init(Clock::default)
+    }
+
+    /// Wall clock time in the user's zone
+    pub fn local(&self, ts: DateTime<Utc>) -> NaiveDateTime {
+        match &self.zone {
+            Zone::System => ts.with_timezone(&Local).naive_local(),
+            Zone::Named(tz) => ts.with_timezone(tz).naive_local(),
+        }
+    }
+
+    /// Resolve a wall clock time in the user's zone to an instant. Times
+    /// skipped by a DST jump are pushed forward past the gap.
+    pub fn to_utc(&self, naive: NaiveDateTime) -> DateTime<Utc> {
+        let resolve = |n: NaiveDateTime| match &self.zone {
+            Zone::System => flatten(Local.from_local_datetime(&n)),
+            Zone::Named(tz) => flatten(tz.from_local_datetime(&n)),
+        };
+        resolve(naive)
+            .or_else(|| resolve(naive + Duration::hours(1)))
+            .unwrap_or_else(|| naive.and_utc())
+    }
+
+    /// Format an instant in the user's zone
+    pub fn format(&self, ts: DateTime<Utc>, fmt: &str) -> String {
+        self.local(ts).format(fmt).to_string()
+    }
+
+    /// The calendar day an instant counts towards, honouring the rollover hour
+    pub fn day_of(&self, ts: DateTime<Utc>) -> NaiveDate {
+        (self.local(ts) - Duration::hours(self.day_start as i64)).date()
+    }
+
+    /// The calendar day it currently is for the user
+    pub fn today(&self) -> NaiveDate {
+        self.day_of(Utc::now())
+    }
+
+    /// The instant a logical day begins
+    pub fn start_of_day(&self, day: NaiveDate) -> DateTime<Utc> {
+        let start = day.and_hms_opt(self.day_start, 0, 0)
+            .expect("day start is validated to be a valid hour");
+        self.to_utc(start)
+    }
+}
+
+fn flatten<T: TimeZone>(result: LocalResult<DateTime<T>>) -> Option<DateTime<Utc>> {
+    result.earliest().map(|dt| dt.with_timezone(&Utc))
+}
@@ -2,6 +2,8 @@
 
 use std::fmt::Display;
 use std::error::Error;
+use chrono::{DateTime, Utc};
+use crate::model::hooks::Record;
 
 ///////////////////////////////////////////////////////////
 
@@ -13,6 +15,9 @@ pub trait Storable: Sized + Display {
     /// storable objects have an associated timestamp
     fn get_timestamp(&self) -> String;
 
+    /// the instant behind the timestamp, formatted timestamps don't sort
+    fn get_datetime(&self) -> DateTime<Utc>;
+
     /// get unique representation
     fn to_key(&self) -> String;
 
@@ -28,6 +33,12 @@ pub trait Storable: Sized + Display {
     ///
     fn from_toml(toml_string: String) -> Result<Self, Box<dyn Error>>;
 
+    /// text to keep in the full-text search index, none by default
+    fn search_text(&self) -> Option<String> { None }
+
+    /// what hooks are told when it changes, none for bookkeeping
+    fn hook_record(&self) -> Option<Record> { None }
+
     /// get dependend objects for CASCADE delete
     fn get_dependents(&self) { panic!("Not implemented"); }
     
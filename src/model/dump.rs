@@ -0,0 +1,154 @@
+// dump.rs
+
+use std::collections::{BTreeMap, HashSet};
+use uuid::Uuid;
+use serde::{Serialize, Deserialize};
+use chrono::{DateTime, Utc};
+use crate::model::clock::Clock;
+use crate::model::convert::Storable;
+use crate::model::task::{Task, TaskFilter};
+use crate::model::task_entry::TaskEntry;
+use crate::model::pause::Pause;
+use crate::model::routine::Routine;
+use crate::model::store::{StoreError, TaskStore};
+
+///////////////////////////////////////////////////////////
+
+/// Bumped whenever the layout of a dump changes
+pub const DUMP_VERSION: u32 = 1;
+
+/// Everything in the store in one document, see `TaskStore::to_json`
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
+pub struct Dump {
+    pub version: u32,
+    pub exported: DateTime<Utc>,
+    pub tasks: Vec<TaskDump>,
+    #[serde(default)]
+    pub pauses: Vec<Pause>,
+    #[serde(default)]
+    pub routines: Vec<Routine>,
+    // what `packrat sync` needs to merge an export, see `sync::Snapshot`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub store_id: Option<Uuid>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub modified: BTreeMap<String, DateTime<Utc>>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub deleted: BTreeMap<String, DateTime<Utc>>,
+}
+
+/// A task with every field plus its entries
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
+pub struct TaskDump {
+    #[serde(flatten)]
+    pub task: Task,
+    pub entries: Vec<EntryDump>,
+}
+
+/// An entry with its content as text rather than the stored bytes
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
+pub struct EntryDump {
+    pub id: Uuid,
+    pub timestamp: DateTime<Utc>,
+    pub content: String,
+}
+
+impl From<&TaskEntry> for EntryDump {
+    fn from(entry: &TaskEntry) -> Self {
+        EntryDump {
+            id: entry.id,
+            timestamp: entry.timestamp,
+            content: entry.get_content(),
+        }
+    }
+}
+
+impl EntryDump {
+
+    /// The entry as stored, under the task it was dumped with
+    pub fn to_entry(&self, task_id: Uuid) -> TaskEntry {
+        TaskEntry {
+            id: self.id,
+            task_id,
+            content: self.content.as_bytes().to_vec(),
+            timestamp: self.timestamp,
+        }
+    }
+}
+
+impl Dump {
+
+    /// Gather the tasks and entries the filter keeps, with the pauses and
+    /// routines that involve them. Archived tasks are always kept, a dump
+    /// leaves nothing out that the filter didn't ask it to.
+    pub fn collect(store: &TaskStore, filter: &TaskFilter) -> Result<Self, StoreError> {
+
+        let filter = TaskFilter { include_archived: true, ..filter.clone() };
+        let mut tasks = vec![];
+        for task in store.get_prefix::<Task>(Task::key_all())? {
+            let entries: Vec<TaskEntry> = store.get_prefix(TaskEntry::key_task(task.id))?;
+            let Some(kept) = filter.select(&task, &entries, Clock::get()) else {
+                continue;
+            };
+            tasks.push(TaskDump {
+                entries: kept.into_iter().map(EntryDump::from).collect(),
+                task,
+            });
+        }
+
+        let ids: HashSet<Uuid> = tasks.iter().map(|t| t.task.id).collect();
+        let pauses = store.get_prefix::<Pause>(Pause::key_all())?
+            .into_iter()
+            .filter(|p| p.task_id.is_none_or(|id| ids.contains(&id)))
+            .collect();
+        let routines = store.get_prefix::<Routine>(Routine::key_all())?
+            .into_iter()
+            .filter(|r| r.task_ids.iter().any(|id| ids.contains(id)))
+            .collect();
+
+        // when the dumped items changed, and what was deleted
+        let mut modified = BTreeMap::new();
+        let mut deleted = BTreeMap::new();
+        for prefix in [Task::key_all(), TaskEntry::key_all(), Pause::key_all(), Routine::key_all()] {
+            modified.append(&mut store.modified(prefix)?);
+            deleted.append(&mut store.deleted(prefix)?);
+        }
+        let mut dump = Dump { tasks, pauses, routines, deleted, ..Dump::default() };
+        let keys = dump.keys();
+        dump.modified = modified.into_iter().filter(|(k, _)| keys.contains(k)).collect();
+        dump.store_id = Some(store.store_id());
+        Ok(dump)
+    }
+
+}
+
+impl Default for Dump {
+
+    /// An empty dump made now
+    fn default() -> Self {
+        Dump {
+            version: DUMP_VERSION,
+            exported: Utc::now(),
+            tasks: vec![],
+            pauses: vec![],
+            routines: vec![],
+            store_id: None,
+            modified: BTreeMap::new(),
+            deleted: BTreeMap::new(),
+        }
+    }
+}
+
+impl Dump {
+
+    /// The store keys of everything in the dump
+    pub fn keys(&self) -> HashSet<String> {
+        let tasks = self.tasks.iter().flat_map(|t| {
+            let entries = t.entries.iter().map(|e| e.to_entry(t.task.id).to_key());
+            std::iter::once(t.task.to_key()).chain(entries)
+        });
+        tasks
+            .chain(self.pauses.iter().map(|p| p.to_key()))
+            .chain(self.routines.iter().map(|r| r.to_key()))
+            .collect()
+    }
+}
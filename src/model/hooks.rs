@@ -0,0 +1,290 @@
+// hooks.rs
+
+use std::fmt;
+use std::io::{Read, Write};
+use std::process::{Command, Stdio};
+use std::sync::mpsc::{self, Sender};
+use std::sync::{Mutex, OnceLock};
+use std::thread;
+use std::time::{Duration, Instant};
+use chrono::Utc;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use crate::model::clock::Clock;
+use crate::model::convert::Storable;
+use crate::model::pause::Pause;
+use crate::model::progress::Progress;
+use crate::model::store::TaskStore;
+use crate::model::task::Task;
+use crate::model::task_entry::TaskEntry;
+
+///////////////////////////////////////////////////////////
+
+/// How long a hook may wait before it is checked on again
+const POLL: Duration = Duration::from_millis(10);
+
+type Job = Box<dyn FnOnce() + Send>;
+
+/// Hooks run one after the other on a thread of their own, so they keep
+/// their order without holding up whoever made the change
+static QUEUE: OnceLock<Mutex<Sender<Job>>> = OnceLock::new();
+
+/// How hooks failed since `take_failures` was last called
+static FAILURES: Mutex<Vec<String>> = Mutex::new(Vec::new());
+
+/// Commands to run when things change, from the `[hooks]` table of the
+/// config. Each gets the record as JSON on stdin and `PACKRAT_EVENT` in
+/// its environment. Imports and syncs don't fire them. They run in the
+/// background, see `wait_for_hooks`.
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(deny_unknown_fields, default)]
+pub struct Hooks {
+    pub on_task_created: Option<String>,
+    pub on_entry_created: Option<String>,
+    pub on_entry_updated: Option<String>,
+    pub on_delete: Option<String>,          // a task or an entry, see PACKRAT_KIND
+    pub on_streak_milestone: Option<String>,
+    pub milestones: Vec<u32>,               // streaks that fire on_streak_milestone
+    pub timeout_ms: u64,                    // a hook still running after this is killed
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum HookEvent {
+    TaskCreated,
+    EntryCreated,
+    EntryUpdated,
+    Delete,
+    StreakMilestone,
+}
+
+/// What a hook can be told about, see `Storable::hook_record`
+#[derive(Debug, Clone, PartialEq)]
+pub enum Record {
+    Task(Task),
+    Entry(TaskEntry),
+}
+
+///////////////////////////////////////////////////////////
+
+impl Default for Hooks {
+    fn default() -> Self {
+        Hooks {
+            on_task_created: None,
+            on_entry_created: None,
+            on_entry_updated: None,
+            on_delete: None,
+            on_streak_milestone: None,
+            milestones: vec![7, 30, 100, 365],
+            timeout_ms: 5000,
+        }
+    }
+}
+
+impl fmt::Display for HookEvent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            HookEvent::TaskCreated => "on_task_created",
+            HookEvent::EntryCreated => "on_entry_created",
+            HookEvent::EntryUpdated => "on_entry_updated",
+            HookEvent::Delete => "on_delete",
+            HookEvent::StreakMilestone => "on_streak_milestone",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+impl Record {
+    fn kind(&self) -> &'static str {
+        match self {
+            Record::Task(_) => "task",
+            Record::Entry(_) => "entry",
+        }
+    }
+
+    fn to_json(&self) -> Value {
+        match self {
+            Record::Task(task) => serde_json::to_value(task).unwrap_or_default(),
+            Record::Entry(entry) => entry.to_json(),
+        }
+    }
+}
+
+impl Hooks {
+
+    /// Is any hook set at all
+    pub fn is_empty(&self) -> bool {
+        self.command(HookEvent::TaskCreated).is_none()
+            && self.command(HookEvent::EntryCreated).is_none()
+            && self.command(HookEvent::EntryUpdated).is_none()
+            && self.command(HookEvent::Delete).is_none()
+            && self.command(HookEvent::StreakMilestone).is_none()
+    }
+
+    pub fn command(&self, event: HookEvent) -> Option<&str> {
+        let command = match event {
+            HookEvent::TaskCreated => &self.on_task_created,
+            HookEvent::EntryCreated => &self.on_entry_created,
+            HookEvent::EntryUpdated => &self.on_entry_updated,
+            HookEvent::Delete => &self.on_delete,
+            HookEvent::StreakMilestone => &self.on_streak_milestone,
+        };
+        command.as_deref().filter(|c| !c.trim().is_empty())
+    }
+
+    /// After `item` was written, `existed` if it replaced a stored one
+    pub fn after_put<T: Storable>(&self, store: &TaskStore, item: &T, existed: bool) {
+        match (item.hook_record(), existed) {
+            (Some(record @ Record::Task(_)), false) => self.fire(HookEvent::TaskCreated, &record),
+            (Some(Record::Entry(entry)), false) => {
+                self.fire(HookEvent::EntryCreated, &Record::Entry(entry.clone()));
+                self.check_streak(store, &entry);
+            }
+            (Some(record @ Record::Entry(_)), true) => self.fire(HookEvent::EntryUpdated, &record),
+            _ => {}
+        }
+    }
+
+    /// After `item` was deleted
+    pub fn after_delete<T: Storable>(&self, item: &T) {
+        if let Some(record) = item.hook_record() {
+            self.fire(HookEvent::Delete, &record);
+        }
+    }
+
+    fn fire(&self, event: HookEvent, record: &Record) {
+        if let Some(command) = self.command(event) {
+            let env = [("PACKRAT_EVENT", event.to_string()), ("PACKRAT_KIND", record.kind().to_string())];
+            self.spawn(event, command, &record.to_json(), &env);
+        }
+    }
+
+    /// Fire on_streak_milestone when the new entry carried the streak of
+    /// its task onto a milestone
+    fn check_streak(&self, store: &TaskStore, entry: &TaskEntry) {
+        let Some(command) = self.command(HookEvent::StreakMilestone) else {
+            return;
+        };
+        let (Ok(Some(task)), Ok(entries), Ok(pauses)) = (
+            store.get::<Task>(Task::key_task(entry.task_id)),
+            store.get_prefix::<TaskEntry>(TaskEntry::key_task(entry.task_id)),
+            store.get_prefix::<Pause>(Pause::key_all()),
+        ) else {
+            return;
+        };
+        let (clock, now) = (Clock::get(), Utc::now());
+        let after = Progress::compute(&task, &entries, &pauses, clock, now).streak;
+        let others: Vec<TaskEntry> = entries.into_iter().filter(|e| e.id != entry.id).collect();
+        let before = Progress::compute(&task, &others, &pauses, clock, now).streak;
+
+        for milestone in self.milestones.iter().filter(|m| before < **m && **m <= after) {
+            let record = json!({
+                "task": serde_json::to_value(&task).unwrap_or_default(),
+                "entry": entry.to_json(),
+                "streak": after,
+                "milestone": milestone,
+            });
+            let env = [
+                ("PACKRAT_EVENT", HookEvent::StreakMilestone.to_string()),
+                ("PACKRAT_KIND", String::from("task")),
+            ];
+            self.spawn(HookEvent::StreakMilestone, command, &record, &env);
+        }
+    }
+
+    /// Queue `command` to run in the background
+    fn spawn(&self, event: HookEvent, command: &str, record: &Value, env: &[(&'static str, String)]) {
+        let timeout = Duration::from_millis(self.timeout_ms);
+        let (command, record, env) = (command.to_string(), record.clone(), env.to_vec());
+        queue(Box::new(move || {
+            if let Err(e) = run_hook(&command, &record, &env, timeout) {
+                FAILURES.lock().unwrap().push(format!("hook {} failed: {}", event, e));
+            }
+        }));
+    }
+}
+
+fn queue(job: Job) {
+    let sender = QUEUE.get_or_init(|| {
+        let (sender, jobs) = mpsc::channel::<Job>();
+        thread::spawn(move || jobs.into_iter().for_each(|job| job()));
+        Mutex::new(sender)
+    });
+    let _ = sender.lock().unwrap().send(job);
+}
+
+/// Block until every hook fired so far has run, returns how any failed
+pub fn wait_for_hooks() -> Vec<String> {
+    if QUEUE.get().is_some() {
+        let (done, finished) = mpsc::channel();
+        queue(Box::new(move || {
+            let _ = done.send(());
+        }));
+        let _ = finished.recv();
+    }
+    take_failures()
+}
+
+/// How hooks failed since the last call, without waiting on running ones
+pub fn take_failures() -> Vec<String> {
+    std::mem::take(&mut *FAILURES.lock().unwrap())
+}
+
+/// Run `command` through the shell with `record` on stdin, killing it
+/// after `timeout`. Errors describe how it failed, with its stderr.
+pub fn run_hook(command: &str, record: &Value, env: &[(&str, String)], timeout: Duration) -> Result<(), String> {
+    let mut child = shell(command)
+        .envs(env.iter().map(|(k, v)| (*k, v.as_str())))
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("can't run '{}': {}", command, e))?;
+
+    // written aside so a hook that doesn't read its input can't block us,
+    // the pipe closes when it exits
+    if let Some(mut stdin) = child.stdin.take() {
+        let input = record.to_string();
+        thread::spawn(move || stdin.write_all(input.as_bytes()));
+    }
+    // and read aside, a hook can't exit while the pipe is full
+    let stderr = child.stderr.take().map(|mut pipe| thread::spawn(move || {
+        let mut stderr = String::new();
+        let _ = pipe.read_to_string(&mut stderr);
+        stderr
+    }));
+
+    let started = Instant::now();
+    let status = loop {
+        match child.try_wait() {
+            Ok(Some(status)) => break status,
+            Ok(None) if started.elapsed() >= timeout => {
+                let _ = child.kill();
+                let _ = child.wait();
+                return Err(format!("'{}' timed out after {} ms", command, timeout.as_millis()));
+            }
+            Ok(None) => thread::sleep(POLL),
+            Err(e) => return Err(format!("'{}': {}", command, e)),
+        }
+    };
+    match status.success() {
+        true => Ok(()),
+        false => {
+            let stderr = stderr.and_then(|t| t.join().ok()).unwrap_or_default();
+            Err(format!("'{}' {}: {}", command, status, stderr.trim()))
+        }
+    }
+}
+
+#[cfg(windows)]
+fn shell(command: &str) -> Command {
+    let mut cmd = Command::new("cmd");
+    cmd.args(["/C", command]);
+    cmd
+}
+
+#[cfg(not(windows))]
+fn shell(command: &str) -> Command {
+    let mut cmd = Command::new("sh");
+    cmd.args(["-c", command]);
+    cmd
+}
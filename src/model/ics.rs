@@ -0,0 +1,179 @@
+// ics.rs
+
+use std::error::Error;
+use std::io::Write;
+use std::str::FromStr;
+use chrono::{DateTime, NaiveDate, Utc};
+use crate::model::clock::Clock;
+use crate::model::task::{EvalPeriod, Task, TaskFilter};
+use crate::model::task_entry::TaskEntry;
+use crate::model::store::TaskStore;
+
+///////////////////////////////////////////////////////////
+
+const PRODID: &str = "-//packrat//packrat//EN";
+
+/// Longest line allowed before folding, in bytes
+const LINE_LIMIT: usize = 75;
+
+/// How entries show up in a calendar
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum IcsKind {
+    #[default]
+    Event,  // a VEVENT at the time of the entry
+    Todo,   // a completed VTODO
+}
+
+impl FromStr for IcsKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "event" | "vevent" => Ok(IcsKind::Event),
+            "todo" | "vtodo" => Ok(IcsKind::Todo),
+            _ => Err(format!("unknown kind '{}', try event or todo", s)),
+        }
+    }
+}
+
+/// The RRULE of a task's period, `Any` doesn't repeat
+pub fn rrule(period: &EvalPeriod) -> Option<&'static str> {
+    match period {
+        EvalPeriod::Any => None,
+        EvalPeriod::Daily => Some("FREQ=DAILY"),
+        EvalPeriod::Weekly => Some("FREQ=WEEKLY"),
+        EvalPeriod::BiWeekly => Some("FREQ=WEEKLY;INTERVAL=2"),
+        EvalPeriod::Monthly => Some("FREQ=MONTHLY"),
+    }
+}
+
+/// Write the entries the filter keeps as an iCalendar. With `recurrence`
+/// each task also gets an all day event repeating every period, starting
+/// with the period it was created in.
+pub fn write_ics<W: Write>(store: &TaskStore, writer: W, filter: &TaskFilter, kind: IcsKind, recurrence: bool)
+    -> Result<(), Box<dyn Error>>
+{
+    let clock = Clock::get();
+    let now = Utc::now();
+    let mut cal = Calendar { writer, stamp: utc(now) };
+
+    cal.line("BEGIN:VCALENDAR")?;
+    cal.line("VERSION:2.0")?;
+    cal.line(&format!("PRODID:{}", PRODID))?;
+    cal.line("CALSCALE:GREGORIAN")?;
+
+    for task in store.get_prefix::<Task>(Task::key_all())? {
+        let entries: Vec<TaskEntry> = store.get_prefix(TaskEntry::key_task(task.id))?;
+        let Some(kept) = filter.select(&task, &entries, clock) else {
+            continue;
+        };
+        if recurrence && !task.archived {
+            cal.reminder(&task, clock)?;
+        }
+        for entry in kept {
+            cal.entry(&task, entry, kind)?;
+        }
+    }
+
+    cal.line("END:VCALENDAR")?;
+    cal.writer.flush()?;
+    Ok(())
+}
+
+struct Calendar<W: Write> {
+    writer: W,
+    stamp: String,  // DTSTAMP of every component
+}
+
+impl<W: Write> Calendar<W> {
+
+    /// Write a content line, folded to the line limit without splitting a
+    /// character
+    fn line(&mut self, line: &str) -> std::io::Result<()> {
+        let mut start = 0;
+        let mut limit = LINE_LIMIT;
+        for (i, c) in line.char_indices() {
+            if i + c.len_utf8() - start > limit {
+                write!(self.writer, "{}\r\n ", &line[start..i])?;
+                start = i;
+                limit = LINE_LIMIT - 1;  // the leading space counts
+            }
+        }
+        write!(self.writer, "{}\r\n", &line[start..])
+    }
+
+    fn text(&mut self, name: &str, value: &str) -> std::io::Result<()> {
+        self.line(&format!("{}:{}", name, escape(value)))
+    }
+
+    fn entry(&mut self, task: &Task, entry: &TaskEntry, kind: IcsKind) -> std::io::Result<()> {
+        let component = match kind {
+            IcsKind::Event => "VEVENT",
+            IcsKind::Todo => "VTODO",
+        };
+        self.line(&format!("BEGIN:{}", component))?;
+        self.line(&format!("UID:{}@packrat", entry.id))?;
+        self.line(&format!("DTSTAMP:{}", self.stamp))?;
+        match kind {
+            IcsKind::Event => self.line(&format!("DTSTART:{}", utc(entry.timestamp)))?,
+            IcsKind::Todo => {
+                self.line("STATUS:COMPLETED")?;
+                self.line(&format!("COMPLETED:{}", utc(entry.timestamp)))?;
+            }
+        }
+        self.text("SUMMARY", &task.name)?;
+        let content = entry.get_content();
+        if !content.trim().is_empty() {
+            self.text("DESCRIPTION", &content)?;
+        }
+        self.categories(task)?;
+        self.line(&format!("END:{}", component))
+    }
+
+    fn reminder(&mut self, task: &Task, clock: &Clock) -> std::io::Result<()> {
+        let created = clock.day_of(task.timestamp);
+        let (Some(rule), Some(start)) = (rrule(&task.eval_period), task.eval_period.start_of(created)) else {
+            return Ok(());
+        };
+        self.line("BEGIN:VEVENT")?;
+        self.line(&format!("UID:{}-reminder@packrat", task.id))?;
+        self.line(&format!("DTSTAMP:{}", self.stamp))?;
+        self.line(&format!("DTSTART;VALUE=DATE:{}", date(start)))?;
+        self.line(&format!("RRULE:{}", rule))?;
+        self.text("SUMMARY", &task.name)?;
+        let goal = format!("{} per {}", task.target, task.eval_period.unit());
+        let desc = match task.desc.trim().is_empty() {
+            true => goal,
+            false => format!("{}\n{}", task.desc, goal),
+        };
+        self.text("DESCRIPTION", &desc)?;
+        self.categories(task)?;
+        self.line("TRANSP:TRANSPARENT")?;
+        self.line("END:VEVENT")
+    }
+
+    fn categories(&mut self, task: &Task) -> std::io::Result<()> {
+        if task.tags.is_empty() {
+            return Ok(());
+        }
+        let tags: Vec<String> = task.tags.iter().map(|t| escape(t)).collect();
+        self.line(&format!("CATEGORIES:{}", tags.join(",")))
+    }
+}
+
+/// Escape a TEXT value
+pub fn escape(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace("\r\n", "\\n")
+        .replace('\n', "\\n")
+}
+
+fn utc(ts: DateTime<Utc>) -> String {
+    ts.format("%Y%m%dT%H%M%SZ").to_string()
+}
+
+fn date(day: NaiveDate) -> String {
+    day.format("%Y%m%d").to_string()
+}
@@ -0,0 +1,128 @@
+// generic.rs
+
+use std::io::Read;
+use chrono::{DateTime, NaiveDate, NaiveDateTime, NaiveTime, Utc};
+use crate::model::clock::Clock;
+use crate::model::dump::Dump;
+use crate::model::import::{csv_error, day_time, find_column, ColumnMap, ImportError, TaskSet};
+use crate::model::store::TaskStore;
+use crate::model::task::EvalPeriod;
+
+///////////////////////////////////////////////////////////
+
+/// Date and time layouts tried when the map doesn't give one
+const DATE_FORMATS: [&str; 3] = ["%Y-%m-%d", "%Y/%m/%d", "%d.%m.%Y"];
+const DATETIME_FORMATS: [&str; 4] = [
+    "%Y-%m-%d %H:%M:%S", "%Y-%m-%d %H:%M", "%Y-%m-%dT%H:%M:%S", "%Y-%m-%dT%H:%M",
+];
+
+/// Cells of a wide sheet that mean the habit wasn't done
+const NOT_DONE: [&str; 6] = ["0", "no", "n", "false", "-", "✗"];
+/// Cells of a wide sheet that mean done without saying more
+const DONE: [&str; 6] = ["1", "yes", "y", "true", "x", "✓"];
+
+/// Read a spreadsheet of check-ins. With a task column each row is one
+/// check-in (long). Without one every column besides the date and time is a
+/// habit and each row a day (wide), ticks like `x` or `yes` log an empty
+/// entry and anything else is logged as the content.
+pub fn read<R: Read>(store: &TaskStore, reader: R, map: &ColumnMap, clock: &Clock)
+    -> Result<Dump, ImportError>
+{
+    let bad = csv_error("CSV");
+    let mut reader = csv::ReaderBuilder::new().flexible(true).from_reader(reader);
+    let headers = reader.headers().map_err(&bad)?.clone();
+
+    // a mapped column must exist, an unmapped one may
+    let column = |mapped: &Option<String>, defaults: &[&str]| match mapped {
+        Some(name) => find_column(&headers, &[name.as_str()])
+            .map(Some)
+            .ok_or_else(|| ImportError(format!("there is no column '{}'", name))),
+        None => Ok(find_column(&headers, defaults)),
+    };
+    let date = column(&map.date, &["date", "day"])?
+        .ok_or_else(|| ImportError(String::from("no date column, name it with --map date=<column>")))?;
+    let time = column(&map.time, &["time"])?;
+    let task = column(&map.task, &["task", "habit"])?;
+    let content = column(&map.content, &["content", "value", "note", "notes"])?;
+    let period = column(&map.period, &["period", "frequency"])?;
+
+    let mut tasks = TaskSet::new(store, "generic-csv")?;
+    for (n, record) in reader.records().enumerate() {
+        let record = record.map_err(&bad)?;
+        let get = |col: Option<usize>| col.and_then(|c| record.get(c)).unwrap_or("").trim();
+        let row = n + 2;  // counting the header, as a spreadsheet does
+
+        if get(Some(date)).is_empty() {
+            continue;
+        }
+        let timestamp = parse_timestamp(get(Some(date)), get(time), map.date_format.as_deref(), clock)
+            .ok_or_else(|| ImportError(format!("row {}, can't read the date '{}'", row, get(Some(date)))))?;
+
+        match task {
+            Some(task) => {
+                let name = get(Some(task));
+                if name.is_empty() {
+                    continue;
+                }
+                let eval_period = match get(period) {
+                    "" => None,
+                    p => Some(p.parse::<EvalPeriod>().map_err(|e| ImportError(format!("row {}, {}", row, e)))?),
+                };
+                tasks.get_or_add(name, |task| {
+                    if let Some(period) = eval_period {
+                        task.eval_period = period;
+                    }
+                });
+                tasks.check_in(name, timestamp, get(content));
+            }
+            None => {
+                for (i, name) in headers.iter().enumerate() {
+                    if i == date || Some(i) == time || name.trim().is_empty() {
+                        continue;
+                    }
+                    let cell = get(Some(i));
+                    if cell.is_empty() || NOT_DONE.iter().any(|v| cell.eq_ignore_ascii_case(v)) {
+                        continue;
+                    }
+                    let cell = match DONE.iter().any(|v| cell.eq_ignore_ascii_case(v)) {
+                        true => "",
+                        false => cell,
+                    };
+                    tasks.check_in(name.trim(), timestamp, cell);
+                }
+            }
+        }
+    }
+    Ok(tasks.into_dump())
+}
+
+/// A date, maybe with a time, maybe with the time in its own column.
+/// Times are wall clock times in the user's zone unless they carry an offset.
+fn parse_timestamp(date: &str, time: &str, format: Option<&str>, clock: &Clock)
+    -> Option<DateTime<Utc>>
+{
+    let at = |day: NaiveDate| match parse_time(time) {
+        Some(t) => clock.to_utc(day.and_time(t)),
+        None => day_time(clock, day),
+    };
+
+    if let Some(format) = format {
+        if let Ok(dt) = NaiveDateTime::parse_from_str(date, format) {
+            return Some(clock.to_utc(dt));
+        }
+        return NaiveDate::parse_from_str(date, format).ok().map(at);
+    }
+    if let Ok(dt) = DateTime::parse_from_rfc3339(date) {
+        return Some(dt.with_timezone(&Utc));
+    }
+    if let Some(dt) = DATETIME_FORMATS.iter().find_map(|f| NaiveDateTime::parse_from_str(date, f).ok()) {
+        return Some(clock.to_utc(dt));
+    }
+    DATE_FORMATS.iter()
+        .find_map(|f| NaiveDate::parse_from_str(date, f).ok())
+        .map(at)
+}
+
+fn parse_time(time: &str) -> Option<NaiveTime> {
+    ["%H:%M:%S", "%H:%M"].iter().find_map(|f| NaiveTime::parse_from_str(time, f).ok())
+}
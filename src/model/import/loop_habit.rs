@@ -0,0 +1,124 @@
+// loop_habit.rs
+
+use std::io::Read;
+use chrono::NaiveDate;
+use crate::model::clock::Clock;
+use crate::model::dump::Dump;
+use crate::model::import::{csv_error, day_time, find_column, ImportError, TaskSet};
+use crate::model::store::TaskStore;
+use crate::model::task::{EvalPeriod, TaskTy};
+
+///////////////////////////////////////////////////////////
+
+/// The files of a Loop Habit Tracker export that matter here
+pub const HABITS_FILE: &str = "Habits.csv";
+pub const CHECKMARKS_FILE: &str = "Checkmarks.csv";
+
+/// Checkmark of a yes/no habit ticked by hand, the other values are
+/// unknown (-1), no (0), implied by the frequency (1) and skipped (3)
+const YES_MANUAL: i64 = 2;
+
+/// Numeric habits store their amounts in thousandths
+const NUMERIC_SCALE: f64 = 1000.0;
+
+/// Read a Loop export. `Habits.csv` describes the habits, when it's missing
+/// they are taken to be daily yes/no habits. `Checkmarks.csv` has a row per
+/// day and a column per habit.
+pub fn read<H: Read, C: Read>(store: &TaskStore, habits: Option<H>, checkmarks: C, clock: &Clock)
+    -> Result<Dump, ImportError>
+{
+    let mut tasks = TaskSet::new(store, "loop")?;
+    if let Some(habits) = habits {
+        read_habits(&mut tasks, habits)?;
+    }
+
+    let bad = csv_error(CHECKMARKS_FILE);
+    let mut reader = csv::ReaderBuilder::new().flexible(true).from_reader(checkmarks);
+    // the rows end in a comma, so the last header is empty
+    let names: Vec<String> = reader.headers().map_err(&bad)?.iter()
+        .map(|h| h.trim().to_string())
+        .collect();
+
+    for record in reader.records() {
+        let record = record.map_err(&bad)?;
+        let date = record.get(0).unwrap_or("");
+        let day = NaiveDate::parse_from_str(date.trim(), "%Y-%m-%d")
+            .map_err(|_| ImportError(format!("bad {}, '{}' is not a date", CHECKMARKS_FILE, date)))?;
+
+        for (name, cell) in names.iter().zip(record.iter()).skip(1) {
+            let Ok(value) = cell.trim().parse::<i64>() else {
+                continue;
+            };
+            if name.is_empty() {
+                continue;
+            }
+            let numeric = tasks.get_or_add(name, |_| {}).task.task_type == TaskTy::Numeric;
+            match numeric {
+                true if value > 0 => {
+                    let amount = value as f64 / NUMERIC_SCALE;
+                    tasks.check_in(name, day_time(clock, day), &amount.to_string());
+                }
+                false if value == YES_MANUAL => tasks.check_in(name, day_time(clock, day), ""),
+                _ => {}
+            }
+        }
+    }
+    Ok(tasks.into_dump())
+}
+
+/// Set up a task for each habit. Older exports name the columns
+/// NumRepetitions and Interval, newer ones FrequencyNumerator and
+/// FrequencyDenominator and add Type, Unit and Archived?.
+fn read_habits<R: Read>(tasks: &mut TaskSet, habits: R) -> Result<(), ImportError> {
+    let bad = csv_error(HABITS_FILE);
+    let mut reader = csv::ReaderBuilder::new().flexible(true).from_reader(habits);
+    let headers = reader.headers().map_err(&bad)?.clone();
+
+    let name_col = find_column(&headers, &["Name"])
+        .ok_or_else(|| ImportError(format!("bad {}, there is no Name column", HABITS_FILE)))?;
+    let question = find_column(&headers, &["Question"]);
+    let description = find_column(&headers, &["Description"]);
+    let numerator = find_column(&headers, &["FrequencyNumerator", "NumRepetitions"]);
+    let denominator = find_column(&headers, &["FrequencyDenominator", "Interval"]);
+    let ty = find_column(&headers, &["Type"]);
+    let archived = find_column(&headers, &["Archived?", "Archived"]);
+
+    for record in reader.records() {
+        let record = record.map_err(&bad)?;
+        let get = |col: Option<usize>| col.and_then(|c| record.get(c)).unwrap_or("").trim();
+        let name = get(Some(name_col));
+        if name.is_empty() {
+            continue;
+        }
+        let number = |col| get(col).parse::<u32>().ok().filter(|n| *n > 0);
+        let (period, target) = period_of(number(numerator).unwrap_or(1), number(denominator).unwrap_or(1));
+
+        tasks.get_or_add(name, |task| {
+            task.desc = [get(question), get(description)]
+                .into_iter()
+                .find(|d| !d.is_empty())
+                .unwrap_or("")
+                .to_string();
+            task.eval_period = period;
+            task.target = target;
+            if matches!(get(ty), "1" | "NUMERICAL") {
+                task.task_type = TaskTy::Numeric;
+            }
+            task.archived = get(archived).eq_ignore_ascii_case("true");
+        });
+    }
+    Ok(())
+}
+
+/// Loop counts `times` check-ins every `days` days, fit that onto the
+/// nearest period and scale the target to it
+pub fn period_of(times: u32, days: u32) -> (EvalPeriod, u32) {
+    let (period, length) = match days {
+        0..=1 => (EvalPeriod::Daily, 1),
+        2..=7 => (EvalPeriod::Weekly, 7),
+        8..=14 => (EvalPeriod::BiWeekly, 14),
+        _ => (EvalPeriod::Monthly, 30),
+    };
+    let target = (times as f64 * length as f64 / days.max(1) as f64).round() as u32;
+    (period, target.max(1))
+}
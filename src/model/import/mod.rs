@@ -0,0 +1,399 @@
+// import/mod.rs
+
+pub mod native;
+pub mod loop_habit;
+pub mod todotxt;
+pub mod generic;
+
+use std::collections::{BTreeMap, HashSet};
+use std::error::Error;
+use std::fmt;
+use std::fs::File;
+use std::io::BufReader;
+use std::path::Path;
+use std::str::FromStr;
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use uuid::Uuid;
+use crate::model::clock::Clock;
+use crate::model::convert::Storable;
+use crate::model::dump::{Dump, EntryDump, TaskDump};
+use crate::model::store::{StoreError, TaskStore, WriteBatch};
+use crate::model::task::Task;
+
+///////////////////////////////////////////////////////////
+
+/// What to do with an imported item whose ID is already in the store
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum MergePolicy {
+    #[default]
+    Skip,       // keep what is stored
+    Overwrite,  // take the imported item
+    Newest,     // keep whichever was modified last
+}
+
+impl FromStr for MergePolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "skip" => Ok(MergePolicy::Skip),
+            "overwrite" => Ok(MergePolicy::Overwrite),
+            "newest" => Ok(MergePolicy::Newest),
+            _ => Err(format!("unknown policy '{}', try skip, overwrite or newest", s)),
+        }
+    }
+}
+
+/// The kinds of file `packrat import` reads
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Format {
+    Json,       // `packrat export json`
+    Csv,        // `packrat export csv`
+    Loop,       // an unzipped Loop Habit Tracker export
+    TodoTxt,    // todo.txt, completed lines are check-ins
+    GenericCsv, // a spreadsheet, see `ColumnMap`
+}
+
+impl FromStr for Format {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "json" => Ok(Format::Json),
+            "csv" => Ok(Format::Csv),
+            "loop" => Ok(Format::Loop),
+            "todotxt" | "todo.txt" => Ok(Format::TodoTxt),
+            "generic-csv" => Ok(Format::GenericCsv),
+            _ => Err(format!(
+                "unknown format '{}', try json, csv, loop, todotxt or generic-csv", s
+            )),
+        }
+    }
+}
+
+impl Format {
+
+    /// Guess the format from the file extension, a directory is taken to be
+    /// an unzipped Loop export
+    pub fn of_path(path: &Path) -> Option<Self> {
+        if path.is_dir() {
+            return Some(Format::Loop);
+        }
+        match path.extension()?.to_str()? {
+            "txt" => Some(Format::TodoTxt),
+            ext => Format::from_str(ext).ok(),
+        }
+    }
+}
+
+/// A file that can't be imported, nothing was written
+#[derive(Debug, PartialEq)]
+pub struct ImportError(pub String);
+
+impl fmt::Display for ImportError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "can't import: {}", self.0)
+    }
+}
+
+impl Error for ImportError {}
+
+/// What an import did, or would do, with one kind of item
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Counts {
+    pub added: usize,
+    pub updated: usize,
+    pub skipped: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct ImportSummary {
+    pub tasks: Counts,
+    pub entries: Counts,
+    pub pauses: Counts,
+    pub routines: Counts,
+}
+
+impl ImportSummary {
+
+    /// Does the import change anything
+    pub fn changes(&self) -> usize {
+        [self.tasks, self.entries, self.pauses, self.routines]
+            .iter()
+            .map(|c| c.added + c.updated)
+            .sum()
+    }
+}
+
+impl fmt::Display for Counts {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} added, {} updated, {} skipped", self.added, self.updated, self.skipped)
+    }
+}
+
+impl fmt::Display for ImportSummary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "tasks:    {}", self.tasks)?;
+        write!(f, "entries:  {}", self.entries)?;
+        // most files have neither
+        if self.pauses != Counts::default() {
+            write!(f, "\npauses:   {}", self.pauses)?;
+        }
+        if self.routines != Counts::default() {
+            write!(f, "\nroutines: {}", self.routines)?;
+        }
+        Ok(())
+    }
+}
+
+/// Which columns of a generic CSV hold what, given as
+/// `date=Day,task=Habit,content=Amount`. Unmapped fields fall back to a
+/// column of the same name.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct ColumnMap {
+    pub date: Option<String>,
+    pub time: Option<String>,
+    pub task: Option<String>,
+    pub content: Option<String>,
+    pub period: Option<String>,
+    pub date_format: Option<String>,  // chrono format of the date column
+}
+
+impl FromStr for ColumnMap {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut map = ColumnMap::default();
+        for pair in s.split(',').map(str::trim).filter(|p| !p.is_empty()) {
+            let Some((field, column)) = pair.split_once('=') else {
+                return Err(format!("'{}' should look like field=column", pair));
+            };
+            let slot = match field.trim().to_lowercase().as_str() {
+                "date" => &mut map.date,
+                "time" => &mut map.time,
+                "task" => &mut map.task,
+                "content" => &mut map.content,
+                "period" => &mut map.period,
+                _ => return Err(format!(
+                    "unknown field '{}', try date, time, task, content or period", field
+                )),
+            };
+            *slot = Some(column.trim().to_string());
+        }
+        Ok(map)
+    }
+}
+
+/// Read a file into a dump, ready for `apply`. The map is only used by
+/// generic CSVs.
+pub fn read_file(store: &TaskStore, path: &Path, format: Format, map: &ColumnMap)
+    -> Result<Dump, Box<dyn Error>>
+{
+    let open = |path: &Path| File::open(path)
+        .map(BufReader::new)
+        .map_err(|e| ImportError(format!("{}: {}", path.display(), e)));
+    let clock = Clock::get();
+    let dump = match format {
+        Format::Json => native::read_json(open(path)?)?,
+        Format::Csv => native::read_csv(store, open(path)?)?,
+        Format::Loop => {
+            // the folder of the export, or one of the files in it
+            let dir = match path.is_dir() {
+                true => path,
+                false => path.parent().unwrap_or(Path::new(".")),
+            };
+            let habits = dir.join(loop_habit::HABITS_FILE);
+            let habits = match habits.exists() {
+                true => Some(open(&habits)?),
+                false => None,
+            };
+            let checkmarks = open(&dir.join(loop_habit::CHECKMARKS_FILE))?;
+            loop_habit::read(store, habits, checkmarks, clock)?
+        }
+        Format::TodoTxt => todotxt::read(store, open(path)?, clock)?,
+        Format::GenericCsv => generic::read(store, open(path)?, map, clock)?,
+    };
+    Ok(dump)
+}
+
+/// The tasks of an import, matched by name with the stored ones
+pub struct TaskSet {
+    source: &'static str,
+    existing: Vec<Task>,
+    tasks: Vec<TaskDump>,
+}
+
+impl TaskSet {
+
+    pub fn new(store: &TaskStore, source: &'static str) -> Result<Self, ImportError> {
+        let existing = store.get_prefix(Task::key_all())
+            .map_err(|e| ImportError(e.to_string()))?;
+        Ok(TaskSet { source, existing, tasks: vec![] })
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.tasks.iter().any(|t| t.task.name.eq_ignore_ascii_case(name))
+    }
+
+    /// The task named `name`, the stored one if there is one, else a new
+    /// one set up by `init`. New tasks get an ID derived from the name so
+    /// importing the same file twice finds them again.
+    pub fn get_or_add(&mut self, name: &str, init: impl FnOnce(&mut Task)) -> &mut TaskDump {
+        let i = match self.tasks.iter().position(|t| t.task.name.eq_ignore_ascii_case(name)) {
+            Some(i) => i,
+            None => {
+                let task = match self.existing.iter().find(|t| t.name.eq_ignore_ascii_case(name)) {
+                    Some(task) => task.clone(),
+                    None => {
+                        let mut task = Task::new(name, "");
+                        task.id = stable_id(&[self.source, "task", &name.to_lowercase()]);
+                        init(&mut task);
+                        task
+                    }
+                };
+                self.tasks.push(TaskDump { task, entries: vec![] });
+                self.tasks.len() - 1
+            }
+        };
+        &mut self.tasks[i]
+    }
+
+    /// Add a check-in, its ID is derived from what it records so a second
+    /// import of the same file skips it
+    pub fn check_in(&mut self, name: &str, timestamp: DateTime<Utc>, content: &str) {
+        let source = self.source;
+        let task_id = self.get_or_add(name, |_| {}).task.id;
+        let id = stable_id(&[source, &task_id.to_string(), &timestamp.to_rfc3339(), content]);
+        self.check_in_as(name, id, timestamp, content);
+    }
+
+    /// Add a check-in under an ID the caller derived
+    pub fn check_in_as(&mut self, name: &str, id: Uuid, timestamp: DateTime<Utc>, content: &str) {
+        let task = self.get_or_add(name, |_| {});
+        task.entries.push(EntryDump { id, timestamp, content: content.to_string() });
+    }
+
+    pub fn into_dump(self) -> Dump {
+        Dump { tasks: self.tasks, ..Dump::default() }
+    }
+}
+
+/// A UUID that is the same for the same parts, FNV-1a over them
+pub fn stable_id(parts: &[&str]) -> Uuid {
+    const OFFSET: u128 = 0x6c62272e07bb014262b821756295c58d;
+    const PRIME: u128 = 0x0000000001000000000000000000013b;
+    let mut hash = OFFSET;
+    for part in parts {
+        // a separator keeps ["ab", "c"] apart from ["a", "bc"]
+        for byte in part.bytes().chain([0xff]) {
+            hash ^= byte as u128;
+            hash = hash.wrapping_mul(PRIME);
+        }
+    }
+    uuid::Builder::from_custom_bytes(hash.to_be_bytes()).into_uuid()
+}
+
+/// The time given to a check-in that only has a date, midway through the
+/// user's day so it counts towards that day whatever the rollover hour
+pub fn day_time(clock: &Clock, day: NaiveDate) -> DateTime<Utc> {
+    clock.start_of_day(day) + Duration::hours(12)
+}
+
+/// Merge a dump into the store in one batch, so either all of it lands or
+/// none of it does. A dry run works out the summary without writing.
+pub fn apply(store: &TaskStore, dump: &Dump, policy: MergePolicy, dry_run: bool)
+    -> Result<ImportSummary, StoreError>
+{
+    let mut merge = Merge {
+        store,
+        policy,
+        batch: WriteBatch::default(),
+        seen: HashSet::new(),
+        theirs: &dump.modified,
+        ours: match policy {
+            MergePolicy::Newest => store.modified("")?,
+            _ => BTreeMap::new(),
+        },
+    };
+    let mut summary = ImportSummary::default();
+
+    for task in &dump.tasks {
+        merge.item(&task.task, &mut summary.tasks)?;
+        for entry in &task.entries {
+            merge.item(&entry.to_entry(task.task.id), &mut summary.entries)?;
+        }
+    }
+    for pause in &dump.pauses {
+        merge.item(pause, &mut summary.pauses)?;
+    }
+    for routine in &dump.routines {
+        merge.item(routine, &mut summary.routines)?;
+    }
+
+    if !dry_run && !merge.batch.is_empty() {
+        store.commit(merge.batch)?;
+    }
+    Ok(summary)
+}
+
+/// The writes of an import as they are decided on
+struct Merge<'a> {
+    store: &'a TaskStore,
+    policy: MergePolicy,
+    batch: WriteBatch,
+    seen: HashSet<String>,  // keys already decided, a file may repeat an ID
+    theirs: &'a BTreeMap<String, DateTime<Utc>>,    // modification times in the dump
+    ours: BTreeMap<String, DateTime<Utc>>,          // and in the store, for `Newest`
+}
+
+impl Merge<'_> {
+
+    fn item<T: Storable + PartialEq>(&mut self, item: &T, counts: &mut Counts) -> Result<(), StoreError> {
+        let key = item.to_key();
+        if !self.seen.insert(key.clone()) {
+            counts.skipped += 1;
+            return Ok(());
+        }
+
+        let write = match self.store.get::<T>(key.clone())? {
+            None => {
+                counts.added += 1;
+                true
+            }
+            Some(old) => {
+                let take = match self.policy {
+                    MergePolicy::Skip => false,
+                    MergePolicy::Overwrite => old != *item,
+                    MergePolicy::Newest => modified_at(self.theirs, &key, item) > modified_at(&self.ours, &key, &old),
+                };
+                match take {
+                    true => counts.updated += 1,
+                    false => counts.skipped += 1,
+                }
+                take
+            }
+        };
+        if write {
+            self.store.stage(&mut self.batch, item)?;
+        }
+        Ok(())
+    }
+}
+
+/// When an item was last changed, by its stamp or else by its own time for
+/// files and stores from before stamps were kept
+fn modified_at<T: Storable>(stamps: &BTreeMap<String, DateTime<Utc>>, key: &str, item: &T) -> DateTime<Utc> {
+    stamps.get(key).copied().unwrap_or_else(|| item.get_datetime())
+}
+
+/// Index of the first of `names` among the headers, ignoring case
+pub fn find_column(headers: &csv::StringRecord, names: &[&str]) -> Option<usize> {
+    names.iter().find_map(|name| {
+        headers.iter().position(|h| h.trim().eq_ignore_ascii_case(name))
+    })
+}
+
+/// Wrap a CSV error with the file it came from
+pub fn csv_error(file: &str) -> impl Fn(csv::Error) -> ImportError + '_ {
+    move |e| ImportError(format!("bad {}, {}", file, e))
+}
@@ -0,0 +1,117 @@
+// native.rs
+
+use std::io::Read;
+use chrono::{DateTime, Duration, Utc};
+use uuid::Uuid;
+use crate::model::dump::{Dump, EntryDump, TaskDump, DUMP_VERSION};
+use crate::model::import::{csv_error, find_column, stable_id, ImportError, TaskSet};
+use crate::model::store::{TaskStore, LONG_COLUMNS};
+use crate::model::task::Task;
+use crate::model::task_entry::TaskEntry;
+
+///////////////////////////////////////////////////////////
+
+/// Read a `packrat export json` file
+pub fn read_json<R: Read>(reader: R) -> Result<Dump, ImportError> {
+    let dump: Dump = serde_json::from_reader(reader)
+        .map_err(|e| ImportError(format!("not a packrat JSON export, {}", e)))?;
+    if dump.version > DUMP_VERSION {
+        return Err(ImportError(format!(
+            "the file is version {}, this packrat reads up to version {}",
+            dump.version, DUMP_VERSION
+        )));
+    }
+    Ok(dump)
+}
+
+/// Read a `packrat export csv` file in either layout
+pub fn read_csv<R: Read>(store: &TaskStore, reader: R) -> Result<Dump, ImportError> {
+    let bad = csv_error("CSV");
+    let mut reader = csv::ReaderBuilder::new().flexible(true).from_reader(reader);
+    let headers = reader.headers().map_err(&bad)?.clone();
+    match LONG_COLUMNS.iter().all(|c| find_column(&headers, &[c]).is_some()) {
+        true => read_long(store, reader, &headers),
+        false => read_wide(store, reader, &headers),
+    }
+}
+
+/// The long layout has everything but the task settings, tasks that aren't
+/// stored yet are made with the defaults
+fn read_long<R: Read>(store: &TaskStore, mut reader: csv::Reader<R>, headers: &csv::StringRecord)
+    -> Result<Dump, ImportError>
+{
+    let bad = csv_error("CSV");
+    let col = |name: &str| find_column(headers, &[name]).expect("checked by read_csv");
+    let (task_id, task_name, entry_id, timestamp, content) =
+        (col("task_id"), col("task_name"), col("entry_id"), col("timestamp"), col("content"));
+
+    let mut tasks: Vec<TaskDump> = vec![];
+    for (n, record) in reader.records().enumerate() {
+        let record = record.map_err(&bad)?;
+        let get = |c: usize| record.get(c).unwrap_or("");
+        let row = n + 2;
+        let uuid = |c: usize| Uuid::parse_str(get(c))
+            .map_err(|_| ImportError(format!("row {}, '{}' is not an ID", row, get(c))));
+
+        let id = uuid(task_id)?;
+        let i = match tasks.iter().position(|t| t.task.id == id) {
+            Some(i) => i,
+            None => {
+                let stored = store.get::<Task>(Task::key_task(id))
+                    .map_err(|e| ImportError(e.to_string()))?;
+                let task = stored.unwrap_or_else(|| Task { id, ..Task::new(get(task_name), "") });
+                tasks.push(TaskDump { task, entries: vec![] });
+                tasks.len() - 1
+            }
+        };
+        let timestamp = DateTime::parse_from_rfc3339(get(timestamp))
+            .map_err(|_| ImportError(format!("row {}, '{}' is not a time", row, get(timestamp))))?;
+        tasks[i].entries.push(EntryDump {
+            id: uuid(entry_id)?,
+            timestamp: timestamp.with_timezone(&Utc),
+            content: get(content).to_string(),
+        });
+    }
+    Ok(Dump { tasks, ..Dump::default() })
+}
+
+/// The wide layout is a column of entry contents per task. Columns go to
+/// the task of the same name, or a new one. There are no IDs or times, so
+/// a cell's ID comes from its column, row and content, and a new one is
+/// stamped with the import time, a second apart to keep the newest first
+/// order of the rows. A cell imported before keeps its time.
+fn read_wide<R: Read>(store: &TaskStore, mut reader: csv::Reader<R>, headers: &csv::StringRecord)
+    -> Result<Dump, ImportError>
+{
+    let bad = csv_error("CSV");
+    let mut tasks = TaskSet::new(store, "csv")?;
+    let names: Vec<String> = headers.iter()
+        .map(|name| name.trim().to_string())
+        .collect();
+    for name in &names {
+        if name.is_empty() {
+            return Err(ImportError(String::from("every column needs a task name")));
+        }
+        if tasks.contains(name) {
+            return Err(ImportError(format!("the column '{}' appears twice", name)));
+        }
+        tasks.get_or_add(name, |_| {});
+    }
+
+    let now = Utc::now();
+    for (row, record) in reader.records().enumerate() {
+        let record = record.map_err(&bad)?;
+        for (name, cell) in names.iter().zip(record.iter()) {
+            if cell.is_empty() {
+                continue;
+            }
+            let task_id = tasks.get_or_add(name, |_| {}).task.id;
+            let id = stable_id(&["csv", name, &row.to_string(), cell]);
+            let timestamp = store.get::<TaskEntry>(TaskEntry::key_task_entry(task_id.to_string(), id.to_string()))
+                .map_err(|e| ImportError(e.to_string()))?
+                .map_or(now - Duration::seconds(row as i64), |entry| entry.timestamp);
+            tasks.check_in_as(name, id, timestamp, cell);
+        }
+    }
+    Ok(tasks.into_dump())
+}
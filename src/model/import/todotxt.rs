@@ -0,0 +1,112 @@
+// todotxt.rs
+
+use std::io::{BufRead, BufReader, Read};
+use chrono::NaiveDate;
+use crate::model::clock::Clock;
+use crate::model::dump::Dump;
+use crate::model::import::{day_time, ImportError, TaskSet};
+use crate::model::store::TaskStore;
+use crate::model::task::{parse_tags, EvalPeriod};
+
+///////////////////////////////////////////////////////////
+
+/// One line of a todo.txt file
+#[derive(Debug, Clone, PartialEq)]
+pub struct TodoLine {
+    pub name: String,               // the words, less projects, contexts and key:values
+    pub done: Option<NaiveDate>,    // completion date of an `x` line
+    pub tags: Vec<String>,          // projects and contexts
+    pub period: Option<EvalPeriod>, // from a `rec:` recurrence
+}
+
+/// Read a todo.txt file. Each distinct description is a task, its
+/// projects and contexts become tags, and every completed line is a
+/// check-in on its completion date.
+pub fn read<R: Read>(store: &TaskStore, reader: R, clock: &Clock) -> Result<Dump, ImportError> {
+    let mut tasks = TaskSet::new(store, "todotxt")?;
+    for (n, line) in BufReader::new(reader).lines().enumerate() {
+        let line = line.map_err(|e| ImportError(format!("line {}, {}", n + 1, e)))?;
+        let Some(todo) = parse_line(&line) else {
+            continue;
+        };
+        tasks.get_or_add(&todo.name, |task| {
+            task.tags = todo.tags.clone();
+            if let Some(period) = &todo.period {
+                task.eval_period = period.clone();
+            }
+        });
+        if let Some(day) = todo.done {
+            tasks.check_in(&todo.name, day_time(clock, day), "");
+        }
+    }
+    Ok(tasks.into_dump())
+}
+
+/// Parse a line, blank lines and lines with only tags give nothing
+pub fn parse_line(line: &str) -> Option<TodoLine> {
+    let date = |word: Option<&&str>| word.and_then(|w| NaiveDate::parse_from_str(w, "%Y-%m-%d").ok());
+    let words: Vec<&str> = line.split_whitespace().collect();
+    let mut rest = &words[..];
+
+    // x COMPLETED [CREATED] ... or (A) [CREATED] ...
+    let mut done = None;
+    if rest.first() == Some(&"x") {
+        done = date(rest.get(1));
+        rest = &rest[1 + done.is_some() as usize..];
+    } else if rest.first().is_some_and(|w| is_priority(w)) {
+        rest = &rest[1..];
+    }
+    if date(rest.first()).is_some() {
+        rest = &rest[1..];
+    }
+
+    let mut name = vec![];
+    let mut tags = vec![];
+    let mut period = None;
+    for word in rest {
+        if let Some(tag) = word.strip_prefix('+').or_else(|| word.strip_prefix('@')) {
+            tags.push(tag);
+        } else if let Some((key, value)) = word.split_once(':').filter(|(k, v)| is_key_value(k, v)) {
+            if key == "rec" {
+                period = parse_recurrence(value);
+            }
+        } else {
+            name.push(*word);
+        }
+    }
+
+    let name = name.join(" ");
+    if name.is_empty() {
+        return None;
+    }
+    Some(TodoLine { name, done, tags: parse_tags(&tags.join(",")), period })
+}
+
+/// `(A)` to `(Z)`
+fn is_priority(word: &str) -> bool {
+    let bytes = word.as_bytes();
+    bytes.len() == 3 && bytes[0] == b'(' && bytes[1].is_ascii_uppercase() && bytes[2] == b')'
+}
+
+/// `due:2026-01-01` but not `https://example.com`
+fn is_key_value(key: &str, value: &str) -> bool {
+    !key.is_empty() && !value.is_empty() && !value.starts_with("//")
+        && key.chars().all(|c| c.is_alphanumeric() || c == '-' || c == '_')
+}
+
+/// The `rec:` extension, e.g. `rec:1d`, `rec:+2w` or `rec:m`
+fn parse_recurrence(value: &str) -> Option<EvalPeriod> {
+    let value = value.trim_start_matches('+');
+    let unit = value.chars().last()?;
+    let n: u32 = match &value[..value.len() - unit.len_utf8()] {
+        "" => 1,
+        n => n.parse().ok()?,
+    };
+    match (n, unit) {
+        (1, 'd') | (1, 'b') => Some(EvalPeriod::Daily),
+        (1, 'w') | (7, 'd') => Some(EvalPeriod::Weekly),
+        (2, 'w') => Some(EvalPeriod::BiWeekly),
+        (1, 'm') => Some(EvalPeriod::Monthly),
+        _ => None,
+    }
+}
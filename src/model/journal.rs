@@ -0,0 +1,113 @@
+// journal.rs
+
+use std::error::Error;
+use std::io::Write;
+use std::str::FromStr;
+use chrono::NaiveDate;
+use crate::model::clock::Clock;
+use crate::model::task::{Task, TaskFilter, TaskTy};
+use crate::model::task_entry::TaskEntry;
+use crate::model::store::TaskStore;
+
+///////////////////////////////////////////////////////////
+
+/// How the journal is split into sections
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum JournalGroup {
+    #[default]
+    Day,    // a section per day, every task's entries in time order
+    Task,   // a section per task, its entries in time order
+}
+
+impl FromStr for JournalGroup {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "day" => Ok(JournalGroup::Day),
+            "task" => Ok(JournalGroup::Task),
+            _ => Err(format!("unknown grouping '{}', try day or task", s)),
+        }
+    }
+}
+
+/// Write the entries the filter keeps as a Markdown journal, oldest first.
+/// Markdown entries go in as they are, JSON and TOML as code blocks and
+/// anything else as a paragraph.
+pub fn write_journal<W: Write>(store: &TaskStore, mut writer: W, filter: &TaskFilter, group: JournalGroup)
+    -> Result<(), Box<dyn Error>>
+{
+    let clock = Clock::get();
+    let mut kept: Vec<(Task, Vec<TaskEntry>)> = vec![];
+    for task in store.get_prefix::<Task>(Task::key_all())? {
+        let entries: Vec<TaskEntry> = store.get_prefix(TaskEntry::key_task(task.id))?;
+        let Some(selected) = filter.select(&task, &entries, clock) else {
+            continue;
+        };
+        let mut selected: Vec<TaskEntry> = selected.into_iter().cloned().collect();
+        selected.reverse();
+        kept.push((task, selected));
+    }
+
+    writeln!(writer, "# Journal")?;
+    match group {
+        JournalGroup::Day => {
+            let mut all: Vec<(&Task, &TaskEntry)> = kept.iter()
+                .flat_map(|(task, entries)| entries.iter().map(move |e| (task, e)))
+                .collect();
+            all.sort_by_key(|(_, e)| e.timestamp);
+
+            let mut current: Option<NaiveDate> = None;
+            for (task, entry) in all {
+                let day = clock.day_of(entry.timestamp);
+                if current != Some(day) {
+                    writeln!(writer, "\n## {}", day.format("%A, %-d %B %Y"))?;
+                    current = Some(day);
+                }
+                let time = clock.format(entry.timestamp, "%H:%M");
+                write_entry(&mut writer, &format!("{} · {}", time, task.name), task, entry)?;
+            }
+        }
+        JournalGroup::Task => {
+            for (task, entries) in kept.iter().filter(|(_, e)| !e.is_empty()) {
+                writeln!(writer, "\n## {}", task.name)?;
+                if !task.desc.trim().is_empty() {
+                    writeln!(writer, "\n_{}_", task.desc.trim())?;
+                }
+                for entry in entries {
+                    let when = clock.format(entry.timestamp, "%Y-%m-%d %H:%M");
+                    write_entry(&mut writer, &when, task, entry)?;
+                }
+            }
+        }
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+fn write_entry<W: Write>(writer: &mut W, heading: &str, task: &Task, entry: &TaskEntry)
+    -> std::io::Result<()>
+{
+    writeln!(writer, "\n### {}", heading)?;
+    let content = entry.get_content();
+    let content = content.trim_end();
+    if content.trim().is_empty() {
+        return Ok(());
+    }
+    match task.task_type {
+        TaskTy::Json => writeln!(writer, "\n```json\n{}\n```", content),
+        TaskTy::Toml => writeln!(writer, "\n```toml\n{}\n```", content),
+        // demote headings so they sit below the entry's own
+        TaskTy::Markdown => {
+            writeln!(writer)?;
+            for line in content.lines() {
+                match line.starts_with('#') {
+                    true => writeln!(writer, "###{}", line)?,
+                    false => writeln!(writer, "{}", line)?,
+                }
+            }
+            Ok(())
+        }
+        _ => writeln!(writer, "\n{}", content),
+    }
+}
@@ -0,0 +1,472 @@
+// mirror.rs
+
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use crate::model::convert::Storable;
+use crate::model::import::stable_id;
+use crate::model::store::{TaskStore, WriteBatch};
+use crate::model::task::Task;
+use crate::model::task_entry::TaskEntry;
+
+///////////////////////////////////////////////////////////
+
+/// What each file looked like when last written or read, kept in the mirror
+pub const MANIFEST: &str = ".packrat-mirror.toml";
+
+/// The file of a task, next to the files of its entries
+pub const TASK_FILE: &str = "task.toml";
+
+/// Fences around the front matter of an entry file
+const FENCE: &str = "+++";
+
+/// Who wins when a file and its record both changed since the last sync
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum ConflictPolicy {
+    #[default]
+    Report,     // leave both alone and list the file
+    Store,      // the store's version overwrites the file
+    Files,      // the file's version goes into the store
+}
+
+impl FromStr for ConflictPolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "report" => Ok(ConflictPolicy::Report),
+            "store" => Ok(ConflictPolicy::Store),
+            "files" => Ok(ConflictPolicy::Files),
+            _ => Err(format!("unknown policy '{}', try report, store or files", s)),
+        }
+    }
+}
+
+/// A directory holding a plain text copy of the store: a folder per task
+/// with its `task.toml`, and a Markdown file per entry with TOML front
+/// matter. Edits made to the files are read back in, see `sync`.
+#[derive(Debug, Clone)]
+pub struct Mirror {
+    pub dir: PathBuf,
+    pub policy: ConflictPolicy,
+}
+
+/// What a sync did, or would do on a dry run
+#[derive(Debug, Default, PartialEq)]
+pub struct MirrorReport {
+    pub ingested: usize,            // records created or changed from files
+    pub deleted: usize,             // records whose file was removed
+    pub written: usize,             // files created or rewritten
+    pub removed: usize,             // files of records no longer stored
+    pub conflicts: BTreeSet<String>,
+    pub errors: Vec<String>,        // files that couldn't be read back
+    taken: BTreeSet<String>,        // files `ingest` read in or deleted by
+}
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+struct Manifest {
+    #[serde(default)]
+    files: BTreeMap<String, Tracked>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+struct Tracked {
+    id: Uuid,
+    hash: String,
+}
+
+/// The front matter of an entry file
+#[derive(Serialize, Deserialize, Debug)]
+struct EntryMeta {
+    id: Option<Uuid>,
+    task_id: Option<Uuid>,
+    timestamp: DateTime<Utc>,
+}
+
+/// A record read back from a file
+enum Parsed {
+    Task(Task),
+    Entry(TaskEntry),
+}
+
+///////////////////////////////////////////////////////////
+
+impl fmt::Display for MirrorReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "from files: {} changed, {} deleted", self.ingested, self.deleted)?;
+        write!(f, "to files:   {} written, {} removed", self.written, self.removed)?;
+        for path in &self.conflicts {
+            write!(f, "\nconflict:   {}", path)?;
+        }
+        for error in &self.errors {
+            write!(f, "\nerror:      {}", error)?;
+        }
+        Ok(())
+    }
+}
+
+impl Parsed {
+    fn id(&self) -> Uuid {
+        match self {
+            Parsed::Task(task) => task.id,
+            Parsed::Entry(entry) => entry.id,
+        }
+    }
+}
+
+impl Mirror {
+
+    pub fn new(dir: impl Into<PathBuf>, policy: ConflictPolicy) -> Self {
+        Mirror { dir: dir.into(), policy }
+    }
+
+    /// Read edited files into the store, then bring the files up to date
+    /// with it. Nothing is changed on a dry run.
+    pub fn sync(&self, store: &TaskStore, dry_run: bool) -> Result<MirrorReport, Box<dyn Error>> {
+        let mut report = MirrorReport::default();
+        self.ingest(store, dry_run, &mut report)?;
+        self.write(store, dry_run, &mut report)?;
+        Ok(report)
+    }
+
+    /// Take files changed since the last sync into the store, and delete
+    /// the records of removed ones. Records changed in the store as well
+    /// are conflicts, settled by the policy.
+    pub fn ingest(&self, store: &TaskStore, dry_run: bool, report: &mut MirrorReport)
+        -> Result<(), Box<dyn Error>>
+    {
+        let manifest = self.load_manifest()?;
+        let files = self.read_files()?;
+        let rendered = render(store)?;
+        let by_id: HashMap<Uuid, &String> = rendered.values().map(|(id, text)| (*id, text)).collect();
+
+        let mut batch = WriteBatch::default();
+        let mut present: HashSet<Uuid> = HashSet::new();
+        let mut settled: Vec<&String> = vec![];
+        // tasks come first so a new task's entries can go in with it
+        let mut new_tasks: HashSet<Uuid> = HashSet::new();
+        let (tasks, entries): (Vec<_>, Vec<_>) = files.iter().partition(|(p, _)| p.ends_with(TASK_FILE));
+        for (path, text) in tasks.into_iter().chain(entries) {
+            let tracked = manifest.files.get(path);
+            if let Some(tracked) = tracked.filter(|t| t.hash == digest(text)) {
+                present.insert(tracked.id);
+                continue;
+            }
+            let parsed = match parse(path, text, &files) {
+                Ok(parsed) => parsed,
+                Err(e) => {
+                    report.errors.push(format!("{}: {}", path, e));
+                    if let Some(tracked) = tracked {
+                        present.insert(tracked.id);
+                    }
+                    continue;
+                }
+            };
+            present.insert(parsed.id());
+            let current = by_id.get(&parsed.id());
+            if current.is_some_and(|c| *c == text) {
+                settled.push(path);
+                continue;
+            }
+
+            // a record changed in the store since the file was written
+            let store_changed = match (tracked, current) {
+                (Some(t), Some(c)) => digest(c) != t.hash,
+                (None, None) => false,
+                _ => true,
+            };
+            if store_changed && !self.take_file(path, report) {
+                continue;
+            }
+            match parsed {
+                Parsed::Task(task) => {
+                    let stored = store.get::<Task>(Task::key_task(task.id))?;
+                    if stored.is_some_and(|s| !s.is_legal_update_from(&task)) {
+                        report.errors.push(format!("{}: the id and timestamp of a task can't change", path));
+                        continue;
+                    }
+                    store.stage(&mut batch, &task)?;
+                    new_tasks.insert(task.id);
+                }
+                Parsed::Entry(entry) => {
+                    let known = new_tasks.contains(&entry.task_id)
+                        || store.get::<Task>(Task::key_task(entry.task_id))?.is_some();
+                    if !known {
+                        report.errors.push(format!("{}: no task {}", path, entry.task_id));
+                        continue;
+                    }
+                    // moved to another task, its key changes with it
+                    if let Ok(old) = store.find_entry(&entry.id.to_string()) {
+                        if old.task_id != entry.task_id {
+                            store.stage_delete(&mut batch, &old);
+                        }
+                    }
+                    store.stage(&mut batch, &entry)?;
+                }
+            }
+            settled.push(path);
+            report.taken.insert(path.clone());
+            report.ingested += 1;
+        }
+
+        // files gone since the last sync take their records with them
+        let mut gone_tasks: Vec<Task> = vec![];
+        let mut gone_entries: Vec<TaskEntry> = vec![];
+        for (path, tracked) in &manifest.files {
+            if files.contains_key(path) || present.contains(&tracked.id) {
+                continue;
+            }
+            let Some(current) = by_id.get(&tracked.id) else {
+                continue;
+            };
+            if digest(current) != tracked.hash && !self.take_file(path, report) {
+                continue;
+            }
+            if let Some(task) = store.get::<Task>(Task::key_task(tracked.id))? {
+                gone_tasks.push(task);
+            } else if let Ok(entry) = store.find_entry(&tracked.id.to_string()) {
+                gone_entries.push(entry);
+            }
+            report.taken.insert(path.clone());
+        }
+        // a task takes all of its entries, removed files or not
+        let cascaded = store.stage_delete_tasks(&mut batch, &gone_tasks, Utc::now())?;
+        gone_entries.retain(|e| !gone_tasks.iter().any(|t| t.id == e.task_id));
+        for entry in &gone_entries {
+            store.stage_delete(&mut batch, entry);
+        }
+        report.deleted += gone_tasks.len() + cascaded.len() + gone_entries.len();
+
+        if !dry_run {
+            store.commit(batch)?;
+            // a file read in under another name than its record's, say one
+            // renamed or with no ID yet, is rewritten at that name
+            let canonical = render(store)?;
+            for path in settled.into_iter().filter(|p| !canonical.contains_key(*p)) {
+                fs::remove_file(self.dir.join(path))?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Write the file of every record whose file is missing or stale, and
+    /// remove the files of records no longer stored. Files edited since the
+    /// last sync are conflicts, unless the policy favours the store.
+    pub fn write(&self, store: &TaskStore, dry_run: bool, report: &mut MirrorReport)
+        -> Result<(), Box<dyn Error>>
+    {
+        let manifest = self.load_manifest()?;
+        let files = self.read_files()?;
+        let rendered = render(store)?;
+        let edited = |path: &String, disk: &String| {
+            manifest.files.get(path).is_none_or(|t| t.hash != digest(disk))
+        };
+
+        // conflicts found by `ingest` stay as they are
+        let mut next = Manifest::default();
+        for (path, tracked) in manifest.files.iter().filter(|(p, _)| report.conflicts.contains(*p)) {
+            next.files.insert(path.clone(), tracked.clone());
+        }
+        // on a dry run the store doesn't have what `ingest` took yet
+        let mut skipped = report.conflicts.clone();
+        if dry_run {
+            skipped.extend(report.taken.iter().cloned());
+        }
+        for (path, (id, text)) in &rendered {
+            if skipped.contains(path) {
+                continue;
+            }
+            match files.get(path) {
+                Some(disk) if disk == text => {}
+                Some(disk) if edited(path, disk) && !self.take_store(path, report) => {
+                    if let Some(tracked) = manifest.files.get(path) {
+                        next.files.insert(path.clone(), tracked.clone());
+                    }
+                    continue;
+                }
+                _ => {
+                    if !dry_run {
+                        let file = self.dir.join(path);
+                        fs::create_dir_all(file.parent().unwrap_or(&self.dir))?;
+                        fs::write(file, text)?;
+                    }
+                    report.written += 1;
+                }
+            }
+            next.files.insert(path.clone(), Tracked { id: *id, hash: digest(text) });
+        }
+
+        for (path, tracked) in &manifest.files {
+            if rendered.contains_key(path) || skipped.contains(path) {
+                continue;
+            }
+            match files.get(path) {
+                None => {}
+                Some(disk) if edited(path, disk) && !self.take_store(path, report) => {
+                    next.files.insert(path.clone(), tracked.clone());
+                }
+                Some(_) => {
+                    if !dry_run {
+                        let file = self.dir.join(path);
+                        fs::remove_file(&file)?;
+                        // the folder of a deleted task
+                        if let Some(parent) = file.parent().filter(|p| *p != self.dir) {
+                            let _ = fs::remove_dir(parent);
+                        }
+                    }
+                    report.removed += 1;
+                }
+            }
+        }
+
+        if !dry_run {
+            fs::create_dir_all(&self.dir)?;
+            fs::write(self.dir.join(MANIFEST), toml::to_string(&next)?)?;
+        }
+        Ok(())
+    }
+
+    /// Should a conflicting file go into the store
+    fn take_file(&self, path: &str, report: &mut MirrorReport) -> bool {
+        match self.policy {
+            ConflictPolicy::Files => true,
+            ConflictPolicy::Store => false,
+            ConflictPolicy::Report => {
+                report.conflicts.insert(path.to_string());
+                false
+            }
+        }
+    }
+
+    /// Should the store's version replace a conflicting file
+    fn take_store(&self, path: &str, report: &mut MirrorReport) -> bool {
+        match self.policy {
+            ConflictPolicy::Store => true,
+            ConflictPolicy::Files | ConflictPolicy::Report => {
+                report.conflicts.insert(path.to_string());
+                false
+            }
+        }
+    }
+
+    fn load_manifest(&self) -> Result<Manifest, Box<dyn Error>> {
+        match fs::read_to_string(self.dir.join(MANIFEST)) {
+            Ok(text) => Ok(toml::from_str(&text).map_err(|e| format!("{}: {}", MANIFEST, e))?),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Manifest::default()),
+            Err(e) => Err(Box::new(e)),
+        }
+    }
+
+    /// The text of every task and entry file, by path relative to the
+    /// mirror. Hidden files are skipped.
+    fn read_files(&self) -> Result<BTreeMap<String, String>, Box<dyn Error>> {
+        let mut files = BTreeMap::new();
+        if !self.dir.exists() {
+            return Ok(files);
+        }
+        for folder in fs::read_dir(&self.dir)? {
+            let folder = folder?.path();
+            if !folder.is_dir() || is_hidden(&folder) {
+                continue;
+            }
+            for file in fs::read_dir(&folder)? {
+                let file = file?.path();
+                let wanted = file.extension().is_some_and(|e| e == "md" || e == "toml");
+                if !file.is_file() || !wanted || is_hidden(&file) {
+                    continue;
+                }
+                let path = file.strip_prefix(&self.dir)?
+                    .components()
+                    .map(|c| c.as_os_str().to_string_lossy())
+                    .collect::<Vec<_>>()
+                    .join("/");
+                files.insert(path, fs::read_to_string(&file)?);
+            }
+        }
+        Ok(files)
+    }
+}
+
+/// The file every task and entry in the store should have, with its ID
+fn render(store: &TaskStore) -> Result<BTreeMap<String, (Uuid, String)>, Box<dyn Error>> {
+    let mut files = BTreeMap::new();
+    for task in store.get_prefix::<Task>(Task::key_all())? {
+        let folder = task_folder(&task);
+        files.insert(format!("{}/{}", folder, TASK_FILE), (task.id, task.to_toml()?));
+        for entry in store.get_prefix::<TaskEntry>(TaskEntry::key_task(task.id))? {
+            let name = format!("{}-{}.md", entry.timestamp.format("%Y-%m-%d_%H%M%S"), short(entry.id));
+            files.insert(format!("{}/{}", folder, name), (entry.id, entry_file(&entry)?));
+        }
+    }
+    Ok(files)
+}
+
+/// An entry as front matter followed by its content
+fn entry_file(entry: &TaskEntry) -> Result<String, Box<dyn Error>> {
+    let meta = EntryMeta { id: Some(entry.id), task_id: Some(entry.task_id), timestamp: entry.timestamp };
+    Ok(format!("{}\n{}{}\n{}", FENCE, toml::to_string(&meta)?, FENCE, entry.get_content()))
+}
+
+/// Read a record back from the file at `path`, an entry file without a
+/// task ID belongs to the task of its folder
+fn parse(path: &str, text: &str, files: &BTreeMap<String, String>) -> Result<Parsed, Box<dyn Error>> {
+    let (folder, name) = path.rsplit_once('/').unwrap_or(("", path));
+    if name == TASK_FILE {
+        return Ok(Parsed::Task(Task::from_toml(text.to_string())?));
+    }
+
+    let text = text.strip_prefix('\u{feff}').unwrap_or(text).replace("\r\n", "\n");
+    let rest = text.strip_prefix(&format!("{}\n", FENCE)).ok_or("no front matter")?;
+    let (meta, content) = rest.split_once(&format!("\n{}\n", FENCE))
+        .or_else(|| rest.strip_suffix(&format!("\n{}", FENCE)).map(|m| (m, "")))
+        .ok_or("front matter isn't closed")?;
+    let meta: EntryMeta = toml::from_str(meta)?;
+
+    let task_id = match meta.task_id {
+        Some(id) => id,
+        None => {
+            let task = files.get(&format!("{}/{}", folder, TASK_FILE)).ok_or("no task_id and no task.toml")?;
+            Task::from_toml(task.clone())?.id
+        }
+    };
+    let mut entry = TaskEntry::new_at(task_id, content, meta.timestamp);
+    if let Some(id) = meta.id {
+        entry.id = id;
+    }
+    Ok(Parsed::Entry(entry))
+}
+
+/// A readable, stable folder name for a task
+fn task_folder(task: &Task) -> String {
+    let slug: String = task.name.to_lowercase()
+        .chars()
+        .map(|c| if c.is_alphanumeric() { c } else { '-' })
+        .collect::<String>()
+        .split('-')
+        .filter(|s| !s.is_empty())
+        .collect::<Vec<_>>()
+        .join("-");
+    let slug: String = slug.chars().take(40).collect();
+    match slug.is_empty() {
+        true => short(task.id),
+        false => format!("{}-{}", slug, short(task.id)),
+    }
+}
+
+fn short(id: Uuid) -> String {
+    id.simple().to_string()[..8].to_string()
+}
+
+fn digest(text: &str) -> String {
+    stable_id(&[text]).simple().to_string()
+}
+
+fn is_hidden(path: &Path) -> bool {
+    path.file_name().is_some_and(|n| n.to_string_lossy().starts_with('.'))
+}
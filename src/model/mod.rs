@@ -1,4 +1,21 @@
+pub mod clock;
 pub mod convert;
 pub mod task;
 pub mod task_entry;
 pub mod store;
+pub mod progress;
+pub mod stats;
+pub mod pause;
+pub mod when;
+pub mod order;
+pub mod routine;
+pub mod search;
+pub mod query;
+pub mod ics;
+pub mod journal;
+pub mod report;
+pub mod dump;
+pub mod import;
+pub mod hooks;
+pub mod mirror;
+pub mod sync;
@@ -0,0 +1,169 @@
+// order.rs
+
+use std::fmt;
+use std::error::Error;
+use std::cmp::Reverse;
+use uuid::Uuid;
+use serde::{Serialize, Deserialize};
+use chrono::{DateTime, Utc};
+use crate::model::convert::Storable;
+use crate::model::clock::Clock;
+use crate::config::Config;
+use crate::model::task::Task;
+use crate::model::progress::Progress;
+use crate::model::store::{StoreError, TaskStore};
+
+///////////////////////////////////////////////////////////
+
+#[derive(Serialize, Deserialize, PartialEq, Eq, Debug, Clone, Copy, Default)]
+pub enum SortMode {
+    #[default]
+    Manual,     // the order the user arranged
+    Name,
+    Due,        // tasks still due this period first
+    Streak,     // longest streak first
+    Created,    // newest first
+}
+
+impl SortMode {
+
+    /// The mode after this one, wrapping around
+    pub fn next(&self) -> Self {
+        match self {
+            SortMode::Manual => SortMode::Name,
+            SortMode::Name => SortMode::Due,
+            SortMode::Due => SortMode::Streak,
+            SortMode::Streak => SortMode::Created,
+            SortMode::Created => SortMode::Manual,
+        }
+    }
+}
+
+impl fmt::Display for SortMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let text = match self {
+            SortMode::Manual => "manual",
+            SortMode::Name => "name",
+            SortMode::Due => "due",
+            SortMode::Streak => "streak",
+            SortMode::Created => "created",
+        };
+        write!(f, "{}", text)
+    }
+}
+
+/// How the task list is ordered. There is a single one in the store, pinned
+/// tasks always come first whatever the mode.
+#[derive(Serialize, Deserialize, PartialEq, Eq, Debug, Clone)]
+pub struct TaskOrder {
+    pub mode: SortMode,
+    pub ids: Vec<Uuid>,     // manual order, tasks missing from it go on top
+    pub timestamp: DateTime<Utc>,
+}
+
+impl Default for TaskOrder {
+    fn default() -> Self {
+        TaskOrder { mode: SortMode::default(), ids: vec![], timestamp: Utc::now() }
+    }
+}
+
+impl TaskOrder {
+
+    /// stateless key of the one saved order
+    pub fn key() -> String {
+        String::from("meta:order")
+    }
+
+    /// The saved order, or the default if none was saved yet
+    pub fn load(store: &TaskStore) -> Result<Self, StoreError> {
+        Ok(store.get(Self::key())?.unwrap_or_default())
+    }
+
+    /// Indices into `tasks` in display order. `progress` is parallel to `tasks`.
+    pub fn sort(&self, tasks: &[Task], progress: &[Progress]) -> Vec<usize> {
+        let mut idxs: Vec<usize> = (0..tasks.len()).collect();
+        match self.mode {
+            SortMode::Manual => idxs.sort_by_key(|i| {
+                self.ids.iter().position(|id| *id == tasks[*i].id)
+            }),
+            SortMode::Name => idxs.sort_by_key(|i| tasks[*i].name.to_lowercase()),
+            SortMode::Due => idxs.sort_by_key(|i| !progress[*i].is_due()),
+            SortMode::Streak => idxs.sort_by_key(|i| Reverse(progress[*i].streak)),
+            SortMode::Created => idxs.sort_by_key(|i| Reverse(tasks[*i].timestamp)),
+        }
+        // stable, so each mode's order holds within the pinned and unpinned
+        idxs.sort_by_key(|i| !tasks[*i].pinned);
+        idxs
+    }
+
+    /// Swap the task at `idx` of the `shown` list with its neighbour below
+    /// (or above when `down` is false), switching to the manual mode. Returns
+    /// the new index of the task, or None if it can't move that way.
+    pub fn move_task(&mut self, shown: &[Task], idx: usize, down: bool) -> Option<usize> {
+        let other = match down {
+            true => idx + 1,
+            false => idx.checked_sub(1)?,
+        };
+        let (a, b) = (shown.get(idx)?, shown.get(other)?);
+        if a.pinned != b.pinned {
+            return None;
+        }
+
+        // start from what the user is looking at
+        if self.mode != SortMode::Manual || shown.iter().any(|t| !self.ids.contains(&t.id)) {
+            let mut ids: Vec<Uuid> = shown.iter().map(|t| t.id).collect();
+            ids.extend(self.ids.iter().filter(|id| !shown.iter().any(|t| t.id == **id)));
+            self.ids = ids;
+            self.mode = SortMode::Manual;
+        }
+
+        let i = self.ids.iter().position(|id| *id == a.id)?;
+        let j = self.ids.iter().position(|id| *id == b.id)?;
+        self.ids.swap(i, j);
+        self.timestamp = Utc::now();
+        Some(other)
+    }
+}
+
+impl Storable for TaskOrder {
+
+    fn get_display_fields(&self) -> Vec<String> {
+        vec![self.mode.to_string()]
+    }
+
+    fn get_timestamp(&self) -> String {
+        Clock::get().format(self.timestamp, &Config::get().timestamp_format)
+    }
+
+    fn get_datetime(&self) -> DateTime<Utc> {
+        self.timestamp
+    }
+
+    fn to_key(&self) -> String {
+        Self::key()
+    }
+
+    fn from_bytes(bytes: &[u8]) -> serde_json::Result<Self> {
+        serde_json::from_slice(bytes)
+    }
+
+    fn to_bytes(&self) -> serde_json::Result<Vec<u8>> {
+        serde_json::to_vec(self)
+    }
+
+    fn to_toml(&self) -> Result<String, Box<dyn Error>> {
+        let toml_string = toml::to_string(self)?;
+        Ok(toml_string)
+    }
+
+    fn from_toml(toml_string: String) -> Result<Self, Box<dyn Error>> {
+        let order = toml::from_str(&toml_string)?;
+        Ok(order)
+    }
+}
+
+impl fmt::Display for TaskOrder {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "sorted by {}", self.mode)
+    }
+}
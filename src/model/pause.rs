@@ -0,0 +1,131 @@
+// pause.rs
+
+use std::fmt;
+use std::error::Error;
+use std::collections::BTreeSet;
+use uuid::Uuid;
+use serde::{Serialize, Deserialize};
+use chrono::{DateTime, NaiveDate, Utc};
+use crate::model::convert::Storable;
+use crate::model::clock::Clock;
+use crate::config::Config;
+
+///////////////////////////////////////////////////////////
+
+/// A range of days, inclusive on both ends, during which a task (or every
+/// task) is on hold. Paused days neither extend nor break a streak and
+/// nothing is due on them.
+#[derive(Serialize, Deserialize, PartialEq, Eq, Debug, Clone)]
+pub struct Pause {
+    pub id: uuid::Uuid,
+    pub task_id: Option<uuid::Uuid>,    // None pauses every task
+    pub start: NaiveDate,
+    pub end: NaiveDate,
+    pub reason: String,
+    pub timestamp: DateTime<Utc>,
+}
+
+impl Pause {
+
+    pub fn new<T: Into<String>>(
+        task_id: Option<Uuid>,
+        start: NaiveDate,
+        end: NaiveDate,
+        reason: T
+    ) -> Self {
+        Pause {
+            id: Uuid::new_v4(),
+            task_id,
+            start,
+            end,
+            reason: reason.into(),
+            timestamp: Utc::now(),
+        }
+    }
+
+    /// stateless key pattern for retrieving all pauses
+    pub fn key_all() -> &'static str {
+        "pause:"
+    }
+
+    /// does the pause hold for this task
+    pub fn applies_to(&self, task_id: Uuid) -> bool {
+        self.task_id.is_none_or(|id| id == task_id)
+    }
+
+    /// does the pause hold for this task on this day
+    pub fn covers(&self, task_id: Uuid, day: NaiveDate) -> bool {
+        self.applies_to(task_id) && self.start <= day && day <= self.end
+    }
+
+    /// does the pause hold for this task on any day of the range [from, to)
+    pub fn overlaps(&self, task_id: Uuid, from: NaiveDate, to: NaiveDate) -> bool {
+        self.applies_to(task_id) && self.start < to && from <= self.end
+    }
+}
+
+/// Is the task paused on the day
+pub fn is_paused(pauses: &[Pause], task_id: Uuid, day: NaiveDate) -> bool {
+    pauses.iter().any(|p| p.covers(task_id, day))
+}
+
+/// Every paused day of the task in the range [from, to]
+pub fn paused_days(pauses: &[Pause], task_id: Uuid, from: NaiveDate, to: NaiveDate)
+    -> BTreeSet<NaiveDate>
+{
+    pauses.iter()
+        .filter(|p| p.applies_to(task_id))
+        .flat_map(|p| p.start.max(from).iter_days().take_while(move |d| *d <= p.end.min(to)))
+        .collect()
+}
+
+impl Storable for Pause {
+
+    fn get_display_fields(&self) -> Vec<String> {
+        vec![self.start.to_string(), self.end.to_string(), self.reason.clone()]
+    }
+
+    fn get_timestamp(&self) -> String {
+        Clock::get().format(self.timestamp, &Config::get().timestamp_format)
+    }
+
+    fn get_datetime(&self) -> DateTime<Utc> {
+        self.timestamp
+    }
+
+    fn to_key(&self) -> String {
+        format!("pause:{}", self.id)
+    }
+
+    fn from_bytes(bytes: &[u8]) -> serde_json::Result<Self> {
+        serde_json::from_slice(bytes)
+    }
+
+    fn to_bytes(&self) -> serde_json::Result<Vec<u8>> {
+        serde_json::to_vec(self)
+    }
+
+    fn to_toml(&self) -> Result<String, Box<dyn Error>> {
+        let toml_string = toml::to_string(self)?;
+        Ok(toml_string)
+    }
+
+    fn from_toml(toml_string: String) -> Result<Self, Box<dyn Error>> {
+        let pause = toml::from_str(&toml_string)?;
+        Ok(pause)
+    }
+
+    fn is_legal_update_from(&self, other: &Self) -> bool {
+        other.id == self.id && other.start <= other.end
+    }
+}
+
+impl fmt::Display for Pause {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "paused {} to {}", self.start, self.end)?;
+        if !self.reason.is_empty() {
+            write!(f, " ({})", self.reason)?;
+        }
+        Ok(())
+    }
+}
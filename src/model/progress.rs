@@ -0,0 +1,183 @@
+// progress.rs
+
+use std::fmt;
+use std::collections::HashMap;
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use crate::model::clock::Clock;
+use crate::model::task::{all_tags, EvalPeriod, Task};
+use crate::model::task_entry::TaskEntry;
+use crate::model::pause::{is_paused, Pause};
+use crate::model::store::{StoreError, TaskStore};
+
+///////////////////////////////////////////////////////////
+
+/// How a task is doing against its target for the running period
+#[derive(Debug, Clone, PartialEq)]
+pub struct Progress {
+    pub count: u32,             // completions in the running period
+    pub target: u32,            // completions wanted per period
+    pub streak: u32,            // consecutive periods the target was met
+    pub paused: bool,           // is the task on hold today
+    pub archived: bool,         // retired tasks are never due
+    pub period: EvalPeriod,
+}
+
+impl Progress {
+
+    /// Tally `entries` of `task` into periods ending with the one containing
+    /// `now`. Periods that fell short while a pause held are skipped over.
+    pub fn compute(
+        task: &Task,
+        entries: &[TaskEntry],
+        pauses: &[Pause],
+        clock: &Clock,
+        now: DateTime<Utc>
+    ) -> Self {
+        let today = clock.day_of(now);
+        let period = task.eval_period.clone();
+        let target = task.target.max(1);
+        let paused = is_paused(pauses, task.id, today);
+        let archived = task.archived;
+
+        // entries logged in the future don't count yet
+        let days = entries.iter()
+            .map(|e| clock.day_of(e.timestamp))
+            .filter(|day| *day <= today);
+
+        let Some(current) = period.start_of(today) else {
+            // a single period spanning all time, nothing to keep a streak of
+            let count = days.count() as u32;
+            return Progress { count, target, streak: 0, paused, archived, period };
+        };
+
+        let mut counts: HashMap<NaiveDate, u32> = HashMap::new();
+        for day in days {
+            if let Some(start) = period.start_of(day) {
+                *counts.entry(start).or_default() += 1;
+            }
+        }
+
+        let count = counts.get(&current).copied().unwrap_or(0);
+        let met = |start: &NaiveDate| counts.get(start).is_some_and(|c| *c >= target);
+        let on_hold = |start: &NaiveDate| {
+            let end = period.next_start(*start).unwrap_or(*start);
+            pauses.iter().any(|p| p.overlaps(task.id, *start, end))
+        };
+
+        // the running period only extends the streak once its target is met,
+        // until then the streak is still alive from the previous periods
+        let mut start = if count >= target { current } else { previous(&period, current) };
+        // nothing before the task or its first entry can count, however
+        // far back a pause reaches
+        let first = counts.keys().copied()
+            .chain(period.start_of(clock.day_of(task.timestamp)))
+            .min()
+            .unwrap_or(current);
+        let mut streak = 0;
+        while start >= first {
+            if met(&start) {
+                streak += 1;
+            } else if !on_hold(&start) {
+                break;
+            }
+            start = previous(&period, start);
+        }
+
+        Progress { count, target, streak, paused, archived, period }
+    }
+
+    /// Has the target for the running period been reached
+    pub fn is_met(&self) -> bool {
+        self.count >= self.target
+    }
+
+    /// Does the task still need doing this period
+    pub fn is_due(&self) -> bool {
+        !self.is_met() && !self.paused && !self.archived
+    }
+
+    /// e.g. "3 weeks"
+    pub fn streak_label(&self) -> String {
+        match self.period {
+            EvalPeriod::Any => String::from("-"),
+            _ => format!(
+                "{} {}{}",
+                self.streak,
+                self.period.unit(),
+                if self.streak == 1 { "" } else { "s" }
+            ),
+        }
+    }
+}
+
+/// Progress of each task in the store's records, as of `now`
+pub fn progress_all(store: &TaskStore, tasks: &[Task], clock: &Clock, now: DateTime<Utc>)
+    -> Result<Vec<Progress>, StoreError>
+{
+    let pauses: Vec<Pause> = store.get_prefix(Pause::key_all())?;
+    tasks.iter()
+        .map(|task| {
+            let entries: Vec<TaskEntry> = store.get_prefix(TaskEntry::key_task(task.id))?;
+            Ok(Progress::compute(task, &entries, &pauses, clock, now))
+        })
+        .collect()
+}
+
+/// How the tasks sharing a tag are doing
+#[derive(Debug, Clone, PartialEq)]
+pub struct TagRollup {
+    pub tag: String,
+    pub tasks: usize,
+    pub met: usize,     // tasks that reached their target this period
+    pub due: usize,     // tasks still to be done this period
+    pub streak: u32,    // best streak among the tasks
+}
+
+/// One rollup per tag in use, in tag order. `progress` is parallel to `tasks`.
+pub fn tag_rollups(tasks: &[Task], progress: &[Progress]) -> Vec<TagRollup> {
+    all_tags(tasks).into_iter()
+        .map(|tag| {
+            let tagged: Vec<&Progress> = tasks.iter()
+                .zip(progress)
+                .filter(|(t, _)| t.has_tag(&tag))
+                .map(|(_, p)| p)
+                .collect();
+            TagRollup {
+                tasks: tagged.len(),
+                met: tagged.iter().filter(|p| p.is_met()).count(),
+                due: tagged.iter().filter(|p| p.is_due()).count(),
+                streak: tagged.iter().map(|p| p.streak).max().unwrap_or(0),
+                tag,
+            }
+        })
+        .collect()
+}
+
+impl fmt::Display for TagRollup {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "#{} {}/{} done", self.tag, self.met, self.tasks)?;
+        if self.due > 0 {
+            write!(f, ", {} due", self.due)?;
+        }
+        Ok(())
+    }
+}
+
+/// Start of the period before the one starting at `start`
+fn previous(period: &EvalPeriod, start: NaiveDate) -> NaiveDate {
+    period.start_of(start - Duration::days(1)).unwrap_or(start)
+}
+
+impl fmt::Display for Progress {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{} {}", self.count, self.target, self.period.label())?;
+        if self.archived {
+            write!(f, " (archived)")?;
+        } else if self.is_met() {
+            write!(f, " ✓")?;
+        } else if self.paused {
+            write!(f, " (paused)")?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,256 @@
+// query.rs
+
+use std::fmt;
+use std::error::Error;
+use chrono::{Duration, NaiveDate};
+use crate::model::clock::Clock;
+use crate::model::task::Task;
+use crate::model::task_entry::TaskEntry;
+
+///////////////////////////////////////////////////////////
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Cmp {
+    Lt,
+    Le,
+    Eq,
+    Ne,
+    Ge,
+    Gt,
+}
+
+/// A single condition of a query
+#[derive(Debug, Clone, PartialEq)]
+pub enum Clause {
+    Task(String),           // task name contains
+    Id(Vec<String>),        // task ID starts with any of these
+    Tag(String),
+    After(NaiveDate),       // entries on or after the day
+    Before(NaiveDate),      // entries before the day
+    On(NaiveDate),
+    Value(Cmp, f64),        // numeric entry content
+    Text(String),           // entry content contains
+    Not(Box<Clause>),
+}
+
+/// A parsed query such as `task:run after:2026-01-01 value>5 tag:health`.
+/// Every clause must hold. Task clauses pick tasks, the others pick entries
+/// of those tasks. The empty query matches everything.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Query {
+    pub clauses: Vec<Clause>,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct QueryError(pub String);
+
+impl fmt::Display for QueryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "bad query: {}", self.0)
+    }
+}
+
+impl Error for QueryError {}
+
+impl Cmp {
+    fn holds(&self, a: f64, b: f64) -> bool {
+        match self {
+            Cmp::Lt => a < b,
+            Cmp::Le => a <= b,
+            Cmp::Eq => a == b,
+            Cmp::Ne => a != b,
+            Cmp::Ge => a >= b,
+            Cmp::Gt => a > b,
+        }
+    }
+}
+
+impl Clause {
+
+    /// Does the clause only look at the task
+    fn is_task_clause(&self) -> bool {
+        match self {
+            Clause::Task(_) | Clause::Id(_) | Clause::Tag(_) => true,
+            Clause::Not(c) => c.is_task_clause(),
+            _ => false,
+        }
+    }
+
+    fn matches_task(&self, task: &Task) -> bool {
+        match self {
+            Clause::Task(name) => task.name.to_lowercase().contains(name.as_str()),
+            Clause::Id(ids) => {
+                let id = task.id.to_string();
+                ids.iter().any(|prefix| id.starts_with(prefix.as_str()))
+            }
+            Clause::Tag(tag) => task.has_tag(tag),
+            Clause::Not(c) if c.is_task_clause() => !c.matches_task(task),
+            _ => true,
+        }
+    }
+
+    fn matches_entry(&self, entry: &TaskEntry, clock: &Clock) -> bool {
+        let day = || clock.day_of(entry.timestamp);
+        match self {
+            Clause::After(date) => day() >= *date,
+            Clause::Before(date) => day() < *date,
+            Clause::On(date) => day() == *date,
+            Clause::Value(cmp, v) => entry.get_value().is_some_and(|x| cmp.holds(x, *v)),
+            Clause::Text(text) => entry.get_content().to_lowercase().contains(text.as_str()),
+            Clause::Not(c) if !c.is_task_clause() => !c.matches_entry(entry, clock),
+            _ => true,
+        }
+    }
+}
+
+impl Query {
+
+    /// Parse a query, relative dates like `after:7d` count back from `today`
+    pub fn parse(input: &str, today: NaiveDate) -> Result<Self, QueryError> {
+        let clauses = split_words(input)?
+            .iter()
+            .map(|word| parse_clause(word, today))
+            .collect::<Result<_, _>>()?;
+        Ok(Query { clauses })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.clauses.is_empty()
+    }
+
+    /// Does the query say anything about entries
+    pub fn has_entry_clauses(&self) -> bool {
+        self.clauses.iter().any(|c| !c.is_task_clause())
+    }
+
+    /// Do the task clauses hold
+    pub fn matches_task(&self, task: &Task) -> bool {
+        self.clauses.iter().all(|c| c.matches_task(task))
+    }
+
+    /// Do the entry clauses hold, the task is assumed to match already
+    pub fn matches_entry(&self, entry: &TaskEntry, clock: &Clock) -> bool {
+        self.clauses.iter().all(|c| c.matches_entry(entry, clock))
+    }
+}
+
+/// Split on whitespace, keeping "double quoted" runs together
+fn split_words(input: &str) -> Result<Vec<String>, QueryError> {
+    let mut words = vec![];
+    let mut word = String::new();
+    let mut quoted = false;
+    for c in input.chars() {
+        match c {
+            '"' => quoted = !quoted,
+            c if c.is_whitespace() && !quoted => {
+                if !word.is_empty() {
+                    words.push(std::mem::take(&mut word));
+                }
+            }
+            c => word.push(c),
+        }
+    }
+    if quoted {
+        return Err(QueryError(String::from("unclosed quote")));
+    }
+    if !word.is_empty() {
+        words.push(word);
+    }
+    Ok(words)
+}
+
+fn parse_clause(word: &str, today: NaiveDate) -> Result<Clause, QueryError> {
+    if let Some(rest) = word.strip_prefix('-').filter(|r| !r.is_empty()) {
+        return Ok(Clause::Not(Box::new(parse_clause(rest, today)?)));
+    }
+
+    if let Some(rest) = word.strip_prefix("value") {
+        let ops = [
+            ("<=", Cmp::Le), (">=", Cmp::Ge), ("!=", Cmp::Ne),
+            ("<", Cmp::Lt), (">", Cmp::Gt), ("=", Cmp::Eq),
+        ];
+        if let Some((op, cmp)) = ops.iter().find(|(op, _)| rest.starts_with(op)) {
+            let number = &rest[op.len()..];
+            let value = number.parse::<f64>()
+                .map_err(|_| QueryError(format!("'{}' is not a number", number)))?;
+            return Ok(Clause::Value(*cmp, value));
+        }
+    }
+
+    let Some((key, value)) = word.split_once(':') else {
+        return Ok(Clause::Text(word.to_lowercase()));
+    };
+    if value.is_empty() {
+        return Err(QueryError(format!("'{}' needs a value", key)));
+    }
+    match key {
+        "task" => Ok(Clause::Task(value.to_lowercase())),
+        "id" => Ok(Clause::Id(value.split(',').map(|id| id.trim().to_lowercase()).collect())),
+        "tag" => Ok(Clause::Tag(value.trim_start_matches('#').to_lowercase())),
+        "after" => Ok(Clause::After(parse_date(value, today)?)),
+        "before" => Ok(Clause::Before(parse_date(value, today)?)),
+        "on" => Ok(Clause::On(parse_date(value, today)?)),
+        "text" => Ok(Clause::Text(value.to_lowercase())),
+        _ => Err(QueryError(format!(
+            "unknown field '{}', try task, id, tag, after, before, on, text or value", key
+        ))),
+    }
+}
+
+/// `2026-01-01`, `today`, `yesterday` or a count back like `7d`, `2w`
+pub fn parse_date(value: &str, today: NaiveDate) -> Result<NaiveDate, QueryError> {
+    let err = || QueryError(format!("'{}' is not a date, try 2026-01-01, yesterday or 7d", value));
+    match value {
+        "today" => return Ok(today),
+        "yesterday" => return Ok(today - Duration::days(1)),
+        _ => {}
+    }
+    if let Ok(date) = NaiveDate::parse_from_str(value, "%Y-%m-%d") {
+        return Ok(date);
+    }
+    let unit = value.chars().last().ok_or_else(err)?;
+    let n: i64 = value[..value.len() - unit.len_utf8()].parse().map_err(|_| err())?;
+    let span = match unit {
+        'd' => Duration::try_days(n),
+        'w' => Duration::try_weeks(n),
+        _ => return Err(err()),
+    };
+    span.and_then(|span| today.checked_sub_signed(span)).ok_or_else(err)
+}
+
+impl fmt::Display for Query {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let words: Vec<String> = self.clauses.iter().map(|c| c.to_string()).collect();
+        write!(f, "{}", words.join(" "))
+    }
+}
+
+impl fmt::Display for Clause {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let quote = |s: &str| match s.contains(char::is_whitespace) {
+            true => format!("\"{}\"", s),
+            false => s.to_string(),
+        };
+        match self {
+            Clause::Task(name) => write!(f, "task:{}", quote(name)),
+            Clause::Id(ids) => write!(f, "id:{}", ids.join(",")),
+            Clause::Tag(tag) => write!(f, "tag:{}", tag),
+            Clause::After(date) => write!(f, "after:{}", date),
+            Clause::Before(date) => write!(f, "before:{}", date),
+            Clause::On(date) => write!(f, "on:{}", date),
+            Clause::Value(cmp, v) => {
+                let op = match cmp {
+                    Cmp::Lt => "<",
+                    Cmp::Le => "<=",
+                    Cmp::Eq => "=",
+                    Cmp::Ne => "!=",
+                    Cmp::Ge => ">=",
+                    Cmp::Gt => ">",
+                };
+                write!(f, "value{}{}", op, v)
+            }
+            Clause::Text(text) => write!(f, "{}", quote(text)),
+            Clause::Not(c) => write!(f, "-{}", c),
+        }
+    }
+}
@@ -0,0 +1,164 @@
+// report.rs
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::error::Error;
+use std::fmt::Write as _;
+use std::io::Write;
+use chrono::{Datelike, Duration, NaiveDate, Utc};
+use crate::model::clock::Clock;
+use crate::model::pause::{paused_days, Pause};
+use crate::model::progress::Progress;
+use crate::model::stats::{daily_totals, shade_level, TaskStats};
+use crate::model::task::{Task, TaskFilter};
+use crate::model::task_entry::TaskEntry;
+use crate::model::store::TaskStore;
+
+///////////////////////////////////////////////////////////
+
+/// Weeks in each heatmap, a year
+const WEEKS: i64 = 53;
+
+/// Side of a heatmap cell and the gap after it, in pixels
+const CELL: i64 = 11;
+const GAP: i64 = 2;
+
+/// Room for the weekday labels and the month labels
+const LEFT: i64 = 30;
+const TOP: i64 = 15;
+
+/// Same shades as the heatmap in the terminal
+const SHADES: [&str; 5] = ["#2d333b", "#0e4429", "#006d32", "#26a641", "#39d353"];
+const PAUSED: &str = "#1f6feb";
+
+const STYLE: &str = "\
+body { font-family: system-ui, sans-serif; background: #22272e; color: #adbac7; margin: 2em auto; max-width: 60em; }
+h1 { font-weight: 400; }
+section { border-top: 1px solid #444c56; padding: 1em 0; }
+h2 { margin: 0; }
+.desc { color: #768390; }
+.tag { background: #373e47; border-radius: 1em; padding: 0 .6em; margin-right: .3em; font-size: .85em; }
+table { border-collapse: collapse; margin: .8em 0; }
+td { padding: .1em 1.5em .1em 0; }
+td:first-child { color: #768390; }
+svg text { fill: #768390; font-size: 9px; }
+";
+
+/// Write a self-contained HTML page with the stats and a heatmap of the
+/// last year for each task the filter keeps. The stats cover all of a
+/// task's entries, the filter only picks the tasks. Nothing is loaded from
+/// elsewhere, so it opens offline.
+pub fn write_report<W: Write>(store: &TaskStore, mut writer: W, filter: &TaskFilter)
+    -> Result<(), Box<dyn Error>>
+{
+    let clock = Clock::get();
+    let now = Utc::now();
+    let today = clock.day_of(now);
+    let pauses: Vec<Pause> = store.get_prefix(Pause::key_all())?;
+
+    let mut html = String::new();
+    writeln!(html, "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">")?;
+    writeln!(html, "<title>Packrat report</title>\n<style>\n{}</style>\n</head>\n<body>", STYLE)?;
+    writeln!(html, "<h1>Packrat report</h1>\n<p class=\"desc\">As of {}</p>", today)?;
+
+    for task in store.get_prefix::<Task>(Task::key_all())? {
+        let entries: Vec<TaskEntry> = store.get_prefix(TaskEntry::key_task(task.id))?;
+        if filter.select(&task, &entries, clock).is_none() {
+            continue;
+        }
+        let stats = TaskStats::compute(&task, &entries, &pauses, clock, now);
+        let progress = Progress::compute(&task, &entries, &pauses, clock, now);
+        let totals = daily_totals(&task, &entries, clock);
+        let paused = paused_days(&pauses, task.id, today - Duration::weeks(WEEKS), today);
+
+        writeln!(html, "<section>\n<h2>{}</h2>", escape(&task.name))?;
+        if !task.desc.trim().is_empty() {
+            writeln!(html, "<p class=\"desc\">{}</p>", escape(&task.desc))?;
+        }
+        if !task.tags.is_empty() {
+            let tags: Vec<String> = task.tags.iter()
+                .map(|t| format!("<span class=\"tag\">#{}</span>", escape(t)))
+                .collect();
+            writeln!(html, "<p>{}</p>", tags.join(""))?;
+        }
+
+        let mut rows = vec![
+            ("Entries", stats.entries.to_string()),
+            ("Target", format!("{} per {}", task.target, task.eval_period.unit())),
+            ("Progress", escape(&progress.to_string())),
+            ("Streak", progress.streak_label()),
+        ];
+        if let Some(rate) = stats.completion_rate() {
+            rows.push(("Met", format!("{:.0}% of {} periods", rate * 100.0, stats.periods)));
+        }
+        if let (Some(min), Some(max), Some(mean)) = (stats.min, stats.max, stats.mean) {
+            rows.push(("Values", format!("{} to {}, {:.2} on average", min, max, mean)));
+        }
+        let busiest = stats.weekdays.iter().enumerate().max_by_key(|(_, n)| **n);
+        if let Some((day, n)) = busiest.filter(|(_, n)| **n > 0) {
+            let name = chrono::Weekday::try_from(day as u8).map_or(String::new(), |d| d.to_string());
+            rows.push(("Busiest day", format!("{} ({} entries)", name, n)));
+        }
+        writeln!(html, "<table>")?;
+        for (label, value) in rows {
+            writeln!(html, "<tr><td>{}</td><td>{}</td></tr>", label, value)?;
+        }
+        writeln!(html, "</table>")?;
+        html.push_str(&heatmap_svg(&totals, &paused, today));
+        writeln!(html, "</section>")?;
+    }
+
+    writeln!(html, "</body>\n</html>")?;
+    writer.write_all(html.as_bytes())?;
+    writer.flush()?;
+    Ok(())
+}
+
+/// A GitHub style calendar of daily totals as inline SVG, ending with the
+/// week of `end`
+pub fn heatmap_svg(days: &BTreeMap<NaiveDate, f64>, paused: &BTreeSet<NaiveDate>, end: NaiveDate) -> String {
+    let last_monday = end - Duration::days(end.weekday().num_days_from_monday() as i64);
+    let first_monday = last_monday - Duration::weeks(WEEKS - 1);
+    let max = days.range(first_monday..=end).map(|(_, v)| *v).fold(0.0, f64::max);
+
+    let step = CELL + GAP;
+    let (width, height) = (LEFT + WEEKS * step, TOP + 7 * step);
+    let mut svg = format!(
+        "<svg width=\"{w}\" height=\"{h}\" viewBox=\"0 0 {w} {h}\" role=\"img\">\n", w = width, h = height
+    );
+    for (row, label) in [(0, "Mon"), (2, "Wed"), (4, "Fri"), (6, "Sun")] {
+        svg.push_str(&format!("<text x=\"0\" y=\"{}\">{}</text>\n", TOP + row * step + CELL - 2, label));
+    }
+
+    for week in 0..WEEKS {
+        let monday = first_monday + Duration::weeks(week);
+        let x = LEFT + week * step;
+        if week == 0 || monday.month() != (monday - Duration::weeks(1)).month() {
+            svg.push_str(&format!("<text x=\"{}\" y=\"{}\">{}</text>\n", x, TOP - 4, monday.format("%b")));
+        }
+        for weekday in 0..7 {
+            let day = monday + Duration::days(weekday);
+            if day > end {
+                break;
+            }
+            let value = days.get(&day).copied().unwrap_or(0.0);
+            let fill = match shade_level(value, max, SHADES.len()) {
+                0 if paused.contains(&day) => PAUSED,
+                level => SHADES[level],
+            };
+            svg.push_str(&format!(
+                "<rect x=\"{}\" y=\"{}\" width=\"{c}\" height=\"{c}\" rx=\"2\" fill=\"{}\"><title>{}: {}</title></rect>\n",
+                x, TOP + weekday * step, fill, day, value, c = CELL
+            ));
+        }
+    }
+    svg.push_str("</svg>\n");
+    svg
+}
+
+/// Escape text for HTML
+pub fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
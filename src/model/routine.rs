@@ -0,0 +1,245 @@
+// routine.rs
+
+use std::fmt;
+use std::error::Error;
+use uuid::Uuid;
+use serde::{Serialize, Deserialize};
+use chrono::{DateTime, Utc};
+use crate::model::convert::Storable;
+use crate::model::clock::Clock;
+use crate::config::Config;
+use crate::model::task::Task;
+use crate::model::task_entry::TaskEntry;
+use crate::model::store::{StoreError, TaskStore};
+
+///////////////////////////////////////////////////////////
+
+/// An ordered checklist of tasks that are done one after the other,
+/// e.g. a morning routine of water, stretch and journal.
+#[derive(Serialize, Deserialize, PartialEq, Eq, Debug, Clone)]
+pub struct Routine {
+    pub id: uuid::Uuid,
+    pub name: String,
+    pub desc: String,
+    pub task_ids: Vec<Uuid>,    // member tasks, in the order they are run
+    pub timestamp: DateTime<Utc>,
+}
+
+/// One pass through a routine. Each member task is either logged or skipped.
+#[derive(Serialize, Deserialize, PartialEq, Eq, Debug, Clone)]
+pub struct RoutineRun {
+    pub id: uuid::Uuid,
+    pub routine_id: uuid::Uuid,
+    pub done: Vec<Uuid>,
+    pub skipped: Vec<Uuid>,
+    pub finished: Option<DateTime<Utc>>,
+    pub timestamp: DateTime<Utc>,   // when the run started
+}
+
+impl Routine {
+
+    pub fn new<T: Into<String>, K: Into<String>>(name: T, desc: K, task_ids: Vec<Uuid>) -> Self {
+        Routine {
+            id: Uuid::new_v4(),
+            name: name.into(),
+            desc: desc.into(),
+            task_ids,
+            timestamp: Utc::now(),
+        }
+    }
+
+    /// stateless key pattern for retrieving all routines
+    pub fn key_all() -> &'static str {
+        "routine:"
+    }
+
+    /// The member tasks in order, leaving out any that were deleted since
+    pub fn tasks(&self, store: &TaskStore) -> Result<Vec<Task>, StoreError> {
+        let mut tasks = vec![];
+        for id in &self.task_ids {
+            if let Some(task) = store.get::<Task>(Task::key_task(*id))? {
+                tasks.push(task);
+            }
+        }
+        Ok(tasks)
+    }
+
+    /// The most recent finished run, if any
+    pub fn last_run(&self, store: &TaskStore) -> Result<Option<RoutineRun>, StoreError> {
+        let runs: Vec<RoutineRun> = store.get_prefix(RoutineRun::key_routine(self.id))?;
+        Ok(runs.into_iter().filter(|r| r.finished.is_some()).max_by_key(|r| r.finished))
+    }
+}
+
+/// Resolve a comma separated list of task names or ID prefixes into IDs
+pub fn parse_members(store: &TaskStore, input: &str) -> Result<Vec<Uuid>, StoreError> {
+    input.split(',')
+        .map(|name| name.trim())
+        .filter(|name| !name.is_empty())
+        .map(|name| store.find_task(name).map(|t| t.id))
+        .collect()
+}
+
+impl RoutineRun {
+
+    pub fn new(routine_id: Uuid) -> Self {
+        RoutineRun {
+            id: Uuid::new_v4(),
+            routine_id,
+            done: vec![],
+            skipped: vec![],
+            finished: None,
+            timestamp: Utc::now(),
+        }
+    }
+
+    /// stateless key pattern for the runs of a routine
+    pub fn key_routine<S: Into<String> + fmt::Display>(routine_id: S) -> String {
+        format!("routine_run:{}", routine_id)
+    }
+
+    /// was every member task logged
+    pub fn is_complete(&self) -> bool {
+        self.finished.is_some() && self.skipped.is_empty()
+    }
+
+    /// The first of `tasks` that was neither logged nor skipped yet
+    pub fn next_step<'a>(&self, tasks: &'a [Task]) -> Option<&'a Task> {
+        tasks.iter().find(|t| !self.done.contains(&t.id) && !self.skipped.contains(&t.id))
+    }
+
+    /// Log an entry for a member task and move past it
+    pub fn log_step(&mut self, store: &TaskStore, task: &Task, content: &str)
+        -> Result<TaskEntry, StoreError>
+    {
+        let entry = store.put(TaskEntry::new(task.id, content))?;
+        self.done.push(task.id);
+        Ok(entry)
+    }
+
+    /// Move past a member task without logging it
+    pub fn skip_step(&mut self, task: &Task) {
+        self.skipped.push(task.id);
+    }
+
+    /// Record the end of the run
+    pub fn finish(&mut self, store: &TaskStore) -> Result<(), StoreError> {
+        self.finished = Some(Utc::now());
+        store.put(self.clone())?;
+        Ok(())
+    }
+
+    /// Record a run broken off before its last step, the steps not reached
+    /// count as skipped. Nothing is recorded if no step was taken.
+    pub fn stop(&mut self, store: &TaskStore, tasks: &[Task]) -> Result<bool, StoreError> {
+        if self.done.is_empty() && self.skipped.is_empty() {
+            return Ok(false);
+        }
+        while let Some(task) = self.next_step(tasks) {
+            self.skipped.push(task.id);
+        }
+        self.finish(store)?;
+        Ok(true)
+    }
+}
+
+impl Storable for Routine {
+
+    fn get_display_fields(&self) -> Vec<String> {
+        vec![
+            self.name.clone(),
+            format!("{} steps", self.task_ids.len()),
+            self.desc.clone(),
+        ]
+    }
+
+    fn get_timestamp(&self) -> String {
+        Clock::get().format(self.timestamp, &Config::get().timestamp_format)
+    }
+
+    fn get_datetime(&self) -> DateTime<Utc> {
+        self.timestamp
+    }
+
+    fn to_key(&self) -> String {
+        format!("routine:{}", self.id)
+    }
+
+    fn from_bytes(bytes: &[u8]) -> serde_json::Result<Self> {
+        serde_json::from_slice(bytes)
+    }
+
+    fn to_bytes(&self) -> serde_json::Result<Vec<u8>> {
+        serde_json::to_vec(self)
+    }
+
+    fn to_toml(&self) -> Result<String, Box<dyn Error>> {
+        let toml_string = toml::to_string(self)?;
+        Ok(toml_string)
+    }
+
+    fn from_toml(toml_string: String) -> Result<Self, Box<dyn Error>> {
+        let routine = toml::from_str(&toml_string)?;
+        Ok(routine)
+    }
+
+    fn is_legal_update_from(&self, other: &Self) -> bool {
+        other.id == self.id && other.timestamp == self.timestamp
+    }
+}
+
+impl Storable for RoutineRun {
+
+    fn get_display_fields(&self) -> Vec<String> {
+        vec![
+            self.get_timestamp(),
+            format!("{} done, {} skipped", self.done.len(), self.skipped.len()),
+        ]
+    }
+
+    fn get_timestamp(&self) -> String {
+        Clock::get().format(self.timestamp, &Config::get().timestamp_format)
+    }
+
+    fn get_datetime(&self) -> DateTime<Utc> {
+        self.timestamp
+    }
+
+    fn to_key(&self) -> String {
+        format!("routine_run:{}:{}", self.routine_id, self.id)
+    }
+
+    fn from_bytes(bytes: &[u8]) -> serde_json::Result<Self> {
+        serde_json::from_slice(bytes)
+    }
+
+    fn to_bytes(&self) -> serde_json::Result<Vec<u8>> {
+        serde_json::to_vec(self)
+    }
+
+    fn to_toml(&self) -> Result<String, Box<dyn Error>> {
+        let toml_string = toml::to_string(self)?;
+        Ok(toml_string)
+    }
+
+    fn from_toml(toml_string: String) -> Result<Self, Box<dyn Error>> {
+        let run = toml::from_str(&toml_string)?;
+        Ok(run)
+    }
+
+    fn is_legal_update_from(&self, other: &Self) -> bool {
+        other.id == self.id && other.routine_id == self.routine_id
+    }
+}
+
+impl fmt::Display for Routine {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "• {}", self.name)
+    }
+}
+
+impl fmt::Display for RoutineRun {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} of {} steps done", self.done.len(), self.done.len() + self.skipped.len())
+    }
+}
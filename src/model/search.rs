@@ -0,0 +1,168 @@
+// search.rs
+
+use std::collections::{HashMap, HashSet};
+use crate::model::task::Task;
+use crate::model::task_entry::TaskEntry;
+use crate::model::store::{StoreError, TaskStore};
+
+///////////////////////////////////////////////////////////
+
+/// Marks a store whose entries were all indexed, older stores predate it
+const INDEX_VERSION_KEY: &str = "meta:search_index";
+const INDEX_VERSION: &[u8] = b"1";
+
+/// Exact matches count for more than matches on the start of a longer word
+const EXACT_WEIGHT: u32 = 2;
+
+/// An entry matching every word of a query
+#[derive(Debug, Clone, PartialEq)]
+pub struct SearchHit {
+    pub entry: TaskEntry,
+    pub task: Task,
+    pub score: u32,
+}
+
+/// stateless key pattern for the whole index
+pub fn key_all() -> &'static str {
+    "search:"
+}
+
+/// Key of the posting of `term` in the item at `item_key`
+pub fn index_key(term: &str, item_key: &str) -> String {
+    format!("search:{}:{}", term, item_key)
+}
+
+/// Lowercase words of the text, anything not alphanumeric separates them
+pub fn tokenize(text: &str) -> Vec<String> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .map(|w| w.to_lowercase())
+        .collect()
+}
+
+/// How often each word occurs in the text
+pub fn term_counts(text: &str) -> HashMap<String, u32> {
+    let mut counts = HashMap::new();
+    for term in tokenize(text) {
+        *counts.entry(term).or_default() += 1;
+    }
+    counts
+}
+
+/// Index every entry from scratch, returns how many were indexed
+pub fn reindex(store: &TaskStore) -> Result<usize, StoreError> {
+    store.clear_prefix(key_all())?;
+    let entries: Vec<TaskEntry> = store.get_prefix(TaskEntry::key_all())?;
+    let n = entries.len();
+    for entry in entries {
+        store.put(entry)?;
+    }
+    store.put_bytes(INDEX_VERSION_KEY, INDEX_VERSION)?;
+    Ok(n)
+}
+
+/// Entries matching every word of the query, each word may be the start of
+/// a longer one. Best matches first, ties go to the most recent entry.
+pub fn search(store: &TaskStore, query: &str) -> Result<Vec<SearchHit>, StoreError> {
+
+    if !store.contains_key(INDEX_VERSION_KEY)? {
+        reindex(store)?;
+    }
+
+    let mut terms = tokenize(query);
+    terms.dedup();
+    if terms.is_empty() {
+        return Ok(vec![]);
+    }
+
+    // item key -> (score, which query words it matched)
+    let mut scores: HashMap<String, (u32, HashSet<usize>)> = HashMap::new();
+    for (i, term) in terms.iter().enumerate() {
+        for (word, item_key, count) in store.postings(term)? {
+            let weight = if word == *term { EXACT_WEIGHT } else { 1 };
+            let (score, matched) = scores.entry(item_key).or_default();
+            *score += count * weight;
+            matched.insert(i);
+        }
+    }
+
+    let mut tasks: HashMap<uuid::Uuid, Option<Task>> = HashMap::new();
+    let mut hits = vec![];
+    for (item_key, (score, matched)) in scores {
+        if matched.len() < terms.len() {
+            continue;
+        }
+        // the index may briefly point at something deleted under another key
+        let Some(entry) = store.get::<TaskEntry>(item_key)? else {
+            continue;
+        };
+        let task = match tasks.get(&entry.task_id) {
+            Some(task) => task.clone(),
+            None => {
+                let task: Option<Task> = store.get(Task::key_task(entry.task_id))?;
+                tasks.insert(entry.task_id, task.clone());
+                task
+            }
+        };
+        if let Some(task) = task {
+            hits.push(SearchHit { entry, task, score });
+        }
+    }
+
+    hits.sort_by(|a, b| b.score.cmp(&a.score).then(b.entry.timestamp.cmp(&a.entry.timestamp)));
+    Ok(hits)
+}
+
+/// Up to `width` characters of `text` around the first match of the query,
+/// split into (text, is a match) pieces for highlighting
+pub fn snippet(text: &str, query: &str, width: usize) -> Vec<(String, bool)> {
+
+    let terms = tokenize(query);
+    let chars: Vec<char> = text.chars()
+        .map(|c| if c.is_whitespace() { ' ' } else { c })
+        .collect();
+
+    // char ranges of the words that match
+    let mut matches = vec![];
+    let mut start = None;
+    for (i, c) in chars.iter().chain([' '].iter()).enumerate() {
+        match (c.is_alphanumeric(), start) {
+            (true, None) => start = Some(i),
+            (false, Some(s)) => {
+                let word: String = chars[s..i].iter().collect::<String>().to_lowercase();
+                if terms.iter().any(|t| word.starts_with(t.as_str())) {
+                    matches.push((s, i));
+                }
+                start = None;
+            }
+            _ => {}
+        }
+    }
+
+    // show a little of what leads up to the first match
+    let first = matches.first().map_or(0, |m| m.0);
+    let from = first.saturating_sub(width / 4);
+    let to = chars.len().min(from + width);
+
+    let mut pieces: Vec<(String, bool)> = vec![];
+    let mut push = |s: &[char], hit: bool| {
+        if !s.is_empty() {
+            pieces.push((s.iter().collect(), hit));
+        }
+    };
+    if from > 0 {
+        push(&['…'], false);
+    }
+    let mut at = from;
+    for (s, e) in matches.into_iter().filter(|(s, e)| *e > from && *s < to) {
+        let (s, e) = (s.max(from), e.min(to));
+        push(&chars[at..s], false);
+        push(&chars[s..e], true);
+        at = e;
+    }
+    push(&chars[at..to], false);
+    if to < chars.len() {
+        push(&['…'], false);
+    }
+    pieces
+}
@@ -0,0 +1,125 @@
+// stats.rs
+
+use std::collections::BTreeMap;
+use chrono::{DateTime, Datelike, Duration, NaiveDate, Utc};
+use crate::model::clock::Clock;
+use crate::model::task::{Task, TaskTy};
+use crate::model::task_entry::TaskEntry;
+use crate::model::pause::Pause;
+
+///////////////////////////////////////////////////////////
+
+/// How much was logged on each day. Numeric tasks sum their values,
+/// every other kind of task counts its entries.
+pub fn daily_totals(task: &Task, entries: &[TaskEntry], clock: &Clock)
+    -> BTreeMap<NaiveDate, f64>
+{
+    let mut totals: BTreeMap<NaiveDate, f64> = BTreeMap::new();
+    for entry in entries {
+        let amount = match task.task_type {
+            TaskTy::Numeric => entry.get_value().unwrap_or(0.0),
+            _ => 1.0,
+        };
+        *totals.entry(clock.day_of(entry.timestamp)).or_default() += amount;
+    }
+    totals
+}
+
+/// Summary of a task's history
+#[derive(Debug, Clone, PartialEq)]
+pub struct TaskStats {
+    pub entries: usize,
+    pub periods: usize,         // finished periods since the task was started, less paused ones
+    pub periods_met: usize,     // finished periods that reached the target
+    pub weekdays: [u64; 7],     // entries per weekday, Monday first
+    pub min: Option<f64>,       // numeric tasks only
+    pub max: Option<f64>,
+    pub mean: Option<f64>,
+}
+
+impl TaskStats {
+
+    pub fn compute(
+        task: &Task,
+        entries: &[TaskEntry],
+        pauses: &[Pause],
+        clock: &Clock,
+        now: DateTime<Utc>
+    ) -> Self {
+        let days: Vec<NaiveDate> = entries.iter()
+            .map(|e| clock.day_of(e.timestamp))
+            .collect();
+
+        let mut weekdays = [0u64; 7];
+        for day in &days {
+            weekdays[day.weekday().num_days_from_monday() as usize] += 1;
+        }
+
+        // walk the finished periods from whichever came first, the task or
+        // a backdated entry
+        let period = &task.eval_period;
+        let today = clock.day_of(now);
+        let target = task.target.max(1) as usize;
+        let first = days.iter().copied()
+            .chain(std::iter::once(clock.day_of(task.timestamp)))
+            .min()
+            .unwrap_or(today);
+
+        let (mut periods, mut periods_met) = (0, 0);
+        let mut start = period.start_of(first);
+        while let Some(from) = start {
+            let Some(to) = period.next_start(from).filter(|to| *to <= today) else {
+                break;
+            };
+            let met = days.iter().filter(|d| **d >= from && **d < to).count() >= target;
+            let on_hold = pauses.iter().any(|p| p.overlaps(task.id, from, to));
+            if met || !on_hold {
+                periods += 1;
+                periods_met += met as usize;
+            }
+            start = Some(to);
+        }
+
+        let values: Vec<f64> = match task.task_type {
+            TaskTy::Numeric => entries.iter().filter_map(|e| e.get_value()).collect(),
+            _ => vec![],
+        };
+        let (min, max, mean) = if values.is_empty() {
+            (None, None, None)
+        } else {
+            (
+                values.iter().copied().reduce(f64::min),
+                values.iter().copied().reduce(f64::max),
+                Some(values.iter().sum::<f64>() / values.len() as f64),
+            )
+        };
+
+        TaskStats { entries: entries.len(), periods, periods_met, weekdays, min, max, mean }
+    }
+
+    /// Share of finished periods the target was met in
+    pub fn completion_rate(&self) -> Option<f64> {
+        match self.periods {
+            0 => None,
+            n => Some(self.periods_met as f64 / n as f64),
+        }
+    }
+}
+
+/// Daily totals for the `n` days ending with `end`, oldest first
+pub fn recent_days(totals: &BTreeMap<NaiveDate, f64>, end: NaiveDate, n: usize) -> Vec<f64> {
+    (0..n as i64).rev()
+        .map(|back| end - Duration::days(back))
+        .map(|day| totals.get(&day).copied().unwrap_or(0.0))
+        .collect()
+}
+
+/// Which of `shades` levels a day's value gets relative to the busiest
+/// day, 0 for nothing logged
+pub fn shade_level(value: f64, max: f64, shades: usize) -> usize {
+    if value <= 0.0 || max <= 0.0 {
+        return 0;
+    }
+    let steps = (shades - 1) as f64;
+    ((value / max * steps).ceil() as usize).clamp(1, shades - 1)
+}
@@ -3,9 +3,26 @@ use crate::model::convert::Storable;
 use sled::IVec;
 use serde_json::Error as SerdeError;
 use std::error::Error;
+use std::fmt;
+use std::collections::{BTreeMap, BTreeSet};
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+use std::str::FromStr;
 use csv::Writer;
-use crate::model::task::Task;
+use crate::model::task::TaskTy;
+use crate::model::task::{Task, TaskFilter};
 use crate::model::task_entry::TaskEntry;
+use crate::model::search;
+use crate::model::clock::Clock;
+use crate::model::dump::Dump;
+use crate::config::Config;
+use crate::model::hooks::{Hooks, Record};
+use crate::model::pause::Pause;
+use crate::model::routine::Routine;
+use crate::model::order::TaskOrder;
+
+/// Key of the ID of a store, see `TaskStore::store_id`
+const STORE_ID_KEY: &str = "meta:store_id";
 
 ///////////////////////////////////////////////////////////
 /// Custom error enum to aggregate error types
@@ -13,8 +30,25 @@ use crate::model::task_entry::TaskEntry;
 pub enum StoreError {
     SerdeError(SerdeError),
     SledError(sled::Error),
+    NotFound(&'static str, String),         // nothing of a kind matched a lookup
+    Ambiguous(&'static str, String, usize), // a lookup matched several items
+}
+
+impl fmt::Display for StoreError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StoreError::SerdeError(e) => write!(f, "serialization error: {}", e),
+            StoreError::SledError(e) => write!(f, "database error: {}", e),
+            StoreError::NotFound(kind, q) => write!(f, "no {} matches '{}'", kind, q),
+            StoreError::Ambiguous(kind, q, n) => {
+                write!(f, "'{}' matches {} {}s, use more of the name or ID", q, n, kind)
+            }
+        }
+    }
 }
 
+impl Error for StoreError {}
+
 impl From<SerdeError> for StoreError {
     fn from(err: SerdeError) -> Self {
         StoreError::SerdeError(err)
@@ -29,37 +63,188 @@ impl From<sled::Error> for StoreError {
 
 ///////////////////////////////////////////////////////////
 
+/// Add the postings of `text` under the item at `key`
+fn index(batch: &mut sled::Batch, key: &str, text: &str) {
+    for (term, count) in search::term_counts(text) {
+        batch.insert(search::index_key(&term, key).as_str(), &count.to_be_bytes());
+    }
+}
+
+/// Drop the postings of a previous version of the item at `key`
+fn unindex(batch: &mut sled::Batch, key: &str, text: Option<String>) {
+    for term in text.iter().flat_map(|t| search::term_counts(t).into_keys()) {
+        batch.remove(search::index_key(&term, key).as_str());
+    }
+}
+
+/// When the item at a key was last changed, see `TaskStore::modified`
+pub fn modified_key(key: &str) -> String {
+    format!("modified:{}", key)
+}
+
+/// When the item at a key was deleted, kept so syncs can pass deletions on
+/// instead of bringing the item back, see `TaskStore::deleted`
+pub fn deleted_key(key: &str) -> String {
+    format!("deleted:{}", key)
+}
+
+/// Stage a PUT of `item`, reindexing it against the version in `db`. A
+/// change of the stored bytes is stamped with `at`.
+fn stage_put<T: Storable>(db: &sled::Db, batch: &mut sled::Batch, item: &T, at: DateTime<Utc>)
+    -> Result<(), StoreError>
+{
+    let bytes = item.to_bytes()?;
+    let key = item.to_key();
+    let old = db.get(&key)?;
+    if let Some(text) = item.search_text() {
+        if let Some(old) = old.as_ref().and_then(|b| T::from_bytes(b).ok()) {
+            unindex(batch, &key, old.search_text());
+        }
+        index(batch, &key, &text);
+    }
+    if old.is_none_or(|old| *old != *bytes) {
+        batch.insert(modified_key(&key).as_str(), at.to_rfc3339().as_bytes());
+        batch.remove(deleted_key(&key).as_str());
+    }
+    batch.insert(key.as_str(), IVec::from(bytes));
+    Ok(())
+}
+
+/// Stage a DELETE of `item`, leaving a tombstone stamped with `at`. The
+/// postings dropped are those of the version in `db`, the caller's copy
+/// may be older.
+fn stage_remove<T: Storable>(db: &sled::Db, batch: &mut sled::Batch, item: &T, at: DateTime<Utc>) {
+    let key = item.to_key();
+    let stored = db.get(&key).ok().flatten().and_then(|b| T::from_bytes(&b).ok());
+    unindex(batch, &key, stored.as_ref().unwrap_or(item).search_text());
+    batch.remove(key.as_str());
+    batch.remove(modified_key(&key).as_str());
+    batch.insert(deleted_key(&key).as_str(), at.to_rfc3339().as_bytes());
+}
+
+/// Writes gathered up to be applied all at once, see `TaskStore::commit`
+#[derive(Default)]
+pub struct WriteBatch {
+    batch: sled::Batch,
+    len: usize,
+}
+
+impl WriteBatch {
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+}
+
 pub struct TaskStore {
     db: Mutex<sled::Db>,
+    hooks: Option<Hooks>,   // run on changes, see `with_hooks`
+    id: Uuid,               // see `store_id`
 }
 
 impl TaskStore {
     
     /// Create a DB
     pub fn new(db_path: &str) -> Self {
-        TaskStore {
-            db: sled::open(db_path).unwrap().into()
-        }
+        TaskStore::open(db_path).unwrap()
+    }
+
+    /// Open or create the DB at `db_path`, failing if another process has
+    /// it open
+    pub fn open(db_path: &str) -> Result<Self, StoreError> {
+        let db = sled::open(db_path)?;
+        // made once, so reading it later never writes
+        let id = match db.get(STORE_ID_KEY)?.and_then(|b| Uuid::from_slice(&b).ok()) {
+            Some(id) => id,
+            None => {
+                let id = Uuid::new_v4();
+                db.insert(STORE_ID_KEY, id.as_bytes())?;
+                id
+            }
+        };
+        Ok(TaskStore {
+            db: db.into(),
+            hooks: None,
+            id,
+        })
+    }
+
+    /// Run `hooks` on every put and delete made through this store. Batches
+    /// don't run them.
+    pub fn with_hooks(mut self, hooks: Hooks) -> Self {
+        self.hooks = Some(hooks).filter(|h| !h.is_empty());
+        self
     }
 
-    /// Singleton instance of TaskManager
-    pub fn instance(/* db_path: &str */) -> &'static TaskStore {
-        let db_path = "./scratch/patrack.db";
+    /// Singleton instance of TaskManager, at the configured path
+    pub fn instance() -> &'static TaskStore {
+        let db_path = &Config::get().db_path;
 
         static INSTANCE: OnceLock<TaskStore> = OnceLock::new();
         
-        INSTANCE.get_or_init(|| TaskStore::new(db_path))
+        INSTANCE.get_or_init(|| {
+            TaskStore::new(&db_path.to_string_lossy()).with_hooks(Config::get().hooks.clone())
+        })
     } 
     
     /// Idempotent PUT 
     pub fn put<T: Storable>(&self, item: T) -> Result<T, StoreError> { 
-        let bytes = item.to_bytes()?;
-         
-        self.db.lock()
-            .unwrap()
-            .insert(item.to_key(), IVec::from(bytes))?;
+        let db = self.db.lock().unwrap();
+        let existed = self.hooks.is_some() && db.contains_key(item.to_key())?;
+
+        // the item and its postings change together
+        let mut batch = sled::Batch::default();
+        stage_put(&db, &mut batch, &item, Utc::now())?;
+        db.apply_batch(batch)?;
+        drop(db);
+
+        if let Some(hooks) = &self.hooks {
+            hooks.after_put(self, &item, existed);
+        }
         Ok(item)
     }
+
+    /// Add a PUT to the batch, nothing is written until it is committed.
+    /// Stage an item at most once per batch, its postings are worked out
+    /// against what is stored now.
+    pub fn stage<T: Storable>(&self, batch: &mut WriteBatch, item: &T) -> Result<(), StoreError> {
+        self.stage_at(batch, item, Utc::now())
+    }
+
+    /// `stage`, as if the change was made at `at`
+    pub fn stage_at<T: Storable>(&self, batch: &mut WriteBatch, item: &T, at: DateTime<Utc>)
+        -> Result<(), StoreError>
+    {
+        stage_put(&self.db.lock().unwrap(), &mut batch.batch, item, at)?;
+        batch.len += 1;
+        Ok(())
+    }
+
+    /// Add a DELETE of `item` to the batch, hooks don't fire for it
+    pub fn stage_delete<T: Storable>(&self, batch: &mut WriteBatch, item: &T) {
+        self.stage_delete_at(batch, item, Utc::now());
+    }
+
+    /// `stage_delete`, as if the item was deleted at `at`
+    pub fn stage_delete_at<T: Storable>(&self, batch: &mut WriteBatch, item: &T, at: DateTime<Utc>) {
+        stage_remove(&self.db.lock().unwrap(), &mut batch.batch, item, at);
+        batch.len += 1;
+    }
+
+    /// Add a write of raw bytes to the batch, for bookkeeping that isn't a
+    /// Storable. It isn't counted in the batch's `len`.
+    pub fn stage_bytes(&self, batch: &mut WriteBatch, key: &str, bytes: &[u8]) {
+        batch.batch.insert(key, bytes);
+    }
+
+    /// Apply every write of the batch, or none of them
+    pub fn commit(&self, batch: WriteBatch) -> Result<(), StoreError> {
+        self.db.lock().unwrap().apply_batch(batch.batch)?;
+        Ok(())
+    }
     
     /// Key ranged GET for retreiving multiple entities
     pub fn get_prefix<T>(&self, prefix: impl Into<String> + AsRef<[u8]>)
@@ -76,7 +261,7 @@ impl TaskStore {
             .map(|(_k, v)| T::from_bytes(&v).map_err(StoreError::from))
             .collect::<Result<Vec<T>, StoreError>>()?;
 
-        results.sort_by(|a, b| b.get_timestamp().cmp(&a.get_timestamp()));
+        results.sort_by_key(|b| std::cmp::Reverse(b.get_datetime()));
         
         Ok(results)
     } 
@@ -94,14 +279,115 @@ impl TaskStore {
     }
     
 
-    /// Delete a storable item
+    /// Find a task by name, ignoring case, or by a prefix of its ID
+    pub fn find_task(&self, query: &str) -> Result<Task, StoreError> {
+        let tasks: Vec<Task> = self.get_prefix(Task::key_all())?;
+
+        let by_name: Vec<&Task> = tasks.iter()
+            .filter(|t| t.name.eq_ignore_ascii_case(query))
+            .collect();
+        let matches: Vec<&Task> = if by_name.is_empty() {
+            tasks.iter()
+                .filter(|t| !query.is_empty() && t.id.to_string().starts_with(query))
+                .collect()
+        } else {
+            by_name
+        };
+
+        match matches.as_slice() {
+            [task] => Ok((*task).clone()),
+            [] => Err(StoreError::NotFound("task", query.to_string())),
+            many => Err(StoreError::Ambiguous("task", query.to_string(), many.len())),
+        }
+    }
+
+    /// Whether a task other than `except` already goes by `name`, ignoring case
+    pub fn name_taken(&self, name: &str, except: Option<Uuid>) -> Result<bool, StoreError> {
+        let tasks: Vec<Task> = self.get_prefix(Task::key_all())?;
+        Ok(tasks.iter().any(|t| Some(t.id) != except && t.name.eq_ignore_ascii_case(name)))
+    }
+
+    /// Find an entry of any task by a prefix of its ID
+    pub fn find_entry(&self, query: &str) -> Result<TaskEntry, StoreError> {
+        let entries: Vec<TaskEntry> = self.get_prefix(TaskEntry::key_all())?;
+        let mut matches: Vec<TaskEntry> = entries.into_iter()
+            .filter(|e| !query.is_empty() && e.id.to_string().starts_with(query))
+            .collect();
+
+        match matches.len() {
+            1 => Ok(matches.remove(0)),
+            0 => Err(StoreError::NotFound("entry", query.to_string())),
+            n => Err(StoreError::Ambiguous("entry", query.to_string(), n)),
+        }
+    }
+
+    /// Delete a task along with its entries and pauses, dropping it from
+    /// routines and the saved order, all at once. Returns how many entries went.
+    pub fn delete_task(&self, task: &Task) -> Result<usize, StoreError> {
+        let mut batch = WriteBatch::default();
+        let entries = self.stage_delete_tasks(&mut batch, std::slice::from_ref(task), Utc::now())?;
+        self.commit(batch)?;
+
+        if let Some(hooks) = &self.hooks {
+            for entry in &entries {
+                hooks.after_delete(entry);
+            }
+            hooks.after_delete(task);
+        }
+        Ok(entries.len())
+    }
+
+    /// Add the deletes of `delete_task` for each of `tasks` to the batch,
+    /// returns the entries that go with them
+    pub fn stage_delete_tasks(&self, batch: &mut WriteBatch, tasks: &[Task], at: DateTime<Utc>)
+        -> Result<Vec<TaskEntry>, StoreError>
+    {
+        let gone = |id: &Uuid| tasks.iter().any(|t| t.id == *id);
+
+        let mut entries = vec![];
+        for task in tasks {
+            let mut of_task: Vec<TaskEntry> = self.get_prefix(TaskEntry::key_task(task.id))?;
+            for entry in &of_task {
+                self.stage_delete_at(batch, entry, at);
+            }
+            self.stage_delete_at(batch, task, at);
+            entries.append(&mut of_task);
+        }
+
+        let pauses: Vec<Pause> = self.get_prefix(Pause::key_all())?;
+        for pause in pauses.iter().filter(|p| p.task_id.as_ref().is_some_and(gone)) {
+            self.stage_delete_at(batch, pause, at);
+        }
+        let routines: Vec<Routine> = self.get_prefix(Routine::key_all())?;
+        for mut routine in routines.into_iter().filter(|r| r.task_ids.iter().any(gone)) {
+            routine.task_ids.retain(|id| !gone(id));
+            self.stage_at(batch, &routine, at)?;
+        }
+        if let Some(mut order) = self.get::<TaskOrder>(TaskOrder::key())? {
+            if order.ids.iter().any(gone) {
+                order.ids.retain(|id| !gone(id));
+                order.timestamp = at;
+                self.stage_at(batch, &order, at)?;
+            }
+        }
+        Ok(entries)
+    }
+
+    /// Delete a storable item. A task takes its dependents with it, see
+    /// `delete_task`.
     pub fn delete_item<T: Storable>(&self, item: &T) -> Result<(), StoreError> {
-        
-        /// TODO: Handle cascade!
-        self.db
-            .lock()
-            .unwrap()
-            .remove(item.to_key())?;
+        if let Some(Record::Task(task)) = item.hook_record() {
+            return self.delete_task(&task).map(|_| ());
+        }
+
+        let mut batch = sled::Batch::default();
+        let db = self.db.lock().unwrap();
+        stage_remove(&db, &mut batch, item, Utc::now());
+        db.apply_batch(batch)?;
+        drop(db);
+        if let Some(hooks) = &self.hooks {
+            hooks.after_delete(item);
+        }
         Ok(()) 
     }
    
@@ -114,6 +400,86 @@ impl TaskStore {
         Ok(()) 
     }
     
+    /// Postings of every indexed term starting with `prefix`, as
+    /// (term, item key, term count) triples
+    pub fn postings(&self, prefix: &str) -> Result<Vec<(String, String, u32)>, StoreError> {
+        let db = self.db.lock().unwrap();
+        let mut postings = vec![];
+        for pair in db.scan_prefix(format!("{}{}", search::key_all(), prefix)) {
+            let (key, value) = pair?;
+            let key = String::from_utf8_lossy(&key);
+            let Some((term, item_key)) = key[search::key_all().len()..].split_once(':') else {
+                continue;
+            };
+            let count = value.as_ref().try_into().map_or(1, u32::from_be_bytes);
+            postings.push((term.to_string(), item_key.to_string(), count));
+        }
+        Ok(postings)
+    }
+
+    /// Remove every key under a prefix
+    pub fn clear_prefix(&self, prefix: &str) -> Result<(), StoreError> {
+        let db = self.db.lock().unwrap();
+        let mut batch = sled::Batch::default();
+        for key in db.scan_prefix(prefix).keys() {
+            batch.remove(key?);
+        }
+        db.apply_batch(batch)?;
+        Ok(())
+    }
+
+    /// When each item under `prefix` last changed here, by key. Items
+    /// stored before changes were stamped have none.
+    pub fn modified(&self, prefix: &str) -> Result<BTreeMap<String, DateTime<Utc>>, StoreError> {
+        self.stamps(&modified_key(prefix))
+    }
+
+    /// When each item under `prefix` that was deleted here went, by key
+    pub fn deleted(&self, prefix: &str) -> Result<BTreeMap<String, DateTime<Utc>>, StoreError> {
+        self.stamps(&deleted_key(prefix))
+    }
+
+    fn stamps(&self, prefix: &str) -> Result<BTreeMap<String, DateTime<Utc>>, StoreError> {
+        let mut stamps = BTreeMap::new();
+        for pair in self.db.lock().unwrap().scan_prefix(prefix) {
+            let (key, value) = pair?;
+            let key = String::from_utf8_lossy(&key);
+            let at = DateTime::parse_from_rfc3339(&String::from_utf8_lossy(&value));
+            if let (Some((_, key)), Ok(at)) = (key.split_once(':'), at) {
+                stamps.insert(key.to_string(), at.with_timezone(&Utc));
+            }
+        }
+        Ok(stamps)
+    }
+
+    /// The raw bytes at a key, for bookkeeping that isn't a Storable
+    pub fn get_bytes(&self, key: &str) -> Result<Option<Vec<u8>>, StoreError> {
+        Ok(self.db.lock().unwrap().get(key)?.map(|b| b.to_vec()))
+    }
+
+    /// An ID telling this store apart from others
+    pub fn store_id(&self) -> Uuid {
+        self.id
+    }
+
+    /// Does anything live at the key
+    pub fn contains_key(&self, key: &str) -> Result<bool, StoreError> {
+        Ok(self.db.lock().unwrap().contains_key(key)?)
+    }
+
+    /// Store raw bytes, for bookkeeping that isn't a Storable
+    pub fn put_bytes(&self, key: &str, bytes: &[u8]) -> Result<(), StoreError> {
+        self.db.lock().unwrap().insert(key, bytes)?;
+        Ok(())
+    }
+
+    /// Write pending changes to disk. sled flushes on its own every so often,
+    /// short lived processes must call this before exiting.
+    pub fn flush(&self) -> Result<(), StoreError> {
+        self.db.lock().unwrap().flush()?;
+        Ok(())
+    }
+
     ///
     pub fn truncate(&self) { 
         let db = self.db.lock().unwrap(); 
@@ -133,45 +499,176 @@ impl TaskStore {
     }
     
     /// export the DB to a CSV
-    pub fn to_csv(&self, file_path: &str) -> Result<(), Box<dyn Error>>{
-        
-        let mut writer = csv::Writer::from_path(file_path)?;
-        let mut columns: Vec<Vec<String>> = vec![]; 
-        let tasks: Vec<Task> = self.get_prefix(Task::key_all()).unwrap(); 
-    
-        for task in &tasks {
-            let entries: Vec<String> = task.get_entries()
-                .iter()
-                .map(|e| e.get_content().replace("\n", ""))
-                .collect();
-            
-            columns.push(entries); 
+    pub fn to_csv(&self, file_path: &str, filter: &TaskFilter, layout: CsvLayout)
+        -> Result<(), Box<dyn Error>>
+    {
+        let file = std::fs::File::create(file_path)?;
+        self.write_csv(file, filter, layout)
+    }
+
+    /// write the CSV export, e.g. to stdout
+    pub fn write_csv<W: std::io::Write>(&self, writer: W, filter: &TaskFilter, layout: CsvLayout)
+        -> Result<(), Box<dyn Error>>
+    {
+        let mut writer = csv::Writer::from_writer(writer);
+        let clock = Clock::get();
+        let mut kept: Vec<(Task, Vec<TaskEntry>)> = vec![];
+        for task in self.get_prefix::<Task>(Task::key_all())? {
+            let entries: Vec<TaskEntry> = self.get_prefix(TaskEntry::key_task(task.id))?;
+            let Some(selected) = filter.select(&task, &entries, clock) else {
+                continue;
+            };
+            let selected = selected.into_iter().cloned().collect();
+            kept.push((task, selected));
         }
-        
-        // determine max number of entries for task, which gives n rows
-        let max_len = columns.iter().map(|col| col.len()).max().unwrap_or(0);
-        
-        // write header row
-        let names: Vec<&String> = tasks.iter().map(|t| &t.name).collect();
-        writer.write_record(&names);
-
-        // write each row into the CSV
-        for i in 0..max_len {
- 
-            let row: Vec<String> = columns.iter()
-                .map(|col| col.get(i).cloned().unwrap_or_else(|| "".to_string()))
-                .collect();
-            
-            writer.write_record(&row);
-        }
-         
-        Ok(()) 
+
+        match layout {
+            CsvLayout::Wide => write_wide(&mut writer, &kept)?,
+            CsvLayout::Long => write_long(&mut writer, &kept, clock)?,
+        }
+        writer.flush()?;
+        Ok(())
     }
     
     /// export the DB to a JSON file
-    pub fn to_json(&self, file_path: &str) -> Result<(), Box<dyn Error>> { 
-        panic!("Not implemented error!");
-        Ok(()) 
+    pub fn to_json(&self, file_path: &str, filter: &TaskFilter) -> Result<(), Box<dyn Error>> { 
+        let file = std::fs::File::create(file_path)?;
+        self.write_json(std::io::BufWriter::new(file), filter)
+    }
+
+    /// write the JSON export, e.g. to stdout
+    pub fn write_json<W: std::io::Write>(&self, mut writer: W, filter: &TaskFilter)
+        -> Result<(), Box<dyn Error>>
+    {
+        let dump = Dump::collect(self, filter)?;
+        serde_json::to_writer_pretty(&mut writer, &dump)?;
+        writeln!(writer)?;
+        writer.flush()?;
+        Ok(())
+    }
+}
+
+
+/// How `TaskStore::to_csv` lays out entries
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum CsvLayout {
+    #[default]
+    Wide,   // a column of entry contents per task
+    Long,   // a row per entry with its IDs and time
+}
+
+impl FromStr for CsvLayout {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "wide" => Ok(CsvLayout::Wide),
+            "long" => Ok(CsvLayout::Long),
+            _ => Err(format!("unknown layout '{}', try long or wide", s)),
+        }
+    }
+}
+
+/// Columns of the long layout before the ones taken from structured entries
+pub const LONG_COLUMNS: [&str; 7] = [
+    "task_id", "task_name", "entry_id", "timestamp", "day", "content", "value",
+];
+
+/// One column per task, padded with empty cells. Newlines are dropped and
+/// times are lost, see the long layout for that.
+fn write_wide<W: std::io::Write>(writer: &mut Writer<W>, kept: &[(Task, Vec<TaskEntry>)])
+    -> Result<(), csv::Error>
+{
+    let columns: Vec<Vec<String>> = kept.iter()
+        .map(|(_, entries)| entries.iter().map(|e| e.get_content().replace("\n", "")).collect())
+        .collect();
+
+    // determine max number of entries for task, which gives n rows
+    let max_len = columns.iter().map(|col| col.len()).max().unwrap_or(0);
+
+    // write header row
+    let names: Vec<&String> = kept.iter().map(|(t, _)| &t.name).collect();
+    writer.write_record(&names)?;
+
+    // write each row into the CSV
+    for i in 0..max_len {
+        let row: Vec<String> = columns.iter()
+            .map(|col| col.get(i).cloned().unwrap_or_else(|| "".to_string()))
+            .collect();
+        writer.write_record(&row)?;
+    }
+    Ok(())
+}
+
+/// A row per entry. Numeric entries fill in `value`, the top level fields
+/// of JSON and TOML entries get a column each.
+fn write_long<W: std::io::Write>(writer: &mut Writer<W>, kept: &[(Task, Vec<TaskEntry>)], clock: &Clock)
+    -> Result<(), csv::Error>
+{
+    let fields = |task: &Task, entry: &TaskEntry| structured_fields(&task.task_type, &entry.get_content());
+
+    let keys: BTreeSet<String> = kept.iter()
+        .flat_map(|(task, entries)| entries.iter().flat_map(move |e| fields(task, e)))
+        .map(|(key, _)| key)
+        .collect();
+    let keys: Vec<String> = keys.into_iter().collect();
+
+    // a field named like a fixed column gets a prefix
+    let header = LONG_COLUMNS.iter().map(|c| c.to_string()).chain(keys.iter().map(|k| {
+        match LONG_COLUMNS.contains(&k.as_str()) {
+            true => format!("data.{}", k),
+            false => k.clone(),
+        }
+    }));
+    writer.write_record(header.collect::<Vec<_>>())?;
+
+    for (task, entries) in kept {
+        for entry in entries {
+            let value = match task.task_type {
+                TaskTy::Numeric => entry.get_value().map(|v| v.to_string()).unwrap_or_default(),
+                _ => String::new(),
+            };
+            let mut row = vec![
+                task.id.to_string(),
+                task.name.clone(),
+                entry.id.to_string(),
+                entry.timestamp.to_rfc3339(),
+                clock.day_of(entry.timestamp).to_string(),
+                entry.get_content(),
+                value,
+            ];
+            let data = fields(task, entry);
+            row.extend(keys.iter().map(|key| {
+                data.iter().find(|(k, _)| k == key).map(|(_, v)| v.clone()).unwrap_or_default()
+            }));
+            writer.write_record(&row)?;
+        }
     }
+    Ok(())
 }
 
+/// Top level fields of a JSON or TOML entry as text, nothing for other
+/// types or content that doesn't parse
+fn structured_fields(ty: &TaskTy, content: &str) -> Vec<(String, String)> {
+    match ty {
+        TaskTy::Json => match serde_json::from_str::<serde_json::Value>(content) {
+            Ok(serde_json::Value::Object(map)) => map.into_iter()
+                .map(|(k, v)| match v {
+                    serde_json::Value::String(s) => (k, s),
+                    v => (k, v.to_string()),
+                })
+                .collect(),
+            _ => vec![],
+        },
+        TaskTy::Toml => match content.parse::<toml::Table>() {
+            Ok(table) => table.into_iter()
+                .map(|(k, v)| match v {
+                    toml::Value::String(s) => (k, s),
+                    v => (k, v.to_string()),
+                })
+                .collect(),
+            Err(_) => vec![],
+        },
+        _ => vec![],
+    }
+}
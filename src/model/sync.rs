@@ -0,0 +1,501 @@
+// sync.rs
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+use std::str::FromStr;
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+use crate::model::convert::Storable;
+use crate::model::dump::Dump;
+use crate::model::import::stable_id;
+use crate::model::pause::Pause;
+use crate::model::routine::Routine;
+use crate::model::store::{StoreError, TaskStore, WriteBatch};
+use crate::model::task::Task;
+use crate::model::task_entry::TaskEntry;
+
+///////////////////////////////////////////////////////////
+
+/// What a record looked like after a sync, when it was deleted
+const GONE: &str = "deleted";
+
+/// Key of what the record at `key` looked like after the last sync with
+/// the store `peer`, a digest of it or `GONE`
+pub fn synced_key(peer: Uuid, key: &str) -> String {
+    format!("synced:{}:{}", peer, key)
+}
+
+/// How to settle a record changed on both sides since the last sync
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum SyncPolicy {
+    #[default]
+    Newest,     // the side that changed it last wins
+    Ask,        // prompt for each one
+}
+
+impl FromStr for SyncPolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "newest" => Ok(SyncPolicy::Newest),
+            "ask" => Ok(SyncPolicy::Ask),
+            _ => Err(format!("unknown policy '{}', try newest or ask", s)),
+        }
+    }
+}
+
+/// Which side of a sync a record is taken from
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Side {
+    Local,
+    Remote,
+}
+
+/// The kinds of record a sync merges, the same as go into a dump
+#[derive(Debug, Clone, PartialEq)]
+pub enum Item {
+    Task(Task),
+    Entry(TaskEntry),
+    Pause(Pause),
+    Routine(Routine),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum State {
+    Live(Item),
+    Deleted,
+}
+
+/// A record both sides changed, with when they did
+#[derive(Debug, Clone, PartialEq)]
+pub struct Conflict {
+    pub key: String,
+    pub local: State,
+    pub local_at: DateTime<Utc>,
+    pub remote: State,
+    pub remote_at: DateTime<Utc>,
+}
+
+/// What is merged with the store, another store or a `packrat export json`
+/// file. Exports can't be written back, changes only come from them.
+pub enum Peer<'a> {
+    Store(&'a TaskStore),
+    Export(Dump),
+}
+
+/// The records and deletions of one side, with when each changed
+#[derive(Debug, Default)]
+pub struct Snapshot {
+    pub id: Option<Uuid>,
+    pub items: BTreeMap<String, Item>,
+    pub modified: BTreeMap<String, DateTime<Utc>>,
+    pub deleted: BTreeMap<String, DateTime<Utc>>,
+}
+
+/// What a sync did, or would do on a dry run
+#[derive(Debug, Default, PartialEq)]
+pub struct SyncReport {
+    pub pulled: usize,          // records added or changed here
+    pub pulled_deleted: usize,
+    pub pushed: usize,          // records added or changed in the other store
+    pub pushed_deleted: usize,
+    pub conflicts: usize,
+}
+
+///////////////////////////////////////////////////////////
+
+impl fmt::Display for SyncReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "from other: {} changed, {} deleted", self.pulled, self.pulled_deleted)?;
+        writeln!(f, "to other:   {} changed, {} deleted", self.pushed, self.pushed_deleted)?;
+        write!(f, "conflicts:  {}", self.conflicts)
+    }
+}
+
+impl fmt::Display for Item {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Item::Task(task) => write!(f, "task {}", task.name),
+            Item::Entry(entry) => write!(f, "entry '{}'", entry.get_content()),
+            Item::Pause(pause) => write!(f, "{}", pause),
+            Item::Routine(routine) => write!(f, "routine {}", routine.name),
+        }
+    }
+}
+
+impl fmt::Display for State {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            State::Live(item) => write!(f, "{}", item),
+            State::Deleted => write!(f, "deleted"),
+        }
+    }
+}
+
+impl fmt::Display for Conflict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let at = |t: &DateTime<Utc>| t.format("%Y-%m-%d %H:%M UTC").to_string();
+        writeln!(f, "{} changed on both sides", self.key)?;
+        writeln!(f, "  here:  {} ({})", self.local, at(&self.local_at))?;
+        write!(f, "  there: {} ({})", self.remote, at(&self.remote_at))
+    }
+}
+
+impl Item {
+
+    fn key(&self) -> String {
+        match self {
+            Item::Task(task) => task.to_key(),
+            Item::Entry(entry) => entry.to_key(),
+            Item::Pause(pause) => pause.to_key(),
+            Item::Routine(routine) => routine.to_key(),
+        }
+    }
+
+    fn datetime(&self) -> DateTime<Utc> {
+        match self {
+            Item::Task(task) => task.get_datetime(),
+            Item::Entry(entry) => entry.get_datetime(),
+            Item::Pause(pause) => pause.get_datetime(),
+            Item::Routine(routine) => routine.get_datetime(),
+        }
+    }
+
+    fn digest(&self) -> Result<String, StoreError> {
+        let bytes = match self {
+            Item::Task(task) => task.to_bytes()?,
+            Item::Entry(entry) => entry.to_bytes()?,
+            Item::Pause(pause) => pause.to_bytes()?,
+            Item::Routine(routine) => routine.to_bytes()?,
+        };
+        Ok(stable_id(&[&String::from_utf8_lossy(&bytes)]).simple().to_string())
+    }
+
+    /// Stage a PUT of the item, stamped with when it changed on its side
+    fn stage(&self, store: &TaskStore, batch: &mut WriteBatch, at: DateTime<Utc>) -> Result<(), StoreError> {
+        match self {
+            Item::Task(task) => store.stage_at(batch, task, at),
+            Item::Entry(entry) => store.stage_at(batch, entry, at),
+            Item::Pause(pause) => store.stage_at(batch, pause, at),
+            Item::Routine(routine) => store.stage_at(batch, routine, at),
+        }
+    }
+
+    /// Stage a DELETE of the item, stamped with when it went on the other side
+    fn unstage(&self, store: &TaskStore, batch: &mut WriteBatch, at: DateTime<Utc>) {
+        match self {
+            Item::Task(task) => store.stage_delete_at(batch, task, at),
+            Item::Entry(entry) => store.stage_delete_at(batch, entry, at),
+            Item::Pause(pause) => store.stage_delete_at(batch, pause, at),
+            Item::Routine(routine) => store.stage_delete_at(batch, routine, at),
+        }
+    }
+
+    /// The task the item belongs to, if any
+    fn task_id(&self) -> Option<Uuid> {
+        match self {
+            Item::Entry(entry) => Some(entry.task_id),
+            Item::Pause(pause) => pause.task_id,
+            Item::Task(_) | Item::Routine(_) => None,
+        }
+    }
+}
+
+impl State {
+    fn digest(&self) -> Result<String, StoreError> {
+        match self {
+            State::Live(item) => item.digest(),
+            State::Deleted => Ok(String::from(GONE)),
+        }
+    }
+
+    /// Does the record belong to one of the `dead` tasks
+    fn of_task_in(&self, dead: &BTreeSet<Uuid>) -> bool {
+        match self {
+            State::Live(item) => item.task_id().is_some_and(|id| dead.contains(&id)),
+            State::Deleted => false,
+        }
+    }
+
+    /// The record with the `dead` tasks taken out of a routine
+    fn without_tasks(mut self, dead: &BTreeSet<Uuid>) -> Self {
+        if let State::Live(Item::Routine(routine)) = &mut self {
+            routine.task_ids.retain(|id| !dead.contains(id));
+        }
+        self
+    }
+}
+
+impl Snapshot {
+
+    pub fn of_store(store: &TaskStore) -> Result<Self, StoreError> {
+        let mut snapshot = Snapshot { id: Some(store.store_id()), ..Snapshot::default() };
+        let items = store.get_prefix::<Task>(Task::key_all())?.into_iter().map(Item::Task)
+            .chain(store.get_prefix::<TaskEntry>(TaskEntry::key_all())?.into_iter().map(Item::Entry))
+            .chain(store.get_prefix::<Pause>(Pause::key_all())?.into_iter().map(Item::Pause))
+            .chain(store.get_prefix::<Routine>(Routine::key_all())?.into_iter().map(Item::Routine));
+        snapshot.items = items.map(|item| (item.key(), item)).collect();
+        for prefix in [Task::key_all(), TaskEntry::key_all(), Pause::key_all(), Routine::key_all()] {
+            snapshot.modified.append(&mut store.modified(prefix)?);
+            snapshot.deleted.append(&mut store.deleted(prefix)?);
+        }
+        Ok(snapshot)
+    }
+
+    /// Records of an export without a change time are taken to have
+    /// changed when it was made. An export from before stores had IDs is
+    /// told apart by that time, so only its first sync has no common base.
+    pub fn of_dump(dump: &Dump) -> Self {
+        let mut items = vec![];
+        for task in &dump.tasks {
+            items.push(Item::Task(task.task.clone()));
+            items.extend(task.entries.iter().map(|e| Item::Entry(e.to_entry(task.task.id))));
+        }
+        items.extend(dump.pauses.iter().cloned().map(Item::Pause));
+        items.extend(dump.routines.iter().cloned().map(Item::Routine));
+
+        let items: BTreeMap<String, Item> = items.into_iter().map(|item| (item.key(), item)).collect();
+        let modified = items.keys()
+            .map(|key| (key.clone(), dump.modified.get(key).copied().unwrap_or(dump.exported)))
+            .collect();
+        let id = dump.store_id.unwrap_or_else(|| stable_id(&["export", &dump.exported.to_rfc3339()]));
+        Snapshot { id: Some(id), items, modified, deleted: dump.deleted.clone() }
+    }
+
+    /// The record at `key` and when it last changed, none if this side has
+    /// never seen it
+    fn state(&self, key: &str) -> Option<(State, DateTime<Utc>)> {
+        if let Some(item) = self.items.get(key) {
+            let at = self.modified.get(key).copied().unwrap_or_else(|| item.datetime());
+            return Some((State::Live(item.clone()), at));
+        }
+        self.deleted.get(key).map(|at| (State::Deleted, *at))
+    }
+
+    fn keys(&self) -> impl Iterator<Item = &String> {
+        self.items.keys().chain(self.deleted.keys())
+    }
+}
+
+/// Last writer wins, the store keeps its version on a tie
+pub fn newest(conflict: &Conflict) -> Side {
+    match conflict.remote_at > conflict.local_at {
+        true => Side::Remote,
+        false => Side::Local,
+    }
+}
+
+/// Merge `peer` into `store` record by record, by ID. A record changed on
+/// one side only since the last sync between the two is taken from that
+/// side, deletions included. Records changed on both are handed to
+/// `choose`. A store peer is brought up to date as well. Nothing is
+/// written on a dry run.
+pub fn sync(store: &TaskStore, peer: &Peer, choose: &mut dyn FnMut(&Conflict) -> Side, dry_run: bool)
+    -> Result<SyncReport, StoreError>
+{
+    let local = Snapshot::of_store(store)?;
+    let (remote, other) = match peer {
+        Peer::Store(other) => (Snapshot::of_store(other)?, Some(*other)),
+        Peer::Export(dump) => (Snapshot::of_dump(dump), None),
+    };
+    let local_id = store.store_id();
+    let mut merge = Merge {
+        store,
+        other,
+        base_id: remote.id,
+        batch: WriteBatch::default(),
+        remote_batch: WriteBatch::default(),
+        gone_here: vec![],
+        gone_there: vec![],
+        choose,
+        report: SyncReport::default(),
+    };
+
+    // tasks go first, whether one is left decides what becomes of the
+    // records that belong to it
+    let keys: BTreeSet<&String> = local.keys().chain(remote.keys()).collect();
+    let (task_keys, keys): (Vec<&String>, Vec<&String>) = keys.into_iter()
+        .partition(|key| key.starts_with(Task::key_all()));
+    let mut settled = vec![];
+    let mut dead = BTreeSet::new();
+    for key in task_keys {
+        let (l, r) = (local.state(key), remote.state(key));
+        let live = [&l, &r].iter().any(|s| matches!(s, Some((State::Live(_), _))));
+        let after = merge.merge(key, l, r)?;
+        if after.as_deref().map_or(!live, |after| after == GONE) {
+            dead.extend(key.strip_prefix(Task::key_all()).and_then(|id| Uuid::parse_str(id).ok()));
+        }
+        settled.push((key, after));
+    }
+
+    // a task deleted on one side takes what belongs to it on the other, so
+    // the records of deleted tasks are neither pulled nor pushed
+    let (here, at) = Merge::unzip(&merge.gone_here);
+    merge.report.pulled_deleted += store.stage_delete_tasks(&mut merge.batch, &here, at)?.len();
+    if let Some(other) = other {
+        let (there, at) = Merge::unzip(&merge.gone_there);
+        merge.report.pushed_deleted += other.stage_delete_tasks(&mut merge.remote_batch, &there, at)?.len();
+    }
+    for key in keys {
+        let (l, r) = (local.state(key), remote.state(key));
+        if [&l, &r].iter().any(|s| s.as_ref().is_some_and(|(s, _)| s.of_task_in(&dead))) {
+            settled.push((key, Some(String::from(GONE))));
+            continue;
+        }
+        let without = |s: Option<(State, DateTime<Utc>)>| s.map(|(s, at)| (s.without_tasks(&dead), at));
+        settled.push((key, merge.merge(key, without(l), without(r))?));
+    }
+
+    let (mut marks, mut remote_marks) = (WriteBatch::default(), WriteBatch::default());
+    for (key, after) in settled {
+        let Some(after) = after else {
+            continue;
+        };
+
+        // what both sides looked like when they last agreed
+        if let Some(id) = remote.id {
+            store.stage_bytes(&mut marks, &synced_key(id, key), after.as_bytes());
+        }
+        if let Some(other) = other {
+            other.stage_bytes(&mut remote_marks, &synced_key(local_id, key), after.as_bytes());
+        }
+    }
+
+    // the marks go last, a sync cut short leaves the next one to redo it
+    // rather than believing the two sides agree
+    if !dry_run {
+        store.commit(merge.batch)?;
+        store.flush()?;
+        if let Some(other) = other {
+            other.commit(merge.remote_batch)?;
+            other.commit(remote_marks)?;
+            other.flush()?;
+        }
+        store.commit(marks)?;
+    }
+    Ok(merge.report)
+}
+
+/// The writes of a sync as they are decided on
+struct Merge<'a> {
+    store: &'a TaskStore,
+    other: Option<&'a TaskStore>,
+    base_id: Option<Uuid>,                      // whose marks tell the last agreed state
+    batch: WriteBatch,
+    remote_batch: WriteBatch,
+    gone_here: Vec<(Task, DateTime<Utc>)>,      // tasks to delete here, with their dependents
+    gone_there: Vec<(Task, DateTime<Utc>)>,     // the same for the other store
+    choose: &'a mut dyn FnMut(&Conflict) -> Side,
+    report: SyncReport,
+}
+
+impl Merge<'_> {
+
+    /// Bring the record at `key` to one state on both sides, returns the
+    /// digest they are left with, none if there's nothing to agree on
+    fn merge(&mut self, key: &str, l: Option<(State, DateTime<Utc>)>, r: Option<(State, DateTime<Utc>)>)
+        -> Result<Option<String>, StoreError>
+    {
+        let after = match (l, r) {
+            (Some(l), Some(r)) => {
+                let base = match self.base_id {
+                    Some(id) => self.store.get_bytes(&synced_key(id, key))?,
+                    None => None,
+                };
+                self.settle(key, l, r, base.map(|b| String::from_utf8_lossy(&b).to_string()))?
+            }
+            // only here, the other store gets it
+            (Some(l @ (State::Live(_), _)), None) if self.other.is_some() => self.push(&State::Deleted, &l)?,
+            // only there
+            (None, Some(r @ (State::Live(_), _))) => self.pull(&State::Deleted, &r)?,
+            _ => return Ok(None),
+        };
+        Ok(Some(after))
+    }
+
+    /// The tasks to delete, and the last time one of them was
+    fn unzip(gone: &[(Task, DateTime<Utc>)]) -> (Vec<Task>, DateTime<Utc>) {
+        let at = gone.iter().map(|(_, at)| *at).max().unwrap_or_else(Utc::now);
+        (gone.iter().map(|(task, _)| task.clone()).collect(), at)
+    }
+
+    /// Bring a record known to both sides to one state, returns the digest
+    /// the other side is left with
+    fn settle(&mut self, key: &str, l: (State, DateTime<Utc>), r: (State, DateTime<Utc>), base: Option<String>)
+        -> Result<String, StoreError>
+    {
+        let (dl, dr) = (l.0.digest()?, r.0.digest()?);
+        if dl == dr {
+            return Ok(dl);
+        }
+        let side = match base {
+            Some(base) if base == dl => Side::Remote,
+            Some(base) if base == dr => Side::Local,
+            _ => {
+                self.report.conflicts += 1;
+                (self.choose)(&Conflict {
+                    key: key.to_string(),
+                    local: l.0.clone(),
+                    local_at: l.1,
+                    remote: r.0.clone(),
+                    remote_at: r.1,
+                })
+            }
+        };
+        match (side, self.other) {
+            (Side::Remote, _) => self.pull(&l.0, &r),
+            (Side::Local, Some(_)) => self.push(&r.0, &l),
+            // an export keeps its version, noted so it isn't asked about again
+            (Side::Local, None) => Ok(dr),
+        }
+    }
+
+    /// Replace the `old` state here with the other side's
+    fn pull(&mut self, old: &State, new: &(State, DateTime<Utc>)) -> Result<String, StoreError> {
+        if let (State::Live(Item::Task(task)), State::Deleted) = (old, &new.0) {
+            self.gone_here.push((task.clone(), new.1));
+            self.report.pulled_deleted += 1;
+            return new.0.digest();
+        }
+        let counts = (&mut self.report.pulled, &mut self.report.pulled_deleted);
+        apply(self.store, &mut self.batch, old, new, counts)
+    }
+
+    /// Replace the `old` state of the other store with this side's
+    fn push(&mut self, old: &State, new: &(State, DateTime<Utc>)) -> Result<String, StoreError> {
+        let Some(other) = self.other else {
+            return new.0.digest();
+        };
+        if let (State::Live(Item::Task(task)), State::Deleted) = (old, &new.0) {
+            self.gone_there.push((task.clone(), new.1));
+            self.report.pushed_deleted += 1;
+            return new.0.digest();
+        }
+        let counts = (&mut self.report.pushed, &mut self.report.pushed_deleted);
+        apply(other, &mut self.remote_batch, old, new, counts)
+    }
+}
+
+/// Stage what brings a store from `old` to `new`, counting it as changed or
+/// deleted. Returns the digest of `new`.
+fn apply(
+    store: &TaskStore,
+    batch: &mut WriteBatch,
+    old: &State,
+    (new, at): &(State, DateTime<Utc>),
+    (changed, deleted): (&mut usize, &mut usize),
+) -> Result<String, StoreError> {
+    match (new, old) {
+        (State::Live(item), _) => {
+            item.stage(store, batch, *at)?;
+            *changed += 1;
+        }
+        (State::Deleted, State::Live(item)) => {
+            item.unstage(store, batch, *at);
+            *deleted += 1;
+        }
+        (State::Deleted, State::Deleted) => {}
+    }
+    new.digest()
+}
@@ -5,8 +5,13 @@ use std::error::Error;
 use uuid::Uuid;
 use serde::{Serialize, Deserialize};
 use std::fmt::Display;
-use chrono::{DateTime, Utc};
+use std::str::FromStr;
+use chrono::{DateTime, Datelike, Duration, NaiveDate, Utc};
 use crate::model::convert::Storable;
+use crate::model::clock::Clock;
+use crate::config::Config;
+use crate::model::hooks::Record;
+use crate::model::query::Query;
 use super::{store::TaskStore, task_entry::TaskEntry}; 
 
 ///////////////////////////////////////////////////////////
@@ -29,6 +34,103 @@ pub enum EvalPeriod {
     BiWeekly,
 }
 
+impl FromStr for TaskTy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "raw" => Ok(TaskTy::Raw),
+            "numeric" => Ok(TaskTy::Numeric),
+            "json" => Ok(TaskTy::Json),
+            "markdown" | "md" => Ok(TaskTy::Markdown),
+            "toml" => Ok(TaskTy::Toml),
+            _ => Err(format!("unknown task type '{}', try raw, numeric, json, markdown or toml", s)),
+        }
+    }
+}
+
+impl FromStr for EvalPeriod {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "any" => Ok(EvalPeriod::Any),
+            "daily" | "day" => Ok(EvalPeriod::Daily),
+            "weekly" | "week" => Ok(EvalPeriod::Weekly),
+            "biweekly" | "fortnight" => Ok(EvalPeriod::BiWeekly),
+            "monthly" | "month" => Ok(EvalPeriod::Monthly),
+            _ => Err(format!("unknown period '{}', try any, daily, weekly, biweekly or monthly", s)),
+        }
+    }
+}
+
+impl EvalPeriod {
+
+    /// First day of the period containing `day`, `Any` has no boundaries.
+    /// Weeks start on Monday and two week periods are counted from the
+    /// Monday of 1970-01-05.
+    pub fn start_of(&self, day: NaiveDate) -> Option<NaiveDate> {
+        match self {
+            EvalPeriod::Any => None,
+            EvalPeriod::Daily => Some(day),
+            EvalPeriod::Weekly => {
+                Some(day - Duration::days(day.weekday().num_days_from_monday() as i64))
+            }
+            EvalPeriod::BiWeekly => {
+                let epoch = NaiveDate::from_ymd_opt(1970, 1, 5).unwrap();
+                let offset = (day - epoch).num_days().rem_euclid(14);
+                Some(day - Duration::days(offset))
+            }
+            EvalPeriod::Monthly => day.with_day(1),
+        }
+    }
+
+    /// First day of the period after the one containing `day`
+    pub fn next_start(&self, day: NaiveDate) -> Option<NaiveDate> {
+        let start = self.start_of(day)?;
+        match self {
+            EvalPeriod::Any => None,
+            EvalPeriod::Daily => Some(start + Duration::days(1)),
+            EvalPeriod::Weekly => Some(start + Duration::days(7)),
+            EvalPeriod::BiWeekly => Some(start + Duration::days(14)),
+            EvalPeriod::Monthly => start.checked_add_months(chrono::Months::new(1)),
+        }
+    }
+
+    /// How the current period is referred to, e.g. "2/3 this week"
+    pub fn label(&self) -> &'static str {
+        match self {
+            EvalPeriod::Any => "total",
+            EvalPeriod::Daily => "today",
+            EvalPeriod::Weekly => "this week",
+            EvalPeriod::BiWeekly => "this fortnight",
+            EvalPeriod::Monthly => "this month",
+        }
+    }
+
+    /// Noun for a single period, used when counting streaks
+    pub fn unit(&self) -> &'static str {
+        match self {
+            EvalPeriod::Any => "period",
+            EvalPeriod::Daily => "day",
+            EvalPeriod::Weekly => "week",
+            EvalPeriod::BiWeekly => "fortnight",
+            EvalPeriod::Monthly => "month",
+        }
+    }
+
+    /// The half open range of instants making up the period that `ts` falls in
+    pub fn bounds(&self, clock: &Clock, ts: DateTime<Utc>)
+        -> Option<(DateTime<Utc>, DateTime<Utc>)>
+    {
+        let day = clock.day_of(ts);
+        Some((
+            clock.start_of_day(self.start_of(day)?),
+            clock.start_of_day(self.next_start(day)?),
+        ))
+    }
+}
+
 #[derive(Serialize, Deserialize, PartialEq, Eq, Debug, Clone)]
 pub struct Task {
     pub id: uuid::Uuid,
@@ -36,7 +138,75 @@ pub struct Task {
     pub desc: String,
     pub timestamp: DateTime<Utc>,
     pub task_type: TaskTy,
-    pub eval_period: EvalPeriod
+    pub eval_period: EvalPeriod,
+    #[serde(default = "default_target")]
+    pub target: u32,        // completions wanted per eval period
+    #[serde(default)]
+    pub tags: Vec<String>,  // lowercase, see `parse_tags`
+    #[serde(default)]
+    pub archived: bool,     // retired, kept for its history
+    #[serde(default)]
+    pub pinned: bool,       // listed above the others
+}
+
+fn default_target() -> u32 { 1 }
+
+/// Which tasks a listing or export covers
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TaskFilter {
+    pub tag: Option<String>,
+    pub include_archived: bool,
+    pub query: Query,
+}
+
+impl TaskFilter {
+    pub fn matches(&self, task: &Task) -> bool {
+        (self.include_archived || !task.archived)
+            && self.tag.as_deref().is_none_or(|tag| task.has_tag(tag))
+            && self.query.matches_task(task)
+    }
+
+    /// Is the entry, of a task that matches, kept
+    pub fn matches_entry(&self, entry: &TaskEntry, clock: &Clock) -> bool {
+        self.query.matches_entry(entry, clock)
+    }
+
+    /// The entries of `task` that are kept, None if the task itself isn't.
+    /// A task is left out when the query asks for entries it has none of.
+    pub fn select<'a>(&self, task: &Task, entries: &'a [TaskEntry], clock: &Clock)
+        -> Option<Vec<&'a TaskEntry>>
+    {
+        if !self.matches(task) {
+            return None;
+        }
+        let kept: Vec<&TaskEntry> = entries.iter()
+            .filter(|e| self.matches_entry(e, clock))
+            .collect();
+        match kept.is_empty() && self.query.has_entry_clauses() {
+            true => None,
+            false => Some(kept),
+        }
+    }
+}
+
+/// Split user input such as "Health, #work" into normalized tags
+pub fn parse_tags(input: &str) -> Vec<String> {
+    let mut tags: Vec<String> = Vec::new();
+    for tag in input.split([',', ' ']) {
+        let tag = tag.trim().trim_start_matches('#').to_lowercase();
+        if !tag.is_empty() && !tags.contains(&tag) {
+            tags.push(tag);
+        }
+    }
+    tags
+}
+
+/// Every tag in use, sorted
+pub fn all_tags(tasks: &[Task]) -> Vec<String> {
+    let mut tags: Vec<String> = tasks.iter().flat_map(|t| t.tags.clone()).collect();
+    tags.sort();
+    tags.dedup();
+    tags
 }
 
 impl Task {
@@ -49,8 +219,18 @@ impl Task {
             timestamp: Utc::now(),
             task_type: TaskTy::Raw,
             eval_period: EvalPeriod::Daily,
+            target: default_target(),
+            tags: vec![],
+            archived: false,
+            pinned: false,
         }
     }
+
+    /// is the task tagged with `tag`, ignoring case and a leading '#'
+    pub fn has_tag(&self, tag: &str) -> bool {
+        let tag = tag.trim_start_matches('#');
+        self.tags.iter().any(|t| t.eq_ignore_ascii_case(tag))
+    }
     
     /// get all the entries for a task
     pub fn get_entries(&self) -> Vec<TaskEntry> {
@@ -77,11 +257,18 @@ impl Storable for Task {
         vec![self.name.clone(), self.get_timestamp(), self.desc.clone()]
     }
 
+    fn hook_record(&self) -> Option<Record> {
+        Some(Record::Task(self.clone()))
+    }
+
     /// storable objects are traceable in time
     fn get_timestamp(&self) -> String {
-        format!("{}",
-            self.timestamp.format("%Y-%m-%d %H:%M:%S"),
-        )
+        Clock::get().format(self.timestamp, &Config::get().timestamp_format)
+    }
+
+    /// creation time, used for ordering
+    fn get_datetime(&self) -> DateTime<Utc> {
+        self.timestamp
     }
     
     /// get key from value 
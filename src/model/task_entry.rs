@@ -5,6 +5,9 @@ use serde::{Serialize, Deserialize};
 use chrono::{DateTime, Utc};
 use std::fmt::Display;
 use crate::model::convert::Storable;
+use crate::model::clock::Clock;
+use crate::config::Config;
+use crate::model::hooks::Record;
 
 #[derive(Serialize, Deserialize, PartialEq, Eq, Debug, Clone)]
 pub struct TaskEntry {
@@ -17,14 +20,29 @@ pub struct TaskEntry {
 impl TaskEntry {
      
     pub fn new<T: Into<Vec<u8>>>(task_id: Uuid, content: T) -> Self {
+        TaskEntry::new_at(task_id, content, Utc::now())
+    }
+
+    /// an entry for something done at another time than now
+    pub fn new_at<T: Into<Vec<u8>>>(task_id: Uuid, content: T, timestamp: DateTime<Utc>) -> Self {
         TaskEntry {
             id: Uuid::new_v4(),
             task_id,
             content: content.into(),
-            timestamp: Utc::now(), 
+            timestamp,
         }
     }
     
+    /// the entry with its content as text, as clients and hooks see it
+    pub fn to_json(&self) -> serde_json::Value {
+        serde_json::json!({
+            "id": self.id,
+            "task_id": self.task_id,
+            "timestamp": self.timestamp,
+            "content": self.get_content(),
+        })
+    }
+
     /// stateless key pattern for retrieving all task entries
     pub fn key_all() -> &'static str {
         "task_entry:"
@@ -45,6 +63,11 @@ impl TaskEntry {
 
         String::from_utf8_lossy(&self.content).to_string()  
     }
+
+    /// the content read as a number, for Numeric tasks
+    pub fn get_value(&self) -> Option<f64> {
+        self.get_content().trim().parse::<f64>().ok()
+    }
 }
 
 impl Display for TaskEntry {
@@ -61,11 +84,23 @@ impl Storable for TaskEntry {
         vec![self.get_timestamp(), self.get_content()]
     }
 
-    /// datetime object was created
+    /// entries are found by their content
+    fn search_text(&self) -> Option<String> {
+        Some(self.get_content())
+    }
+
+    fn hook_record(&self) -> Option<Record> {
+        Some(Record::Entry(self.clone()))
+    }
+
+    /// datetime object was created, in the user's timezone
     fn get_timestamp(&self) -> String {
-        format!("{}",
-            self.timestamp.format("%Y-%m-%d %H:%M:%S"),
-        )
+        Clock::get().format(self.timestamp, &Config::get().timestamp_format)
+    }
+
+    /// when the entry was logged, used for ordering
+    fn get_datetime(&self) -> DateTime<Utc> {
+        self.timestamp
     }
     
     /// fill a KV lookup key
@@ -92,5 +127,10 @@ impl Storable for TaskEntry {
         let task = toml::from_str(&toml_string)?;
         Ok(task)
     }
+
+    /// an entry may move in time, but not between tasks
+    fn is_legal_update_from(&self, other: &Self) -> bool {
+        other.id == self.id && other.task_id == self.task_id
+    }
 }
 
@@ -0,0 +1,157 @@
+// when.rs
+
+use std::fmt;
+use std::error::Error;
+use chrono::{
+    DateTime, Datelike, Duration, NaiveDate, NaiveDateTime, NaiveTime, Timelike, Utc,
+    Weekday,
+};
+use crate::model::clock::Clock;
+
+///////////////////////////////////////////////////////////
+
+/// Date only inputs are placed at noon, well clear of any day rollover
+const DEFAULT_HOUR: u32 = 12;
+
+#[derive(Debug, PartialEq)]
+pub struct WhenError(pub String);
+
+impl fmt::Display for WhenError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "can't understand '{}', try e.g. '2026-03-04 19:30', 'yesterday 7pm' or '3 days ago'",
+            self.0
+        )
+    }
+}
+
+impl Error for WhenError {}
+
+/// Parse a user supplied point in time relative to `now`. Understands
+///
+///   * RFC 3339 and ISO dates with an optional time: `2026-03-04`,
+///     `2026-03-04 19:30`, `2026-03-04T19:30:00-08:00`
+///   * `now`, `today`, `yesterday`, weekday names and `last <weekday>`,
+///     each optionally followed by a time: `yesterday 7pm`, `monday at 9:30`
+///   * `<n> minutes|hours|days|weeks ago`
+///
+/// Days are the user's logical days, so with a 4am rollover `yesterday 2am`
+/// is the early morning after yesterday.
+pub fn parse_when(input: &str, clock: &Clock, now: DateTime<Utc>)
+    -> Result<DateTime<Utc>, WhenError>
+{
+    let text = input.trim().to_lowercase();
+    let err = || WhenError(input.trim().to_string());
+
+    if let Ok(ts) = DateTime::parse_from_rfc3339(&text.to_uppercase()) {
+        return Ok(ts.with_timezone(&Utc));
+    }
+    for fmt in ["%Y-%m-%d %H:%M:%S", "%Y-%m-%dT%H:%M:%S", "%Y-%m-%d %H:%M", "%Y-%m-%dT%H:%M"] {
+        if let Ok(naive) = NaiveDateTime::parse_from_str(&text, fmt) {
+            return Ok(clock.to_utc(naive));
+        }
+    }
+
+    let words: Vec<&str> = text.split_whitespace().filter(|w| *w != "at").collect();
+    let today = clock.day_of(now);
+
+    match words.as_slice() {
+        [] => Err(err()),
+        ["now"] => Ok(now),
+        [n, unit, "ago"] => {
+            let n: i64 = n.parse().map_err(|_| err())?;
+            let span = match unit.trim_end_matches('s') {
+                "minute" | "min" => Duration::try_minutes(n),
+                "hour" | "hr" => Duration::try_hours(n),
+                "day" => Duration::try_days(n),
+                "week" => Duration::try_weeks(n),
+                _ => return Err(err()),
+            };
+            // counts too large for a date are a typo, not a crash
+            span.and_then(|span| now.checked_sub_signed(span)).ok_or_else(err)
+        }
+        [day, rest @ ..] => {
+            let (date, rest) = match parse_day(day, today) {
+                Some(date) => (date, rest),
+                None => match (*day, rest) {
+                    ("last", [weekday, rest @ ..]) => {
+                        let weekday = parse_weekday(weekday).ok_or_else(err)?;
+                        (last_weekday(weekday, today), rest)
+                    }
+                    // a bare time means today
+                    _ => (today, &words[..]),
+                },
+            };
+
+            let time = match rest {
+                [] if date == today => return Ok(now),
+                [] => NaiveTime::from_hms_opt(DEFAULT_HOUR, 0, 0).unwrap(),
+                parts => parse_time(&parts.join("")).ok_or_else(err)?,
+            };
+
+            // times before the rollover hour belong to the night after `date`
+            let date = if time.hour() < clock.day_start {
+                date.succ_opt().ok_or_else(err)?
+            } else {
+                date
+            };
+            Ok(clock.to_utc(date.and_time(time)))
+        }
+    }
+}
+
+fn parse_day(word: &str, today: NaiveDate) -> Option<NaiveDate> {
+    match word {
+        "today" => Some(today),
+        "yesterday" => Some(today - Duration::days(1)),
+        _ => {
+            if let Ok(date) = NaiveDate::parse_from_str(word, "%Y-%m-%d") {
+                return Some(date);
+            }
+            parse_weekday(word).map(|w| last_weekday(w, today))
+        }
+    }
+}
+
+fn parse_weekday(word: &str) -> Option<Weekday> {
+    word.parse::<Weekday>().ok()
+}
+
+/// The most recent `weekday` before `today`
+fn last_weekday(weekday: Weekday, today: NaiveDate) -> NaiveDate {
+    let back = (today.weekday().num_days_from_monday() as i64
+        - weekday.num_days_from_monday() as i64 + 6).rem_euclid(7) + 1;
+    today - Duration::days(back)
+}
+
+/// `7pm`, `7:30pm`, `19:30`, `noon` or `midnight`
+fn parse_time(text: &str) -> Option<NaiveTime> {
+    match text {
+        "noon" => return NaiveTime::from_hms_opt(12, 0, 0),
+        "midnight" => return NaiveTime::from_hms_opt(0, 0, 0),
+        _ => {}
+    }
+
+    let (clock_part, offset) = if let Some(t) = text.strip_suffix("am") {
+        (t, Some(0))
+    } else if let Some(t) = text.strip_suffix("pm") {
+        (t, Some(12))
+    } else {
+        (text, None)
+    };
+
+    let (hour, minute) = match clock_part.split_once(':') {
+        Some((h, m)) => (h.parse::<u32>().ok()?, m.parse::<u32>().ok()?),
+        None => (clock_part.parse::<u32>().ok()?, 0),
+    };
+
+    let hour = match offset {
+        Some(_) if hour == 0 || hour > 12 => return None,
+        Some(offset) => hour % 12 + offset,
+        // a lone number is too ambiguous to be a time
+        None if !clock_part.contains(':') => return None,
+        None => hour,
+    };
+    NaiveTime::from_hms_opt(hour, minute, 0)
+}
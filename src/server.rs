@@ -0,0 +1,434 @@
+// server.rs
+
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+use std::io::{Cursor, Read};
+use std::net::SocketAddr;
+use chrono::Utc;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use tiny_http::{Header, Method, Request, Response, Server};
+use crate::model::clock::Clock;
+use crate::model::pause::Pause;
+use crate::model::progress::{progress_all, Progress};
+use crate::model::query::Query;
+use crate::model::stats::TaskStats;
+use crate::model::store::{StoreError, TaskStore};
+use crate::model::task::{parse_tags, EvalPeriod, Task, TaskFilter, TaskTy};
+use crate::model::task_entry::TaskEntry;
+use crate::model::when::parse_when;
+use crate::model::hooks::take_failures;
+
+///////////////////////////////////////////////////////////
+
+/// Requests bodies past this are refused
+const MAX_BODY: u64 = 1 << 20;
+
+/// A JSON API over the store for other programs on the machine. Reading
+/// is open, anything that writes needs `Authorization: Bearer <token>`.
+///
+/// ```text
+/// GET    /tasks                   ?tag= &archived=true &q=<query>
+/// POST   /tasks                   {name, desc, period, type, target, tags}
+/// GET    /tasks/{task}            name or ID prefix
+/// PATCH  /tasks/{task}            any of the fields above, and archived
+/// DELETE /tasks/{task}            with its entries
+/// GET    /tasks/{task}/entries    ?q=<query>
+/// POST   /tasks/{task}/entries    {content, at}
+/// GET    /tasks/{task}/stats
+/// GET    /entries/{id}
+/// PATCH  /entries/{id}            {content, at}
+/// DELETE /entries/{id}
+/// GET    /stats                   progress of every active task
+/// ```
+pub struct ApiServer {
+    server: Server,
+    token: String,
+}
+
+/// A failed request, sent back as `{"error": message}`
+#[derive(Debug, PartialEq)]
+pub struct ApiError {
+    pub status: u16,
+    pub message: String,
+}
+
+impl fmt::Display for ApiError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}", self.status, self.message)
+    }
+}
+
+impl Error for ApiError {}
+
+impl From<StoreError> for ApiError {
+    fn from(e: StoreError) -> Self {
+        let status = match e {
+            StoreError::NotFound(..) => 404,
+            StoreError::Ambiguous(..) => 409,
+            _ => 500,
+        };
+        ApiError { status, message: e.to_string() }
+    }
+}
+
+/// Fields of a task sent by a client, absent ones are left alone
+#[derive(Deserialize, Debug, Default)]
+#[serde(deny_unknown_fields)]
+struct TaskBody {
+    name: Option<String>,
+    desc: Option<String>,
+    period: Option<String>,
+    #[serde(rename = "type")]
+    task_type: Option<String>,
+    target: Option<u32>,
+    tags: Option<Vec<String>>,
+    archived: Option<bool>,
+    pinned: Option<bool>,
+}
+
+/// Fields of an entry sent by a client, `at` as understood by `parse_when`
+#[derive(Deserialize, Debug, Default)]
+#[serde(deny_unknown_fields)]
+struct EntryBody {
+    content: Option<String>,
+    at: Option<String>,
+}
+
+type Reply = Result<(u16, Value), ApiError>;
+
+///////////////////////////////////////////////////////////
+
+impl ApiError {
+    pub fn new(status: u16, message: impl ToString) -> Self {
+        ApiError { status, message: message.to_string() }
+    }
+
+    fn invalid(message: impl ToString) -> Self {
+        ApiError::new(400, message)
+    }
+}
+
+impl ApiServer {
+
+    /// Listen on `addr`, e.g. 127.0.0.1:7878. Port 0 picks a free one.
+    pub fn bind(addr: &str, token: &str) -> Result<Self, Box<dyn Error>> {
+        let server = Server::http(addr).map_err(|e| format!("can't listen on {}: {}", addr, e))?;
+        Ok(ApiServer { server, token: token.to_string() })
+    }
+
+    /// The address actually bound
+    pub fn addr(&self) -> Option<SocketAddr> {
+        self.server.server_addr().to_ip()
+    }
+
+    /// Answer requests until `stop` is called
+    pub fn run(&self, store: &TaskStore) {
+        for mut request in self.server.incoming_requests() {
+            let (status, body) = match self.handle(store, &mut request) {
+                Ok(reply) => reply,
+                Err(e) => (e.status, json!({ "error": e.message })),
+            };
+            let _ = request.respond(json_response(status, &body));
+            for failure in take_failures() {
+                eprintln!("packrat: {}", failure);
+            }
+        }
+    }
+
+    /// Make `run` return, from another thread
+    pub fn stop(&self) {
+        self.server.unblock();
+    }
+
+    fn handle(&self, store: &TaskStore, request: &mut Request) -> Reply {
+        let method = request.method().clone();
+        let (path, params) = split_url(request.url());
+        let segments: Vec<&str> = path.split('/').filter(|s| !s.is_empty()).collect();
+
+        if method != Method::Get && !self.authorized(request) {
+            return Err(ApiError::new(401, "writing needs the header 'Authorization: Bearer <token>'"));
+        }
+        let mut body = String::new();
+        request.as_reader()
+            .take(MAX_BODY)
+            .read_to_string(&mut body)
+            .map_err(|e| ApiError::invalid(format!("can't read the body: {}", e)))?;
+
+        let segments: Vec<String> = segments.iter().map(|s| decode(s)).collect();
+        let segments: Vec<&str> = segments.iter().map(String::as_str).collect();
+        let reply = match (&method, segments.as_slice()) {
+            (Method::Get, ["tasks"]) => list_tasks(store, &params),
+            (Method::Post, ["tasks"]) => create_task(store, parse_body(&body)?),
+            (Method::Get, ["tasks", task]) => get_task(store, task),
+            (Method::Patch, ["tasks", task]) => update_task(store, task, parse_body(&body)?),
+            (Method::Delete, ["tasks", task]) => delete_task(store, task),
+            (Method::Get, ["tasks", task, "entries"]) => list_entries(store, task, &params),
+            (Method::Post, ["tasks", task, "entries"]) => create_entry(store, task, parse_body(&body)?),
+            (Method::Get, ["tasks", task, "stats"]) => task_stats(store, task),
+            (Method::Get, ["entries", entry]) => {
+                let entry = store.find_entry(entry)?;
+                Ok((200, entry.to_json()))
+            }
+            (Method::Patch, ["entries", entry]) => update_entry(store, entry, parse_body(&body)?),
+            (Method::Delete, ["entries", entry]) => {
+                let entry = store.find_entry(entry)?;
+                store.delete_item(&entry)?;
+                Ok((200, json!({ "deleted": entry.id })))
+            }
+            (Method::Get, ["stats"]) => all_stats(store),
+            _ => Err(ApiError::new(404, format!("no route for {} {}", method, path))),
+        }?;
+        if method != Method::Get {
+            store.flush()?;
+        }
+        Ok(reply)
+    }
+
+    fn authorized(&self, request: &Request) -> bool {
+        request.headers().iter()
+            .find(|h| h.field.equiv("Authorization"))
+            .and_then(|h| h.value.as_str().strip_prefix("Bearer "))
+            .is_some_and(|token| token.trim() == self.token)
+    }
+}
+
+fn list_tasks(store: &TaskStore, params: &HashMap<String, String>) -> Reply {
+    let clock = Clock::get();
+    let filter = filter_of(params, clock)?;
+    let tasks: Vec<Task> = store.get_prefix(Task::key_all())?;
+    let progress = progress_all(store, &tasks, clock, Utc::now())?;
+
+    let mut listed = vec![];
+    for (task, progress) in tasks.iter().zip(&progress) {
+        let entries: Vec<TaskEntry> = store.get_prefix(TaskEntry::key_task(task.id))?;
+        if filter.select(task, &entries, clock).is_some() {
+            listed.push(task_json(task, Some(progress)));
+        }
+    }
+    Ok((200, Value::Array(listed)))
+}
+
+fn get_task(store: &TaskStore, query: &str) -> Reply {
+    let task = store.find_task(query)?;
+    let progress = progress_all(store, std::slice::from_ref(&task), Clock::get(), Utc::now())?;
+    Ok((200, task_json(&task, progress.first())))
+}
+
+fn create_task(store: &TaskStore, body: TaskBody) -> Reply {
+    let name = body.name.as_deref().map(str::trim).unwrap_or("");
+    if name.is_empty() {
+        return Err(ApiError::invalid("the task needs a name"));
+    }
+    if store.name_taken(name, None)? {
+        return Err(ApiError::new(409, format!("a task named '{}' already exists", name)));
+    }
+    let task = apply_task(Task::new(name, ""), body)?;
+    let task = store.put(task)?;
+    Ok((201, task_json(&task, None)))
+}
+
+fn update_task(store: &TaskStore, query: &str, body: TaskBody) -> Reply {
+    let task = store.find_task(query)?;
+    if let Some(name) = body.name.as_deref().map(str::trim) {
+        if name.is_empty() {
+            return Err(ApiError::invalid("the task needs a name"));
+        }
+        if store.name_taken(name, Some(task.id))? {
+            return Err(ApiError::new(409, format!("a task named '{}' already exists", name)));
+        }
+    }
+    let task = store.put(apply_task(task, body)?)?;
+    Ok((200, task_json(&task, None)))
+}
+
+fn delete_task(store: &TaskStore, query: &str) -> Reply {
+    let task = store.find_task(query)?;
+    let entries = store.delete_task(&task)?;
+    Ok((200, json!({ "deleted": task.id, "entries": entries })))
+}
+
+fn list_entries(store: &TaskStore, query: &str, params: &HashMap<String, String>) -> Reply {
+    let clock = Clock::get();
+    let task = store.find_task(query)?;
+    let entries: Vec<TaskEntry> = store.get_prefix(TaskEntry::key_task(task.id))?;
+    let filter = TaskFilter { include_archived: true, ..filter_of(params, clock)? };
+    let kept = filter.select(&task, &entries, clock).unwrap_or_default();
+    Ok((200, Value::Array(kept.into_iter().map(TaskEntry::to_json).collect())))
+}
+
+fn create_entry(store: &TaskStore, query: &str, body: EntryBody) -> Reply {
+    let task = store.find_task(query)?;
+    let now = Utc::now();
+    let timestamp = match body.at {
+        Some(when) => parse_when(&when, Clock::get(), now).map_err(ApiError::invalid)?,
+        None => now,
+    };
+    let content = body.content.unwrap_or_default();
+    let entry = store.put(TaskEntry::new_at(task.id, content.as_str(), timestamp))?;
+    Ok((201, entry.to_json()))
+}
+
+fn update_entry(store: &TaskStore, query: &str, body: EntryBody) -> Reply {
+    let mut entry = store.find_entry(query)?;
+    if let Some(content) = body.content {
+        entry.content = content.into_bytes();
+    }
+    if let Some(when) = body.at {
+        entry.timestamp = parse_when(&when, Clock::get(), Utc::now()).map_err(ApiError::invalid)?;
+    }
+    let entry = store.put(entry)?;
+    Ok((200, entry.to_json()))
+}
+
+fn task_stats(store: &TaskStore, query: &str) -> Reply {
+    let clock = Clock::get();
+    let now = Utc::now();
+    let task = store.find_task(query)?;
+    let entries: Vec<TaskEntry> = store.get_prefix(TaskEntry::key_task(task.id))?;
+    let pauses: Vec<Pause> = store.get_prefix(Pause::key_all())?;
+    let stats = TaskStats::compute(&task, &entries, &pauses, clock, now);
+    let progress = Progress::compute(&task, &entries, &pauses, clock, now);
+    Ok((200, json!({
+        "task": task.id,
+        "name": task.name,
+        "progress": progress_json(&progress),
+        "entries": stats.entries,
+        "periods": stats.periods,
+        "periods_met": stats.periods_met,
+        "completion_rate": stats.completion_rate(),
+        "weekdays": stats.weekdays,
+        "min": stats.min,
+        "max": stats.max,
+        "mean": stats.mean,
+    })))
+}
+
+fn all_stats(store: &TaskStore) -> Reply {
+    let tasks: Vec<Task> = store.get_prefix::<Task>(Task::key_all())?
+        .into_iter()
+        .filter(|t| !t.archived)
+        .collect();
+    let progress = progress_all(store, &tasks, Clock::get(), Utc::now())?;
+    let rows = tasks.iter().zip(&progress)
+        .map(|(task, progress)| json!({
+            "task": task.id,
+            "name": task.name,
+            "progress": progress_json(progress),
+        }))
+        .collect();
+    Ok((200, Value::Array(rows)))
+}
+
+/// Copy the fields a client sent onto a task
+fn apply_task(mut task: Task, body: TaskBody) -> Result<Task, ApiError> {
+    if let Some(name) = body.name {
+        task.name = name.trim().to_string();
+    }
+    if let Some(desc) = body.desc {
+        task.desc = desc;
+    }
+    if let Some(period) = body.period {
+        task.eval_period = period.parse::<EvalPeriod>().map_err(ApiError::invalid)?;
+    }
+    if let Some(task_type) = body.task_type {
+        task.task_type = task_type.parse::<TaskTy>().map_err(ApiError::invalid)?;
+    }
+    if let Some(target) = body.target {
+        task.target = target;
+    }
+    if let Some(tags) = body.tags {
+        task.tags = parse_tags(&tags.join(","));
+    }
+    if let Some(archived) = body.archived {
+        task.archived = archived;
+    }
+    if let Some(pinned) = body.pinned {
+        task.pinned = pinned;
+    }
+    Ok(task)
+}
+
+/// `tag`, `archived` and `q` of a listing
+fn filter_of(params: &HashMap<String, String>, clock: &Clock) -> Result<TaskFilter, ApiError> {
+    let query = params.get("q").map(String::as_str).unwrap_or("");
+    Ok(TaskFilter {
+        tag: params.get("tag").map(|t| t.trim_start_matches('#').to_lowercase()),
+        include_archived: params.get("archived").is_some_and(|a| a == "true" || a == "1"),
+        query: Query::parse(query, clock.today()).map_err(ApiError::invalid)?,
+    })
+}
+
+fn task_json(task: &Task, progress: Option<&Progress>) -> Value {
+    let mut value = serde_json::to_value(task).unwrap_or_default();
+    if let (Some(progress), Value::Object(fields)) = (progress, &mut value) {
+        fields.insert(String::from("progress"), progress_json(progress));
+    }
+    value
+}
+
+fn progress_json(progress: &Progress) -> Value {
+    json!({
+        "count": progress.count,
+        "target": progress.target,
+        "streak": progress.streak,
+        "paused": progress.paused,
+        "label": progress.to_string(),
+    })
+}
+
+fn parse_body<T: for<'de> Deserialize<'de> + Default>(body: &str) -> Result<T, ApiError> {
+    match body.trim().is_empty() {
+        true => Ok(T::default()),
+        false => serde_json::from_str(body).map_err(|e| ApiError::invalid(format!("bad JSON body: {}", e))),
+    }
+}
+
+fn json_response(status: u16, body: &Value) -> Response<Cursor<Vec<u8>>> {
+    let header = Header::from_bytes("Content-Type", "application/json").unwrap();
+    Response::from_data(body.to_string().into_bytes())
+        .with_status_code(status)
+        .with_header(header)
+}
+
+/// The path of a URL and its query parameters, decoded
+fn split_url(url: &str) -> (String, HashMap<String, String>) {
+    let (path, query) = url.split_once('?').unwrap_or((url, ""));
+    let params = query.split('&')
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| {
+            let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+            (decode_query(key), decode_query(value))
+        })
+        .collect();
+    (path.to_string(), params)
+}
+
+/// Undo the encoding of a query parameter, where `+` is a space
+pub fn decode_query(text: &str) -> String {
+    decode(&text.replace('+', " "))
+}
+
+/// Undo percent encoding, as of a path segment
+pub fn decode(text: &str) -> String {
+    let bytes = text.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let hex = bytes.get(i + 1..i + 3)
+            .and_then(|h| std::str::from_utf8(h).ok())
+            .and_then(|h| u8::from_str_radix(h, 16).ok());
+        match (bytes[i], hex) {
+            (b'%', Some(byte)) => {
+                out.push(byte);
+                i += 3;
+            }
+            (byte, _) => {
+                out.push(byte);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
@@ -4,6 +4,7 @@ use std::fmt;
 use std::collections::HashMap;
 use crossterm::event::{KeyCode};
 use crate::ui::view::Transition;
+use crate::config::Config;
 
 ///////////////////////////////////////////////////////////
 
@@ -15,38 +16,104 @@ pub enum UserAction {
     New,
     Edit,
     Delete,
+    Stats,
+    Retime,
+    Tag,
+    Pause,
+    PauseAll,
+    Archive,
+    ShowArchived,
+    Pin,
+    Move,
+    Sort,
+    Routines,
+    Run,
+    Search,
+    Filter,
     Back,
     Quit,
 }
 
 impl UserAction {
-    pub fn all() -> Vec<UserAction> {
+    /// actions offered on the list of tasks
+    pub fn main_view() -> Vec<UserAction> {
         vec![
             UserAction::Select,
             UserAction::New,
             UserAction::Edit,
             UserAction::Delete,
+            UserAction::Tag,
+            UserAction::Pause,
+            UserAction::PauseAll,
+            UserAction::Archive,
+            UserAction::ShowArchived,
+            UserAction::Pin,
+            UserAction::Move,
+            UserAction::Sort,
+            UserAction::Routines,
+            UserAction::Search,
+            UserAction::Filter,
             UserAction::Back,
             UserAction::Quit,
         ]
-    } 
+    }
+
+    /// actions offered on the list of routines
+    pub fn routine_view() -> Vec<UserAction> {
+        vec![
+            UserAction::Run,
+            UserAction::New,
+            UserAction::Edit,
+            UserAction::Delete,
+            UserAction::Back,
+            UserAction::Quit,
+        ]
+    }
 
-    pub fn from_index(index: usize) -> Self {
-        UserAction::all()[index].clone()
-    }    
+    /// actions offered on the entries of a task
+    pub fn task_view() -> Vec<UserAction> {
+        vec![
+            UserAction::Select,
+            UserAction::New,
+            UserAction::Delete,
+            UserAction::Retime,
+            UserAction::Stats,
+            UserAction::Back,
+            UserAction::Quit,
+        ]
+    }
+
+    /// actions offered on the stats of a task
+    pub fn stats_view() -> Vec<UserAction> {
+        vec![UserAction::Back, UserAction::Quit]
+    }
 }
 
 impl fmt::Display for UserAction {
     fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
-        let text = match self {
-            UserAction::Select => "Select (s)",  
-            UserAction::Back => "Back (b)",  
-            UserAction::New => "New (n)",  
-            UserAction::Delete => "Delete (d)",  
-            UserAction::Edit => "Edit (e)",  
-            UserAction::Quit => "Quit (q)",  
-        };
-        write!(fmt, "{}", text)
+        let keys = &Config::get().keys;
+        match self {
+            UserAction::Select => write!(fmt, "Select ({})", keys.select),
+            UserAction::Back => write!(fmt, "Back ({})", keys.back),
+            UserAction::New => write!(fmt, "New ({})", keys.new),
+            UserAction::Delete => write!(fmt, "Delete ({})", keys.delete),
+            UserAction::Edit => write!(fmt, "Edit ({})", keys.edit),
+            UserAction::Stats => write!(fmt, "Stats ({})", keys.stats),
+            UserAction::Retime => write!(fmt, "Time ({})", keys.retime),
+            UserAction::Tag => write!(fmt, "Filter tag ({})", keys.tag),
+            UserAction::Pause => write!(fmt, "Pause ({})", keys.pause),
+            UserAction::PauseAll => write!(fmt, "Pause all ({})", keys.pause_all),
+            UserAction::Archive => write!(fmt, "Archive ({})", keys.archive),
+            UserAction::ShowArchived => write!(fmt, "Archived ({})", keys.show_archived),
+            UserAction::Pin => write!(fmt, "Pin ({})", keys.pin),
+            UserAction::Move => write!(fmt, "Move ({}/{})", keys.move_down, keys.move_up),
+            UserAction::Sort => write!(fmt, "Sort ({})", keys.sort),
+            UserAction::Routines => write!(fmt, "Routines ({})", keys.routines),
+            UserAction::Run => write!(fmt, "Run ({})", keys.run),
+            UserAction::Search => write!(fmt, "Search ({})", keys.search),
+            UserAction::Filter => write!(fmt, "Filter ({})", keys.filter),
+            UserAction::Quit => write!(fmt, "Quit ({})", keys.quit),
+        }
     }
 } 
 
@@ -1,38 +1,134 @@
-use std::io;
-use std::io::{Write, Read};
-use std::fs::File;
-use tempfile::NamedTempFile;
+// editor.rs
+
+use std::env;
+use std::fmt;
+use std::error::Error;
+use std::io::{self, Write};
 use std::process::Command;
+use tempfile::NamedTempFile;
+use crate::config::Config;
 
 ///////////////////////////////////////////////////////////
 
-pub fn open_editor(bytes: &[u8]) -> io::Result<String> {
-    
-    // open a temporary file 
-    let mut tmp_file = NamedTempFile::new()
-        .map_err(|e| format!("Failed to create tmpfile: {}", e))
-        .unwrap();
-    
-    // write the contents into the file 
-    tmp_file
-        .write_all(bytes)
-        .map_err(|e| format!("Failed to write to temp file: {}", e));
-
-    // open the editor
-    let status = Command::new("nvim")
+/// Tried in order when neither the config nor the environment names one
+pub const FALLBACK_EDITORS: [&str; 4] = ["nvim", "vim", "vi", "nano"];
+
+#[derive(Debug)]
+pub enum EditorError {
+    NoEditor,                   // nothing configured and no fallback installed
+    NotFound(String),           // the configured program doesn't exist
+    Failed(String, String),     // the program and how it exited
+    Io(io::Error),
+}
+
+impl fmt::Display for EditorError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EditorError::NoEditor => write!(
+                f, "No editor found. Set editor in the config, $VISUAL or $EDITOR, or install one of {}",
+                FALLBACK_EDITORS.join(", ")
+            ),
+            EditorError::NotFound(program) => write!(f, "Editor '{}' not found", program),
+            EditorError::Failed(program, status) => {
+                write!(f, "'{}' {}, nothing was saved", program, status)
+            }
+            EditorError::Io(e) => write!(f, "Editor failed: {}", e),
+        }
+    }
+}
+
+impl Error for EditorError {}
+
+/// io errors only compare by kind
+impl PartialEq for EditorError {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (EditorError::NoEditor, EditorError::NoEditor) => true,
+            (EditorError::NotFound(a), EditorError::NotFound(b)) => a == b,
+            (EditorError::Failed(a, x), EditorError::Failed(b, y)) => a == b && x == y,
+            (EditorError::Io(a), EditorError::Io(b)) => a.kind() == b.kind(),
+            _ => false,
+        }
+    }
+}
+
+impl From<io::Error> for EditorError {
+    fn from(e: io::Error) -> Self {
+        EditorError::Io(e)
+    }
+}
+
+///////////////////////////////////////////////////////////
+
+/// Let the user edit `bytes` and return what they saved. Changes are only
+/// kept when the editor exits cleanly.
+pub fn open_editor(bytes: &[u8]) -> Result<String, EditorError> {
+
+    let command = editor_command()?;
+    let (program, args) = command.split_first().ok_or(EditorError::NoEditor)?;
+
+    let mut tmp_file = NamedTempFile::new()?;
+    tmp_file.write_all(bytes)?;
+    tmp_file.flush()?;
+
+    let status = Command::new(program)
+        .args(args)
         .arg(tmp_file.path())
         .status()
-        .expect("Failed to open editor");
-
+        .map_err(|e| match e.kind() {
+            io::ErrorKind::NotFound => EditorError::NotFound(program.clone()),
+            _ => EditorError::Io(e),
+        })?;
     if !status.success() {
-        eprintln!("Neovim exited with an error.");
+        let how = match status.code() {
+            Some(code) => format!("exited with status {}", code),
+            None => String::from("was interrupted"),
+        };
+        return Err(EditorError::Failed(program.clone(), how));
     }
 
-    // read the contents back
-    let mut content_updated = String::new();
-    File::open(&tmp_file)?
-        .read_to_string(&mut content_updated)?;
-    
-    Ok(content_updated)
+    Ok(std::fs::read_to_string(tmp_file.path())?)
 }
 
+/// The editor to run as program and arguments: the config's, then
+/// `$VISUAL`, then `$EDITOR`, then the first fallback on the PATH
+pub fn editor_command() -> Result<Vec<String>, EditorError> {
+    let named = Config::get().editor.clone()
+        .into_iter()
+        .chain(["VISUAL", "EDITOR"].iter().filter_map(|var| env::var(var).ok()))
+        .map(|line| split_command(&line))
+        .find(|words| !words.is_empty());
+    if let Some(words) = named {
+        return Ok(words);
+    }
+    FALLBACK_EDITORS.iter()
+        .find(|program| on_path(program))
+        .map(|program| vec![program.to_string()])
+        .ok_or(EditorError::NoEditor)
+}
+
+/// Split a command line into words, quotes keep spaces in a word
+pub fn split_command(line: &str) -> Vec<String> {
+    let mut words = vec![];
+    let mut word: Option<String> = None;
+    let mut quote: Option<char> = None;
+    for c in line.chars() {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (Some(_), c) => word.get_or_insert_with(String::new).push(c),
+            (None, '"' | '\'') => {
+                quote = Some(c);
+                word.get_or_insert_with(String::new);
+            }
+            (None, c) if c.is_whitespace() => words.extend(word.take()),
+            (None, c) => word.get_or_insert_with(String::new).push(c),
+        }
+    }
+    words.extend(word);
+    words
+}
+
+fn on_path(program: &str) -> bool {
+    env::var_os("PATH")
+        .is_some_and(|paths| env::split_paths(&paths).any(|dir| dir.join(program).is_file()))
+}
@@ -6,3 +6,10 @@ pub mod render_create;
 pub mod render_delete;
 pub mod render_config;
 pub mod render_popup;
+pub mod render_stats;
+pub mod render_pause;
+pub mod render_timestamp;
+pub mod render_routines;
+pub mod render_run;
+pub mod render_create_routine;
+pub mod render_search;
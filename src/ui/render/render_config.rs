@@ -1,16 +1,10 @@
 use std::io;
-use std::io::{Write, Read};
-use std::fs::File;
-use std::process::Command;
 use std::fmt::Debug;
-use tempfile::NamedTempFile;
-use crate::log::debug_log;
-use crate::ui::editor::open_editor;
 use crate::model::convert::Storable;
 use crate::model::store::TaskStore;
 use crate::ui::view::{Transition, View};
 use crate::ui::state::{ConfigViewState, PopUpViewState};
-use crate::ui::render::renderable::Renderable;
+use crate::ui::render::renderable::{Renderable, edit_in_view};
 
 ///////////////////////////////////////////////////////////
 
@@ -18,8 +12,16 @@ impl<T> Renderable for ConfigViewState<T> where T: Storable + Debug {
 
     fn render(&mut self) -> io::Result<Transition> {
         
+        // back from the popup about a failed editor
+        if self.editor_error.is_some() {
+            return Ok(Transition::Pop);
+        }
+
         let item_config = self.config_item.to_toml().unwrap();  
-        let updated_config = open_editor(&item_config.as_bytes()).unwrap();
+        let updated_config = match edit_in_view(item_config.as_bytes(), &mut self.editor_error) {
+            Ok(updated) => updated,
+            Err(transition) => return Ok(*transition),
+        };
         
         let transition = match T::from_toml(updated_config) {
             Ok(t) => {
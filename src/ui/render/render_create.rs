@@ -1,11 +1,10 @@
 /// render_create.rs
-/// TODO: Factor these Task and Entry implementations into one, generic version.
 
 use std::io;
-use crate::model::task::Task;
+use crate::model::task::{parse_tags, Task};
 use crate::model::store::TaskStore;
-use crate::ui::view::Transition;
-use crate::ui::state::CreateTaskViewState;
+use crate::ui::view::{Transition, View};
+use crate::ui::state::{CreateTaskViewState, PopUpViewState};
 use tui::{ 
     backend::Backend,
     layout::{Constraint, Direction, Layout, Rect},
@@ -44,9 +43,29 @@ pub trait FormRenderable {
     /// Valid by default 
     fn validate() -> bool { true } 
     
-    /// Shared method with Renderable trait
+    /// Render a dialogue box overtop of the current view, taking the user input.
     fn render(&mut self) -> io::Result<Transition> {
-        Ok(Transition::Stay) 
+
+        let mut terminal = render_view_startup()?;
+        let transition = loop {
+            terminal.draw(|f| {
+                let chunks: Vec<Rect> = self.chunks(f.size());
+                let widgets = self.widgets().unwrap();
+
+                widgets.into_iter().enumerate().for_each(|(i, w)| {
+                    f.render_widget(w, chunks[i]);
+                });
+            })?;
+
+            match self.controller() {
+                Transition::Stay => continue,
+                t => break t,
+            }
+        };
+
+        // Ensure the terminal is properly torn down before returning
+        render_view_teardown(&mut terminal)?;
+        Ok(transition)
     }
     
     /// Must implement!
@@ -58,6 +77,28 @@ pub trait FormRenderable {
 
 impl FormRenderable for CreateTaskViewState {
     
+    /// Name, description and tags
+    fn chunks(&self, frame: Rect) -> Vec<Rect> {
+
+        let modal_width = frame.width / 3 * 2;
+        let modal_height = 13;
+        let modal_area = tui::layout::Rect::new(
+                    (frame.width - modal_width) / 2,
+                    (frame.height.saturating_sub(modal_height)) / 2,
+                    modal_width,
+                    modal_height.min(frame.height),
+                );
+
+        Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([
+                Constraint::Length(3),
+                Constraint::Min(4),
+                Constraint::Length(3),
+            ])
+            .split(modal_area)
+    }
+
     ///
     fn widgets(&mut self) -> io::Result<Vec<AnyWidget>> {
         
@@ -66,41 +107,13 @@ impl FormRenderable for CreateTaskViewState {
         
         let desc_widget = paragraph_factory(
             " Task Descripion", self.inputs[1].as_str(), self.active_input == 1);
-        
-        Ok(vec![title_widget, desc_widget])
-    }
 
-    /// Render a dialgoue box overtop of the current view, taking the user input.
-    fn render(&mut self) -> io::Result<Transition> {
+        let tags_widget = paragraph_factory(
+            "Tags (comma separated)", self.inputs[2].as_str(), self.active_input == 2);
         
-        let mut terminal = render_view_startup()?;
-        let mut transition = Transition::Stay; 
-
-        
-
-        let result = loop {
-            terminal.draw(|f| {
-                
-                let chunks: Vec<Rect> = self.chunks(f.size()); 
-                let widgets = self.widgets().unwrap();
-                
-                widgets.into_iter().enumerate().for_each(|(i, w)| {
-                    f.render_widget(w, chunks[i]);
-                });
-            })?;
-            
-            transition = self.controller(); 
-            match transition {
-                Transition::Stay => continue,
-                _ => break
-            } 
-        };
-
-        // Ensure the terminal is properly torn down before returning
-        render_view_teardown(&mut terminal)?;
-        Ok(transition)
+        Ok(vec![title_widget, desc_widget, tags_widget])
     }
-        
+
     /// handle user inputs
     fn controller(&mut self) -> Transition {
         match event::read().unwrap() {
@@ -119,25 +132,30 @@ impl FormRenderable for CreateTaskViewState {
                 Transition::Stay
             }
             Event::Key(KeyEvent { code: KeyCode::Enter, .. }) => {
-                
-                /// Clone city, this is not great Rust... but works for now  
-                if self.is_edit {
-
-                    self.item.name = self.inputs[0].clone();
-                    self.item.desc = self.inputs[1].clone();
-
-                    TaskStore::instance().put(self.item.clone());
-
-                } else {
-                    /// Create a new task
-                    TaskStore::instance().put(
-                        Task::new(
-                            self.inputs[0].clone(),
-                            self.inputs[1].clone()
-                        )
-                    );
-                } 
-                Transition::Pop
+
+                // tasks are looked up by name, so no two may share one
+                let store = TaskStore::instance();
+                let name = self.inputs[0].trim().to_string();
+                let saved = match store.name_taken(&name, self.is_edit.then_some(self.item.id)) {
+                    Ok(true) => Err(format!("a task named '{}' already exists", name)),
+                    Ok(false) if self.is_edit => {
+                        self.item.name = name;
+                        self.item.desc = self.inputs[1].clone();
+                        self.item.tags = parse_tags(&self.inputs[2]);
+                        store.put(self.item.clone()).map(|_| ()).map_err(|e| e.to_string())
+                    }
+                    Ok(false) => {
+                        // Create a new task
+                        let mut task = Task::new(name, self.inputs[1].clone());
+                        task.tags = parse_tags(&self.inputs[2]);
+                        store.put(task).map(|_| ()).map_err(|e| e.to_string())
+                    }
+                    Err(e) => Err(e.to_string()),
+                };
+                match saved {
+                    Ok(()) => Transition::Pop,
+                    Err(message) => Transition::Push(View::PopUpView(PopUpViewState::new(message))),
+                }
             }
             _ => Transition::Stay,
         }
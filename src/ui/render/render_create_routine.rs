@@ -0,0 +1,109 @@
+// render_create_routine.rs
+
+use std::io;
+use crate::model::routine::{parse_members, Routine};
+use crate::model::store::TaskStore;
+use crate::ui::view::Transition;
+use crate::ui::state::CreateRoutineViewState;
+use tui::{
+    layout::{Constraint, Direction, Layout, Rect},
+    widgets::Paragraph,
+};
+use crate::ui::render::renderable::AnyWidget;
+use crate::ui::render::render_create::FormRenderable;
+use crate::ui::widgets::paragraph_factory;
+use crossterm::event::{self, Event, KeyCode, KeyEvent};
+
+///////////////////////////////////////////////////////////
+
+impl CreateRoutineViewState {
+
+    /// Validate the inputs into a routine
+    fn to_routine(&self) -> Result<Routine, String> {
+        let name = self.inputs[0].trim();
+        if name.is_empty() {
+            return Err(String::from("The routine needs a name"));
+        }
+        let task_ids = parse_members(TaskStore::instance(), &self.inputs[2])
+            .map_err(|e| e.to_string())?;
+        if task_ids.is_empty() {
+            return Err(String::from("List at least one task, e.g. 'Water, Stretch, Journal'"));
+        }
+        Ok(Routine::new(name, self.inputs[1].trim(), task_ids))
+    }
+}
+
+impl FormRenderable for CreateRoutineViewState {
+
+    fn chunks(&self, frame: Rect) -> Vec<Rect> {
+
+        let modal_width = frame.width / 3 * 2;
+        let modal_height = 12;
+        let modal_area = tui::layout::Rect::new(
+                    (frame.width - modal_width) / 2,
+                    (frame.height.saturating_sub(modal_height)) / 2,
+                    modal_width,
+                    modal_height.min(frame.height),
+                );
+
+        Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([
+                Constraint::Length(3),
+                Constraint::Length(3),
+                Constraint::Length(3),
+                Constraint::Min(1),
+            ])
+            .split(modal_area)
+    }
+
+    fn widgets(&mut self) -> io::Result<Vec<AnyWidget<'_>>> {
+
+        let status = match &self.error {
+            Some(e) => e.clone(),
+            None => String::from("Tab to switch fields, Enter to save, Esc to cancel."),
+        };
+
+        Ok(vec![
+            paragraph_factory("Routine Name", self.inputs[0].as_str(), self.active_input == 0),
+            paragraph_factory("Description", self.inputs[1].as_str(), self.active_input == 1),
+            paragraph_factory("Tasks in order (comma separated)", self.inputs[2].as_str(), self.active_input == 2),
+            AnyWidget::Paragraph(Paragraph::new(status)),
+        ])
+    }
+
+    /// handle user inputs
+    fn controller(&mut self) -> Transition {
+        match event::read().unwrap() {
+            Event::Key(KeyEvent { code: KeyCode::Esc, .. }) => Transition::Pop,
+            Event::Key(KeyEvent { code: KeyCode::Char(c), .. }) => {
+                self.inputs[self.active_input].push(c);
+                Transition::Stay
+            }
+            Event::Key(KeyEvent { code: KeyCode::Backspace, .. }) => {
+                self.inputs[self.active_input].pop();
+                Transition::Stay
+            }
+            Event::Key(KeyEvent { code: KeyCode::Tab, .. }) => {
+                self.active_input = (self.active_input + 1) % self.inputs.len();
+                Transition::Stay
+            }
+            Event::Key(KeyEvent { code: KeyCode::Enter, .. }) => {
+                match self.to_routine() {
+                    Ok(routine) => match TaskStore::instance().put(routine) {
+                        Ok(_) => Transition::Pop,
+                        Err(e) => {
+                            self.error = Some(format!("Failed to save routine: {}", e));
+                            Transition::Stay
+                        }
+                    },
+                    Err(e) => {
+                        self.error = Some(e);
+                        Transition::Stay
+                    }
+                }
+            }
+            _ => Transition::Stay,
+        }
+    }
+}
@@ -3,9 +3,10 @@ use crate::model::convert::Storable;
 use crate::model::store::TaskStore;
 use crate::ui::view::Transition;
 use crate::ui::state::DeleteViewState;
+use crate::config::Config;
 use tui::{ 
     layout::{Constraint, Direction, Layout, Rect},
-    style::{Color, Style},
+    style::Style,
     widgets::{Block, Borders, Paragraph},
 };
 use crate::ui::render::renderable::{
@@ -31,7 +32,7 @@ impl<T: Display + Storable> Renderable for DeleteViewState<T> {
                             .borders(Borders::ALL)
                     )
                     .style(
-                        Style::default().fg(Color::Yellow)
+                        Style::default().fg(Config::get().colors.accent.0)
                     )
             )]
         )
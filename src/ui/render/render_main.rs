@@ -1,13 +1,15 @@
 use std::io;
 use tui::layout::Rect;
-use crate::model::store::TaskStore;
+use crate::config::KeyView;
 use crate::ui::view::Transition;
 use crate::ui::state::*;
-use crate::ui::widgets::{control_widget, item_table};
+use crate::ui::control::UserAction;
+use crate::ui::widgets::{control_height, control_widget, paragraph_factory, row_table};
+use crate::model::convert::Storable;
 use crate::model::task::Task;
 use std::time::Instant;
 use tui::layout::{Constraint, Layout};
-use crossterm::event::{Event, KeyCode, KeyEvent};
+use crossterm::event::{self, Event, KeyCode, KeyEvent};
 use crate::ui::view::View;
 use crate::ui::render::renderable::{
     Renderable, ControlOption, AnyWidget,
@@ -18,33 +20,111 @@ use crate::ui::render::renderable::{
     
 /// Table rendering constants, static lifetimes are useful for borrow
 /// only TUI api
-static COLUMN_HEADERS: [&str; 3] = ["Habit", "Created", "Description"];
-static CONSTRAINTS: [Constraint; 3] = [
-    Constraint::Percentage(25),
-    Constraint::Percentage(25),
-    Constraint::Percentage(50),
+static COLUMN_HEADERS: [&str; 5] = ["Habit", "Progress", "Streak", "Created", "Description"];
+static CONSTRAINTS: [Constraint; 5] = [
+    Constraint::Percentage(20),
+    Constraint::Percentage(15),
+    Constraint::Percentage(10),
+    Constraint::Percentage(20),
+    Constraint::Percentage(35),
 ];
 
 ///////////////////////////////////////////////////////////
 
+impl MainViewState {
+
+    fn shows_filter_bar(&self) -> bool {
+        self.editing_query || !self.query.is_empty()
+    }
+
+    /// Keys go to the filter bar, the filter follows as long as it parses
+    fn edit_query(&mut self) -> Transition {
+        match event::read().unwrap() {
+            Event::Key(KeyEvent { code: KeyCode::Char(c), .. }) => {
+                self.query_input.push(c);
+                self.apply_query();
+            }
+            Event::Key(KeyEvent { code: KeyCode::Backspace, .. }) => {
+                self.query_input.pop();
+                self.apply_query();
+            }
+            Event::Key(KeyEvent { code: KeyCode::Enter, .. }) => {
+                self.apply_query();
+                self.editing_query = self.query_error.is_some();
+            }
+            Event::Key(KeyEvent { code: KeyCode::Esc, .. }) => {
+                self.query_input.clear();
+                self.apply_query();
+                self.editing_query = false;
+            }
+            _ => {}
+        }
+        Transition::Stay
+    }
+}
+
 impl Renderable for MainViewState {
        
     /// Create the chunks that widgets will render ontop of 
     fn chunks(&self, frame: Rect) -> Vec<Rect> {        
+        let controls = control_height(&UserAction::main_view(), frame.width);
+        let mut constraints = vec![Constraint::Length(controls), Constraint::Length(3)];
+        if self.shows_filter_bar() {
+            constraints.push(Constraint::Length(3));
+        }
+        constraints.push(Constraint::Max(50));
         Layout::default()
-            .constraints([Constraint::Length(3),Constraint::Max(50)].as_ref())
+            .constraints(constraints)
             .split(frame)
     }
     
     /// Render the main view controls and the list of tasks
     fn widgets(&mut self) -> io::Result<Vec<AnyWidget>> {
                   
-        let task_slice = &self.items[..];
+        let rows = self.items.iter()
+            .zip(&self.progress)
+            .map(|(task, progress)| vec![
+                match task.pinned {
+                    true => format!("★ {}", task.name),
+                    false => task.name.clone(),
+                },
+                progress.to_string(),
+                progress.streak_label(),
+                task.get_timestamp(),
+                task.desc.clone(),
+            ])
+            .collect();
+
+        let mut title = match &self.tag_filter {
+            Some(tag) => format!("Tasks #{}", tag),
+            None => String::from("Tasks"),
+        };
+        if self.show_archived {
+            title.push_str(" (with archived)");
+        }
+        title.push_str(&format!(", {}", self.order));
+        let task_widget = row_table(
+            rows, &COLUMN_HEADERS, &CONSTRAINTS, self.selector.idx, title);
 
-        let task_widget = item_table(
-            task_slice, &COLUMN_HEADERS, &CONSTRAINTS, self.selector.idx);
+        let rollups = match self.rollups.is_empty() {
+            true => String::from("No tags yet, add some with (e)dit"),
+            false => self.rollups.iter()
+                .map(|r| r.to_string())
+                .collect::<Vec<_>>()
+                .join("  |  "),
+        };
+        let rollup_widget = paragraph_factory("Tags", rollups.as_str(), false);
         
-        Ok(vec![control_widget(), task_widget])
+        let mut widgets = vec![control_widget(UserAction::main_view()), rollup_widget];
+        if self.shows_filter_bar() {
+            let title = match &self.query_error {
+                Some(e) => format!("Filter: {}", e),
+                None => String::from("Filter, e.g. task:run after:7d value>5 (Enter to keep, Esc to clear)"),
+            };
+            widgets.push(paragraph_factory(title.as_str(), self.query_input.as_str(), self.editing_query));
+        }
+        widgets.push(task_widget);
+        Ok(widgets)
     } 
 
     /// Check the poll interval 
@@ -58,12 +138,7 @@ impl Renderable for MainViewState {
     /// What to do during each poll interval
     fn update(&mut self) { 
         // poll new items
-        self.items = TaskStore::instance()
-            .get_prefix(Task::key_all())
-            .unwrap(); 
-            
-        // update selector
-        self.selector.max_idx = self.items.len();
+        self.refresh();
     }
     
     /// Draw the View on the terminal
@@ -74,7 +149,11 @@ impl Renderable for MainViewState {
     /// Handle the user inputs
     fn controler(&mut self) -> Transition {
 
-        match default_controls(&mut self.selector) {
+        if self.editing_query {
+            return self.edit_query();
+        }
+
+        match default_controls(&mut self.selector, KeyView::Tasks) {
     
             // A default case was handled
             ControlOption::T(t) => t,
@@ -86,7 +165,9 @@ impl Renderable for MainViewState {
                     // What to do on "delete"
                     Event::Key(KeyEvent { code: KeyCode::Char('d'), .. })
                         => {
-                            let item = self.items[self.selector.idx].clone();
+                            let Some(item) = self.items.get(self.selector.idx).cloned() else {
+                                return Transition::Stay;
+                            };
                             Transition::Push(
                                 View::DeleteView(
                                     Box::new(DeleteViewState::new(item))
@@ -96,7 +177,9 @@ impl Renderable for MainViewState {
                     // What to do on "edit"
                     Event::Key(KeyEvent { code: KeyCode::Char('e'), .. }) 
                         => {
-                            let item = self.items[self.selector.idx].clone();
+                            let Some(item) = self.items.get(self.selector.idx).cloned() else {
+                                return Transition::Stay;
+                            };
                             
                             Transition::Push(
                                 View::ConfigView(
@@ -113,22 +196,90 @@ impl Renderable for MainViewState {
                     /// What to do on "new"
                     Event::Key(KeyEvent { code: KeyCode::Char('n'), .. }) 
                         => {
+                            // start out in the tag being looked at
+                            let mut task = Task::new("New Task", "Task Description");
+                            task.tags = self.tag_filter.iter().cloned().collect();
                             Transition::Push(
                                 View::CreateTaskView(
-                                    CreateTaskViewState::new(
-                                        Task::new("New Task", "Task Description"),
-                                        false
-                                    )
+                                    CreateTaskViewState::new(task, false)
                                 )
                             )
                         } 
+                    // What to do on "tag"
+                    Event::Key(KeyEvent { code: KeyCode::Char('t'), .. })
+                        => {
+                            self.cycle_tag_filter();
+                            Transition::Stay
+                        }
+                    // What to do on "archive"
+                    Event::Key(KeyEvent { code: KeyCode::Char('a'), .. })
+                        => {
+                            self.archive_selected();
+                            Transition::Stay
+                        }
+                    // What to do on "show archived"
+                    Event::Key(KeyEvent { code: KeyCode::Char('A'), .. })
+                        => {
+                            self.toggle_archived();
+                            Transition::Stay
+                        }
+                    // What to do on "pin"
+                    Event::Key(KeyEvent { code: KeyCode::Char('f'), .. })
+                        => {
+                            self.pin_selected();
+                            Transition::Stay
+                        }
+                    // What to do on "move"
+                    Event::Key(KeyEvent { code: KeyCode::Char('J'), .. })
+                        => {
+                            self.move_selected(true);
+                            Transition::Stay
+                        }
+                    Event::Key(KeyEvent { code: KeyCode::Char('K'), .. })
+                        => {
+                            self.move_selected(false);
+                            Transition::Stay
+                        }
+                    // What to do on "sort"
+                    Event::Key(KeyEvent { code: KeyCode::Char('o'), .. })
+                        => {
+                            self.cycle_sort();
+                            Transition::Stay
+                        }
+                    // What to do on "routines"
+                    Event::Key(KeyEvent { code: KeyCode::Char('r'), .. })
+                        => Transition::Push(
+                            View::RoutineListView(Box::default())
+                        ),
+                    // What to do on "filter"
+                    Event::Key(KeyEvent { code: KeyCode::Char('F'), .. })
+                        => {
+                            self.editing_query = true;
+                            Transition::Stay
+                        }
+                    // What to do on "search"
+                    Event::Key(KeyEvent { code: KeyCode::Char('/'), .. })
+                        => Transition::Push(View::SearchView(Box::default())),
+                    // What to do on "pause"
+                    Event::Key(KeyEvent { code: KeyCode::Char('p'), .. })
+                        => {
+                            let Some(item) = self.items.get(self.selector.idx).cloned() else {
+                                return Transition::Stay;
+                            };
+                            Transition::Push(View::PauseView(PauseViewState::new(Some(item))))
+                        }
+                    // What to do on "pause all"
+                    Event::Key(KeyEvent { code: KeyCode::Char('P'), .. })
+                        => Transition::Push(View::PauseView(PauseViewState::new(None))),
                     /// What to do on "select"
                     Event::Key(KeyEvent { code: KeyCode::Char('s') | KeyCode::Enter, .. })
                         => {
-                            let item = self.items[self.selector.idx].clone();
+                            let Some(item) = self.items.get(self.selector.idx).cloned() else {
+                                return Transition::Stay;
+                            };
                             Transition::Push(
                                 View::TaskView(
-                                    TaskViewState::new(item)
+                                    Box::new(TaskViewState::new(item))
                                 )
                             )
                         }
@@ -0,0 +1,113 @@
+// render_pause.rs
+
+use std::io;
+use chrono::NaiveDate;
+use crate::model::pause::Pause;
+use crate::model::store::TaskStore;
+use crate::ui::view::Transition;
+use crate::ui::state::PauseViewState;
+use tui::{
+    layout::{Constraint, Direction, Layout, Rect},
+    widgets::Paragraph,
+};
+use crate::ui::render::renderable::AnyWidget;
+use crate::ui::render::render_create::FormRenderable;
+use crate::ui::widgets::paragraph_factory;
+use crossterm::event::{self, Event, KeyCode, KeyEvent};
+
+///////////////////////////////////////////////////////////
+
+impl PauseViewState {
+
+    /// Validate the inputs into a pause
+    fn to_pause(&self) -> Result<Pause, String> {
+        let parse = |s: &str| NaiveDate::parse_from_str(s.trim(), "%Y-%m-%d")
+            .map_err(|_| format!("'{}' is not a YYYY-MM-DD date", s));
+
+        let start = parse(&self.inputs[0])?;
+        let end = parse(&self.inputs[1])?;
+        if end < start {
+            return Err(String::from("The pause must end after it starts"));
+        }
+        Ok(Pause::new(self.task.as_ref().map(|t| t.id), start, end, self.inputs[2].trim()))
+    }
+}
+
+impl FormRenderable for PauseViewState {
+
+    fn chunks(&self, frame: Rect) -> Vec<Rect> {
+
+        let modal_width = frame.width / 3 * 2;
+        let modal_height = 12;
+        let modal_area = tui::layout::Rect::new(
+                    (frame.width - modal_width) / 2,
+                    (frame.height.saturating_sub(modal_height)) / 2,
+                    modal_width,
+                    modal_height.min(frame.height),
+                );
+
+        Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([
+                Constraint::Length(3),
+                Constraint::Length(3),
+                Constraint::Length(3),
+                Constraint::Min(1),
+            ])
+            .split(modal_area)
+    }
+
+    fn widgets(&mut self) -> io::Result<Vec<AnyWidget<'_>>> {
+
+        let target = match &self.task {
+            Some(task) => task.name.clone(),
+            None => String::from("all tasks"),
+        };
+        let status = match &self.error {
+            Some(e) => e.clone(),
+            None => format!("Pausing {}. Tab to switch fields, Enter to save, Esc to cancel.", target),
+        };
+
+        Ok(vec![
+            paragraph_factory("From", self.inputs[0].as_str(), self.active_input == 0),
+            paragraph_factory("To", self.inputs[1].as_str(), self.active_input == 1),
+            paragraph_factory("Reason", self.inputs[2].as_str(), self.active_input == 2),
+            AnyWidget::Paragraph(Paragraph::new(status)),
+        ])
+    }
+
+    /// handle user inputs
+    fn controller(&mut self) -> Transition {
+        match event::read().unwrap() {
+            Event::Key(KeyEvent { code: KeyCode::Esc, .. }) => Transition::Pop,
+            Event::Key(KeyEvent { code: KeyCode::Char(c), .. }) => {
+                self.inputs[self.active_input].push(c);
+                Transition::Stay
+            }
+            Event::Key(KeyEvent { code: KeyCode::Backspace, .. }) => {
+                self.inputs[self.active_input].pop();
+                Transition::Stay
+            }
+            Event::Key(KeyEvent { code: KeyCode::Tab, .. }) => {
+                self.active_input = (self.active_input + 1) % self.inputs.len();
+                Transition::Stay
+            }
+            Event::Key(KeyEvent { code: KeyCode::Enter, .. }) => {
+                match self.to_pause() {
+                    Ok(pause) => match TaskStore::instance().put(pause) {
+                        Ok(_) => Transition::Pop,
+                        Err(e) => {
+                            self.error = Some(format!("Failed to save pause: {}", e));
+                            Transition::Stay
+                        }
+                    },
+                    Err(e) => {
+                        self.error = Some(e);
+                        Transition::Stay
+                    }
+                }
+            }
+            _ => Transition::Stay,
+        }
+    }
+}
@@ -3,10 +3,11 @@ use crate::model::convert::Storable;
 use crate::model::store::TaskStore;
 use crate::ui::view::Transition;
 use crate::ui::state::PopUpViewState;
+use crate::config::Config;
 use tui::{ 
     layout::{Constraint, Direction, Layout, Rect},
-    style::{Color, Style},
-    widgets::{Block, Borders, Paragraph},
+    style::Style,
+    widgets::{Block, Borders, Paragraph, Wrap},
 };
 use crate::ui::render::renderable::{
     Renderable, AnyWidget, render_view
@@ -24,12 +25,13 @@ impl Renderable for PopUpViewState {
         Ok(vec![
             AnyWidget::Paragraph(
                 Paragraph::new(self.text.clone())
+                    .wrap(Wrap { trim: true })
                     .block(
                         Block::default()
                             .borders(Borders::ALL)
                     )
                     .style(
-                        Style::default().fg(Color::Yellow)
+                        Style::default().fg(Config::get().colors.accent.0)
                     )
             )]
         )
@@ -0,0 +1,122 @@
+// render_routines.rs
+
+use std::io;
+use tui::layout::{Constraint, Layout, Rect};
+use crossterm::event::{Event, KeyCode, KeyEvent};
+use std::time::Instant;
+use crate::config::KeyView;
+use crate::model::convert::Storable;
+use crate::model::store::TaskStore;
+use crate::ui::control::UserAction;
+use crate::ui::view::{Transition, View};
+use crate::ui::state::{
+    RoutineListViewState, RoutineRunViewState, CreateRoutineViewState,
+    DeleteViewState, ConfigViewState, PopUpViewState,
+};
+use crate::ui::widgets::{control_height, control_widget, row_table};
+use crate::ui::render::renderable::{
+    Renderable, ControlOption, AnyWidget,
+    render_view, default_controls
+};
+
+///////////////////////////////////////////////////////////
+
+static COLUMN_HEADERS: [&str; 4] = ["Routine", "Steps", "Last run", "Description"];
+static CONSTRAINTS: [Constraint; 4] = [
+    Constraint::Percentage(25),
+    Constraint::Percentage(10),
+    Constraint::Percentage(30),
+    Constraint::Percentage(35),
+];
+
+///////////////////////////////////////////////////////////
+
+impl Renderable for RoutineListViewState {
+
+    fn render(&mut self) -> io::Result<Transition> {
+        render_view(self, Self::controler)
+    }
+
+    fn chunks(&self, frame: Rect) -> Vec<Rect> {
+        Layout::default()
+            .constraints([
+                Constraint::Length(control_height(&UserAction::routine_view(), frame.width)),
+                Constraint::Max(50),
+            ].as_ref())
+            .split(frame)
+    }
+
+    /// Each routine with its last finished run
+    fn widgets(&mut self) -> io::Result<Vec<AnyWidget<'_>>> {
+
+        let rows = self.items.iter()
+            .zip(&self.last_runs)
+            .map(|(routine, run)| vec![
+                routine.name.clone(),
+                routine.task_ids.len().to_string(),
+                match run {
+                    Some(run) => format!("{} ({})", run.get_timestamp(), run),
+                    None => String::from("never"),
+                },
+                routine.desc.clone(),
+            ])
+            .collect();
+
+        let table = row_table(
+            rows, &COLUMN_HEADERS, &CONSTRAINTS, self.selector.idx, "Routines");
+
+        Ok(vec![control_widget(UserAction::routine_view()), table])
+    }
+
+    fn poll(&mut self) {
+        if self.last_poll_time.elapsed() >= self.poll_interval {
+            self.update();
+            self.last_poll_time = Instant::now();
+        }
+    }
+
+    fn update(&mut self) {
+        self.refresh();
+    }
+
+    fn controler(&mut self) -> Transition {
+
+        match default_controls(&mut self.selector, KeyView::Routines) {
+
+            // A default case was handled
+            ControlOption::T(t) => t,
+
+            // A custom case occurred
+            ControlOption::E(e) => {
+                let selected = self.items.get(self.selector.idx).cloned();
+                match (e, selected) {
+                    // What to do on "new"
+                    (Event::Key(KeyEvent { code: KeyCode::Char('n'), .. }), _)
+                        => Transition::Push(View::CreateRoutineView(CreateRoutineViewState::new())),
+                    // What to do on "run"
+                    (Event::Key(KeyEvent { code: KeyCode::Char('s') | KeyCode::Enter, .. }), Some(item))
+                        => match item.tasks(TaskStore::instance()) {
+                            // a run of nothing would count as finished
+                            Ok(tasks) if tasks.is_empty() => Transition::Push(View::PopUpView(
+                                PopUpViewState::new(format!("{} has no tasks left, edit it to add some", item.name))
+                            )),
+                            _ => Transition::Push(
+                                View::RoutineRunView(Box::new(RoutineRunViewState::new(item)))
+                            ),
+                        },
+                    // What to do on "edit"
+                    (Event::Key(KeyEvent { code: KeyCode::Char('e'), .. }), Some(item))
+                        => Transition::Push(
+                            View::ConfigView(Box::new(ConfigViewState::new(item)))
+                        ),
+                    // What to do on "delete"
+                    (Event::Key(KeyEvent { code: KeyCode::Char('d'), .. }), Some(item))
+                        => Transition::Push(
+                            View::DeleteView(Box::new(DeleteViewState::new(item)))
+                        ),
+                    _ => Transition::Stay
+                }
+            }
+        }
+    }
+}
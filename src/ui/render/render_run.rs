@@ -0,0 +1,146 @@
+// render_run.rs
+
+use std::io;
+use tui::{
+    layout::{Constraint, Layout, Rect},
+    style::Style,
+    widgets::{Block, Borders, List, ListItem, Paragraph},
+};
+use crossterm::event::{self, Event, KeyCode, KeyEvent};
+use crate::model::store::TaskStore;
+use crate::ui::view::Transition;
+use crate::ui::state::RoutineRunViewState;
+use crate::ui::widgets::paragraph_factory;
+use crate::ui::render::renderable::{Renderable, AnyWidget, render_view};
+use crate::config::Config;
+
+///////////////////////////////////////////////////////////
+
+impl RoutineRunViewState {
+
+    /// Log the current step, or skip it, finishing the run after the last one
+    fn advance(&mut self, skip: bool) -> Result<(), String> {
+        let store = TaskStore::instance();
+        let Some(task) = self.run.next_step(&self.tasks).cloned() else {
+            return Ok(());
+        };
+
+        if skip {
+            self.run.skip_step(&task);
+        } else {
+            self.run.log_step(store, &task, self.input.trim())
+                .map_err(|e| format!("Failed to log {}: {}", task.name, e))?;
+        }
+        self.input.clear();
+
+        if self.run.next_step(&self.tasks).is_none() {
+            self.run.finish(store).map_err(|e| format!("Failed to save the run: {}", e))?;
+        }
+        Ok(())
+    }
+
+    /// Leave the run, keeping the steps taken so far
+    fn stop(&mut self) -> Transition {
+        let store = TaskStore::instance();
+        match self.run.stop(store, &self.tasks) {
+            Ok(_) => Transition::Pop,
+            Err(e) => {
+                self.error = Some(format!("Failed to save the run: {}", e));
+                Transition::Stay
+            }
+        }
+    }
+}
+
+impl Renderable for RoutineRunViewState {
+
+    fn render(&mut self) -> io::Result<Transition> {
+        render_view(self, Self::controler)
+    }
+
+    fn chunks(&self, frame: Rect) -> Vec<Rect> {
+        Layout::default()
+            .constraints([
+                Constraint::Length(3),
+                Constraint::Min(3),
+                Constraint::Length(3),
+                Constraint::Length(1),
+            ].as_ref())
+            .split(frame)
+    }
+
+    /// Progress through the routine, the steps and the note for the current one
+    fn widgets(&mut self) -> io::Result<Vec<AnyWidget<'_>>> {
+
+        let current = self.run.next_step(&self.tasks).map(|t| t.id);
+        let header = match current {
+            Some(_) => format!(
+                "Step {} of {}", self.run.done.len() + self.run.skipped.len() + 1, self.tasks.len()
+            ),
+            None => format!("Finished, {}", self.run),
+        };
+
+        let steps: Vec<ListItem> = self.tasks.iter()
+            .map(|task| {
+                let (mark, style) = if self.run.done.contains(&task.id) {
+                    ("✓", Style::default().fg(Config::get().colors.good.0))
+                } else if self.run.skipped.contains(&task.id) {
+                    ("-", Style::default().fg(Config::get().colors.muted.0))
+                } else if Some(task.id) == current {
+                    ("▶", Style::default().fg(Config::get().colors.accent.0))
+                } else {
+                    (" ", Style::default())
+                };
+                ListItem::new(format!("{} {}", mark, task.name)).style(style)
+            })
+            .collect();
+
+        let help = match (&self.error, current) {
+            (Some(e), _) => e.clone(),
+            (None, Some(_)) if self.run.done.is_empty() && self.run.skipped.is_empty() => {
+                String::from("Enter to log the step, Tab to skip it, Esc to leave without saving a run")
+            }
+            (None, Some(_)) => String::from("Enter to log the step, Tab to skip it, Esc to stop and skip the rest"),
+            (None, None) => String::from("Enter or Esc to close"),
+        };
+
+        Ok(vec![
+            AnyWidget::Paragraph(
+                Paragraph::new(header)
+                    .block(Block::default().title(self.routine.name.clone()).borders(Borders::ALL))
+            ),
+            AnyWidget::List(
+                List::new(steps).block(Block::default().title("Steps").borders(Borders::ALL))
+            ),
+            paragraph_factory("Note", self.input.as_str(), current.is_some()),
+            AnyWidget::Paragraph(Paragraph::new(help)),
+        ])
+    }
+
+    /// Typing goes into the note, the run can't be navigated otherwise
+    fn controler(&mut self) -> Transition {
+        let finished = self.run.next_step(&self.tasks).is_none();
+        match event::read().unwrap() {
+            Event::Key(KeyEvent { code: KeyCode::Esc, .. }) if finished => Transition::Pop,
+            Event::Key(KeyEvent { code: KeyCode::Esc, .. }) => self.stop(),
+            Event::Key(KeyEvent { code: KeyCode::Enter, .. }) if finished => Transition::Pop,
+            Event::Key(KeyEvent { code: KeyCode::Enter, .. }) => {
+                self.error = self.advance(false).err();
+                Transition::Stay
+            }
+            Event::Key(KeyEvent { code: KeyCode::Tab, .. }) => {
+                self.error = self.advance(true).err();
+                Transition::Stay
+            }
+            Event::Key(KeyEvent { code: KeyCode::Char(c), .. }) if !finished => {
+                self.input.push(c);
+                Transition::Stay
+            }
+            Event::Key(KeyEvent { code: KeyCode::Backspace, .. }) => {
+                self.input.pop();
+                Transition::Stay
+            }
+            _ => Transition::Stay,
+        }
+    }
+}
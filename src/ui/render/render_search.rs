@@ -0,0 +1,121 @@
+// render_search.rs
+
+use std::io;
+use tui::{
+    layout::{Constraint, Layout, Rect},
+    style::{Color, Modifier, Style},
+    text::{Span, Spans, Text},
+    widgets::{Block, Borders, List, ListItem, Paragraph},
+};
+use crossterm::event::{self, Event, KeyCode, KeyEvent};
+use crate::model::convert::Storable;
+use crate::model::search::snippet;
+use crate::ui::view::{Transition, View};
+use crate::ui::state::{SearchViewState, TaskViewState};
+use crate::ui::widgets::paragraph_factory;
+use crate::ui::render::renderable::{Renderable, AnyWidget, render_view};
+use crate::config::Config;
+
+///////////////////////////////////////////////////////////
+
+/// Characters of entry content shown around the first match
+const SNIPPET_WIDTH: usize = 80;
+
+impl Renderable for SearchViewState {
+
+    fn render(&mut self) -> io::Result<Transition> {
+        render_view(self, Self::controler)
+    }
+
+    fn chunks(&self, frame: Rect) -> Vec<Rect> {
+        Layout::default()
+            .constraints([
+                Constraint::Length(3),
+                Constraint::Min(3),
+                Constraint::Length(1),
+            ].as_ref())
+            .split(frame)
+    }
+
+    /// The query and its hits, two lines each with the matches highlighted
+    fn widgets(&mut self) -> io::Result<Vec<AnyWidget<'_>>> {
+
+        let highlight = Style::default().fg(Color::Black).bg(Config::get().colors.accent.0);
+        let selected = Style::default().fg(Config::get().colors.accent.0).add_modifier(Modifier::BOLD);
+
+        // keep the selection in view without knowing the height
+        let skip = self.selector.idx.saturating_sub(5);
+        let items: Vec<ListItem> = self.hits.iter()
+            .enumerate()
+            .skip(skip)
+            .map(|(i, hit)| {
+                let header = Spans::from(Span::styled(
+                    format!("{}  {}", hit.task.name, hit.entry.get_timestamp()),
+                    if i == self.selector.idx { selected } else { Style::default() },
+                ));
+                let body = std::iter::once(Span::raw("  "))
+                    .chain(
+                        snippet(&hit.entry.get_content(), &self.input, SNIPPET_WIDTH)
+                            .into_iter()
+                            .map(|(text, hit)| match hit {
+                                true => Span::styled(text, highlight),
+                                false => Span::raw(text),
+                            })
+                    )
+                    .collect::<Vec<_>>();
+                ListItem::new(Text::from(vec![header, Spans::from(body)]))
+            })
+            .collect();
+
+        let title = match self.input.trim().is_empty() {
+            true => String::from("Results"),
+            false => format!("Results ({})", self.hits.len()),
+        };
+        let help = match &self.error {
+            Some(e) => e.clone(),
+            None => String::from("Type to search, Up/Down to pick, Enter to open, Esc to go back"),
+        };
+
+        Ok(vec![
+            paragraph_factory("Search /", self.input.as_str(), true),
+            AnyWidget::List(List::new(items).block(Block::default().title(title).borders(Borders::ALL))),
+            AnyWidget::Paragraph(Paragraph::new(help)),
+        ])
+    }
+
+    /// Typing goes into the query, so only arrows move the selection
+    fn controler(&mut self) -> Transition {
+        match event::read().unwrap() {
+            Event::Key(KeyEvent { code: KeyCode::Esc, .. }) => Transition::Pop,
+            Event::Key(KeyEvent { code: KeyCode::Up, .. }) => {
+                self.selector.incr();
+                Transition::Stay
+            }
+            Event::Key(KeyEvent { code: KeyCode::Down, .. }) => {
+                self.selector.decr();
+                Transition::Stay
+            }
+            Event::Key(KeyEvent { code: KeyCode::Enter, .. }) => {
+                match self.hits.get(self.selector.idx) {
+                    Some(hit) => {
+                        let mut state = TaskViewState::new(hit.task.clone());
+                        state.select_entry(hit.entry.id);
+                        Transition::Push(View::TaskView(Box::new(state)))
+                    }
+                    None => Transition::Stay,
+                }
+            }
+            Event::Key(KeyEvent { code: KeyCode::Char(c), .. }) => {
+                self.input.push(c);
+                self.refresh();
+                Transition::Stay
+            }
+            Event::Key(KeyEvent { code: KeyCode::Backspace, .. }) => {
+                self.input.pop();
+                self.refresh();
+                Transition::Stay
+            }
+            _ => Transition::Stay,
+        }
+    }
+}
@@ -0,0 +1,98 @@
+use std::io;
+use tui::{
+    layout::{Constraint, Layout, Rect},
+    style::{Color, Style},
+    widgets::{BarChart, Block, Borders, Paragraph, Sparkline},
+};
+use crossterm::event::{Event, KeyCode, KeyEvent};
+use crate::model::task::TaskTy;
+use crate::ui::control::UserAction;
+use crate::ui::view::Transition;
+use crate::ui::state::StatsViewState;
+use crate::ui::widgets::{control_height, control_widget};
+use crate::ui::render::renderable::{Renderable, AnyWidget, render_view, read_bound_key};
+use crate::config::{Config, KeyView};
+
+///////////////////////////////////////////////////////////
+
+impl StatsViewState {
+
+    /// Plain text summary shown above the charts
+    fn summary(&self) -> String {
+        let stats = &self.stats;
+        let rate = match stats.completion_rate() {
+            Some(r) => format!("{:.0}% ({}/{})", r * 100.0, stats.periods_met, stats.periods),
+            None => String::from("no finished periods yet"),
+        };
+
+        let mut lines = vec![
+            format!("Entries: {}", stats.entries),
+            format!(
+                "Target: {} per {}, met in {}",
+                self.task.target, self.task.eval_period.unit(), rate
+            ),
+        ];
+        if self.task.task_type == TaskTy::Numeric {
+            let show = |v: Option<f64>| v.map_or(String::from("-"), |v| format!("{:.2}", v));
+            lines.push(format!(
+                "Min: {}  Max: {}  Average: {}",
+                show(stats.min), show(stats.max), show(stats.mean)
+            ));
+        }
+        lines.join("\n")
+    }
+}
+
+impl Renderable for StatsViewState {
+
+    fn render(&mut self) -> io::Result<Transition> {
+        render_view(self, Self::controler)
+    }
+
+    fn chunks(&self, frame: Rect) -> Vec<Rect> {
+        Layout::default()
+            .constraints([
+                Constraint::Length(control_height(&UserAction::stats_view(), frame.width)),
+                Constraint::Length(5),
+                Constraint::Length(8),
+                Constraint::Min(8),
+            ].as_ref())
+            .split(frame)
+    }
+
+    /// Summary, activity over time and the weekday distribution
+    fn widgets(&mut self) -> io::Result<Vec<AnyWidget<'_>>> {
+
+        let summary = Paragraph::new(self.summary())
+            .block(Block::default().title(self.task.name.clone()).borders(Borders::ALL));
+
+        let activity = Sparkline::default()
+            .block(Block::default().title("Activity").borders(Borders::ALL))
+            .data(&self.activity)
+            .style(Style::default().fg(Config::get().colors.good.0));
+
+        let weekdays = BarChart::default()
+            .block(Block::default().title("By weekday").borders(Borders::ALL))
+            .data(&self.weekdays)
+            .bar_width(5)
+            .bar_gap(2)
+            .bar_style(Style::default().fg(Config::get().colors.good.0))
+            .value_style(Style::default().fg(Color::Black).bg(Config::get().colors.good.0));
+
+        Ok(vec![
+            control_widget(UserAction::stats_view()),
+            AnyWidget::Paragraph(summary),
+            AnyWidget::Sparkline(activity),
+            AnyWidget::BarChart(weekdays),
+        ])
+    }
+
+    fn controler(&mut self) -> Transition {
+        match read_bound_key(KeyView::Stats) {
+            Event::Key(KeyEvent { code: KeyCode::Char('q'), .. }) => Transition::Quit,
+            Event::Key(KeyEvent { code: KeyCode::Char('b') | KeyCode::Esc, .. })
+                => Transition::Pop,
+            _ => Transition::Stay,
+        }
+    }
+}
@@ -1,10 +1,19 @@
 use std::io;
 use tui::layout::Rect;
+use crate::config::KeyView;
 use crate::model::store::TaskStore;
 use crate::ui::view::Transition;
-use crate::ui::state::{TaskViewState, EntryViewState, DeleteViewState};
-use crate::ui::widgets::{control_widget, item_table};
+use crate::ui::state::{
+    TaskViewState, EntryViewState, DeleteViewState, StatsViewState, TimestampViewState,
+    recent_paused_days
+};
+use crate::ui::control::UserAction;
+use crate::ui::widgets::{control_height, control_widget, item_table};
 use crate::model::task_entry::TaskEntry;
+use crate::model::clock::Clock;
+use crate::model::stats::daily_totals;
+use crate::ui::widgets::heatmap::Heatmap;
+use tui::widgets::{Block, Borders};
 use std::time::Instant;
 use tui::layout::{Constraint, Layout};
 use crossterm::event::{Event, KeyCode, KeyEvent};
@@ -34,7 +43,11 @@ impl Renderable for TaskViewState {
     // Create the chunks that widgets will render ontop of 
     fn chunks(&self, frame: Rect) -> Vec<Rect> {        
         Layout::default()
-            .constraints([Constraint::Length(3), Constraint::Max(50)].as_ref())
+            .constraints([
+                Constraint::Length(control_height(&UserAction::task_view(), frame.width)),
+                Constraint::Length(Heatmap::height() + 2),
+                Constraint::Max(50),
+            ].as_ref())
             .split(frame)
     }
 
@@ -51,7 +64,11 @@ impl Renderable for TaskViewState {
             self.selector.idx,
         );
 
-        Ok(vec![control_widget(), entries_widget])
+        let heatmap = Heatmap::new(self.days.clone(), Clock::get().today())
+            .paused(self.paused.clone())
+            .block(Block::default().title("Activity").borders(Borders::ALL));
+
+        Ok(vec![control_widget(UserAction::task_view()), AnyWidget::Heatmap(heatmap), entries_widget])
     }
  
     /// Check the poll interval 
@@ -67,12 +84,14 @@ impl Renderable for TaskViewState {
         self.items = TaskStore::instance()
             .get_prefix(TaskEntry::key_task(self.task.id))
             .unwrap(); 
+        self.days = daily_totals(&self.task, &self.items, Clock::get());
+        self.paused = recent_paused_days(&self.task);
         self.selector.max_idx = self.items.len();
     }
 
     fn controler(&mut self) -> Transition {
 
-        match default_controls(&mut self.selector) {
+        match default_controls(&mut self.selector, KeyView::Task) {
     
             // A default case was handled
             ControlOption::T(t) => t,
@@ -108,6 +127,21 @@ impl Renderable for TaskViewState {
                             let item = self.items[self.selector.idx].clone();
                             Transition::Push(View::EntryView(EntryViewState::new(item)))
                         } 
+                    // What to do on "time"
+                    Event::Key(KeyEvent { code: KeyCode::Char('t'), .. })
+                        => {
+                            let item = self.items[self.selector.idx].clone();
+                            Transition::Push(View::TimestampView(TimestampViewState::new(item)))
+                        }
+                    // What to do on "stats"
+                    Event::Key(KeyEvent { code: KeyCode::Char('S'), .. })
+                        => {
+                            Transition::Push(
+                                View::StatsView(
+                                    Box::new(StatsViewState::new(self.task.clone(), self.items.clone()))
+                                )
+                            )
+                        }
                     _ => Transition::Stay
                 }
             }
@@ -0,0 +1,75 @@
+// render_timestamp.rs
+
+use std::io;
+use chrono::Utc;
+use crate::model::clock::Clock;
+use crate::model::convert::Storable;
+use crate::model::store::TaskStore;
+use crate::model::when::parse_when;
+use crate::ui::view::Transition;
+use crate::ui::state::TimestampViewState;
+use tui::widgets::Paragraph;
+use crate::ui::render::renderable::AnyWidget;
+use crate::ui::render::render_create::FormRenderable;
+use crate::ui::widgets::paragraph_factory;
+use crossterm::event::{self, Event, KeyCode, KeyEvent};
+
+///////////////////////////////////////////////////////////
+
+impl FormRenderable for TimestampViewState {
+
+    fn widgets(&mut self) -> io::Result<Vec<AnyWidget<'_>>> {
+
+        let status = match &self.error {
+            Some(e) => e.clone(),
+            None => String::from(
+                "When was this done? e.g. '2026-03-04 19:30' or 'yesterday 7pm'. Enter to save, Esc to cancel."
+            ),
+        };
+
+        Ok(vec![
+            paragraph_factory("Timestamp", self.input.as_str(), true),
+            AnyWidget::Paragraph(Paragraph::new(status).wrap(tui::widgets::Wrap { trim: true })),
+        ])
+    }
+
+    /// handle user inputs
+    fn controller(&mut self) -> Transition {
+        match event::read().unwrap() {
+            Event::Key(KeyEvent { code: KeyCode::Esc, .. }) => Transition::Pop,
+            Event::Key(KeyEvent { code: KeyCode::Char(c), .. }) => {
+                self.input.push(c);
+                Transition::Stay
+            }
+            Event::Key(KeyEvent { code: KeyCode::Backspace, .. }) => {
+                self.input.pop();
+                Transition::Stay
+            }
+            Event::Key(KeyEvent { code: KeyCode::Enter, .. }) => {
+                let mut updated = self.task_entry.clone();
+                match parse_when(&self.input, Clock::get(), Utc::now()) {
+                    Ok(timestamp) => updated.timestamp = timestamp,
+                    Err(e) => {
+                        self.error = Some(e.to_string());
+                        return Transition::Stay;
+                    }
+                }
+
+                // entries are ordered by timestamp when read, so a put
+                // is all it takes to move one
+                if !self.task_entry.is_legal_update_from(&updated) {
+                    self.error = Some(String::from("Invalid update"));
+                    return Transition::Stay;
+                }
+                match TaskStore::instance().put(updated) {
+                    Ok(_) => Transition::Pop,
+                    Err(e) => {
+                        self.error = Some(format!("Failed to save entry: {}", e));
+                        Transition::Stay
+                    }
+                }
+            }
+            _ => Transition::Stay,
+        }
+    }
+}
@@ -4,12 +4,15 @@ use crossterm::execute;
 use tui::layout::Rect;
 use tui::widgets::Paragraph;
 use crate::ui::view::{Transition, View};
-use crate::ui::state::SelectionState;
+use crate::ui::state::{PopUpViewState, SelectionState};
+use crate::ui::editor::{open_editor, EditorError};
+use crate::config::{Config, KeyView};
+use crate::ui::widgets::heatmap::Heatmap;
 use tui::buffer::Buffer;
 use tui::{ 
     backend::{Backend, CrosstermBackend},
     layout::Layout,
-    widgets::{List, Table, Widget},
+    widgets::{BarChart, List, Sparkline, Table, Widget},
     Terminal,
 };
 use crossterm::event::{self, Event, KeyCode, KeyEvent};
@@ -26,6 +29,9 @@ pub enum AnyWidget<'a> {
     List(List<'a>),
     Table(Table<'a>),
     Paragraph(Paragraph<'a>),
+    Heatmap(Heatmap<'a>),
+    Sparkline(Sparkline<'a>),
+    BarChart(BarChart<'a>),
 }
 
 pub enum ControlOption {
@@ -40,6 +46,9 @@ impl<'a> Widget for AnyWidget<'a> {
             AnyWidget::List(list) => list.render(area, buf),
             AnyWidget::Table(table) => table.render(area, buf),
             AnyWidget::Paragraph(para) => para.render(area, buf),
+            AnyWidget::Heatmap(heatmap) => heatmap.render(area, buf),
+            AnyWidget::Sparkline(sparkline) => sparkline.render(area, buf),
+            AnyWidget::BarChart(chart) => chart.render(area, buf),
         }
     }
 }
@@ -68,10 +77,11 @@ pub trait Renderable {
 
 ///
 /// Try to match one of the default events, in which case return the appropriate
-/// transition, otherwise return the event read so it can be dealt with manually
-pub fn default_controls(selector: &mut SelectionState) -> ControlOption {
+/// transition, otherwise return the event read so it can be dealt with manually.
+/// Keys come back as their built in ones whatever the user bound them to.
+pub fn default_controls(selector: &mut SelectionState, view: KeyView) -> ControlOption {
     
-    let event: Event = event::read().unwrap();
+    let event: Event = read_bound_key(view);
     match event {
 
         // Event::Key(KeyEvent { code: KeyCode::Char('q'), .. })
@@ -98,6 +108,30 @@ pub fn default_controls(selector: &mut SelectionState) -> ControlOption {
     } 
 }
 
+///
+/// Read an event, with key presses translated through the keys configured
+/// for `view`
+pub fn read_bound_key(view: KeyView) -> Event {
+    match event::read().unwrap() {
+        Event::Key(mut key) => {
+            key.code = Config::get().keys.translate(key.code, view);
+            Event::Key(key)
+        }
+        event => event,
+    }
+}
+
+///
+/// Open the editor on `bytes` for a view. A failure goes in `failed` and is
+/// shown in the popup, on coming back from it the view should be left.
+pub fn edit_in_view(bytes: &[u8], failed: &mut Option<EditorError>) -> Result<String, Box<Transition>> {
+    open_editor(bytes).map_err(|e| {
+        let popup = View::PopUpView(PopUpViewState::new(e.to_string()));
+        *failed = Some(e);
+        Box::new(Transition::Push(popup))
+    })
+}
+
 ///
 /// Render a generic View type on the screen,
 /// TODO: remove need for control_handler
@@ -127,7 +161,11 @@ where
             Transition::Stay => {
                 return Ok(transition);
             }
-            Transition::Push(View::DeleteView(_)) | Transition::Push(View::CreateTaskView(_)) => {
+            Transition::Push(View::DeleteView(_))
+            | Transition::Push(View::CreateTaskView(_))
+            | Transition::Push(View::PauseView(_))
+            | Transition::Push(View::TimestampView(_))
+            | Transition::Push(View::CreateRoutineView(_)) => {
                 return Ok(transition);
             }
             _ => {
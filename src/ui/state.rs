@@ -1,10 +1,22 @@
 
 /// state.rs
 
-use crate::model::task::Task;
+use crate::model::task::{all_tags, Task, TaskFilter};
 use crate::model::task_entry::TaskEntry;
 use crate::model::store::TaskStore;
 use crate::model::convert::Storable;
+use crate::model::clock::{Clock, DEFAULT_TIMESTAMP_FORMAT};
+use crate::config::Config;
+use crate::model::progress::{progress_all, tag_rollups, Progress, TagRollup};
+use crate::model::stats::{daily_totals, recent_days, TaskStats};
+use crate::model::pause::{paused_days, Pause};
+use crate::model::order::TaskOrder;
+use crate::model::routine::{Routine, RoutineRun};
+use crate::model::search::{search, SearchHit};
+use crate::model::query::Query;
+use crate::ui::editor::EditorError;
+use chrono::{NaiveDate, Utc};
+use std::collections::{BTreeMap, BTreeSet};
 use std::time::{Duration, Instant};
 
 ///////////////////////////////////////////////////////////
@@ -19,6 +31,16 @@ pub struct SelectionState {
 pub struct MainViewState {
     pub selector: SelectionState,
     pub items: Vec<Task>,
+    pub progress: Vec<Progress>,    // parallel to items
+    pub rollups: Vec<TagRollup>,
+    pub tags: Vec<String>,          // every tag in use
+    pub tag_filter: Option<String>, // only show tasks with this tag
+    pub show_archived: bool,
+    pub order: TaskOrder,
+    pub query: Query,               // applied filter
+    pub query_input: String,        // filter bar text, may not parse yet
+    pub query_error: Option<String>,
+    pub editing_query: bool,
     pub poll_interval: Duration,
     pub last_poll_time: Instant,
 }
@@ -28,6 +50,8 @@ pub struct TaskViewState {
     pub selector: SelectionState,
     pub task: Task,
     pub items: Vec<TaskEntry>,
+    pub days: BTreeMap<NaiveDate, f64>,  // heatmap data
+    pub paused: BTreeSet<NaiveDate>,
     pub poll_interval: Duration,
     pub last_poll_time: Instant,
 }
@@ -35,7 +59,15 @@ pub struct TaskViewState {
 #[derive(Debug, PartialEq)]
 pub struct EntryViewState {
     pub task_entry: TaskEntry,
-    pub in_editor: bool,
+    pub editor_error: Option<EditorError>,  // shown in a popup, then the view is left
+}
+
+#[derive(Debug, PartialEq)]
+pub struct StatsViewState {
+    pub task: Task,
+    pub stats: TaskStats,
+    pub activity: Vec<u64>,                 // per day, oldest first
+    pub weekdays: Vec<(&'static str, u64)>,
 }
 
 #[derive(Debug, PartialEq)]
@@ -46,6 +78,54 @@ pub struct CreateTaskViewState {
     pub is_edit: bool,
 }
 
+#[derive(Debug, PartialEq)]
+pub struct PauseViewState {
+    pub task: Option<Task>,     // None pauses every task
+    pub inputs: Vec<String>,    // from, to, reason
+    pub active_input: usize,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct TimestampViewState {
+    pub task_entry: TaskEntry,
+    pub input: String,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct RoutineListViewState {
+    pub selector: SelectionState,
+    pub items: Vec<Routine>,
+    pub last_runs: Vec<Option<RoutineRun>>, // parallel to items
+    pub poll_interval: Duration,
+    pub last_poll_time: Instant,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct RoutineRunViewState {
+    pub routine: Routine,
+    pub tasks: Vec<Task>,       // members still around, in order
+    pub run: RoutineRun,
+    pub input: String,          // content of the next entry
+    pub error: Option<String>,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct CreateRoutineViewState {
+    pub inputs: Vec<String>,    // name, description, member tasks
+    pub active_input: usize,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct SearchViewState {
+    pub selector: SelectionState,
+    pub input: String,
+    pub hits: Vec<SearchHit>,
+    pub error: Option<String>,
+}
+
 #[derive(Debug, PartialEq)]
 pub struct PopUpViewState {
     pub text: String
@@ -59,6 +139,7 @@ pub struct DeleteViewState<T> {
 #[derive(Debug, PartialEq)]
 pub struct ConfigViewState<T> {
     pub config_item: T,
+    pub editor_error: Option<EditorError>,  // shown in a popup, then the view is left
 }
 
 ///////////////////////////////////////////////////////////
@@ -94,17 +175,151 @@ impl MainViewState {
 
     pub fn new() -> Self {
 
-        let tasks = TaskStore::instance()
-            .get_prefix(Task::key_all())
-            .unwrap();
-    
-        MainViewState {
-            selector: SelectionState::new(tasks.len()),
-            items: tasks,
-            poll_interval: Duration::from_millis(100),
+        let mut state = MainViewState {
+            selector: SelectionState::new(0),
+            items: vec![],
+            progress: vec![],
+            rollups: vec![],
+            tags: vec![],
+            tag_filter: None,
+            show_archived: false,
+            order: TaskOrder::load(TaskStore::instance()).unwrap(),
+            query: Query::default(),
+            query_input: String::new(),
+            query_error: None,
+            editing_query: false,
+            poll_interval: Config::get().poll_interval(),
             last_poll_time: Instant::now(),
-        }
+        };
+        state.refresh();
+        state
     }    
+
+    /// Reload the tasks from the store, applying the tag filter
+    pub fn refresh(&mut self) {
+
+        let tasks: Vec<Task> = TaskStore::instance()
+            .get_prefix::<Task>(Task::key_all())
+            .unwrap()
+            .into_iter()
+            .filter(|t| self.show_archived || !t.archived)
+            .collect();
+        let progress = task_progress(&tasks);
+
+        self.rollups = tag_rollups(&tasks, &progress);
+        self.tags = all_tags(&tasks);
+
+        let filter = TaskFilter {
+            tag: self.tag_filter.clone(),
+            include_archived: self.show_archived,
+            query: self.query.clone(),
+        };
+        let keep = |task: &Task| {
+            let entries: Vec<TaskEntry> = match filter.query.has_entry_clauses() {
+                true => TaskStore::instance().get_prefix(TaskEntry::key_task(task.id)).unwrap(),
+                false => vec![],
+            };
+            filter.select(task, &entries, Clock::get()).is_some()
+        };
+        (self.items, self.progress) = self.order.sort(&tasks, &progress)
+            .into_iter()
+            .filter(|i| keep(&tasks[*i]))
+            .map(|i| (tasks[i].clone(), progress[i].clone()))
+            .unzip();
+
+        self.selector.max_idx = self.items.len();
+        self.selector.idx = self.selector.idx.min(self.items.len().saturating_sub(1));
+    }
+
+    /// Show only the next tag's tasks, after the last tag show every task
+    pub fn cycle_tag_filter(&mut self) {
+        let next = match &self.tag_filter {
+            None => 0,
+            Some(tag) => self.tags.iter().position(|t| t == tag).map_or(0, |i| i + 1),
+        };
+        self.tag_filter = self.tags.get(next).cloned();
+        self.refresh();
+    }
+
+    /// Apply the filter bar text if it parses, otherwise say why not
+    pub fn apply_query(&mut self) {
+        match Query::parse(&self.query_input, Clock::get().today()) {
+            Ok(query) => {
+                self.query = query;
+                self.query_error = None;
+                self.refresh();
+            }
+            Err(e) => self.query_error = Some(e.to_string()),
+        }
+    }
+
+    /// Show or hide the archived tasks
+    pub fn toggle_archived(&mut self) {
+        self.show_archived = !self.show_archived;
+        self.refresh();
+    }
+
+    /// Pin the selected task to the top, or unpin it
+    pub fn pin_selected(&mut self) {
+        if let Some(task) = self.items.get(self.selector.idx) {
+            let mut task = task.clone();
+            task.pinned = !task.pinned;
+            let id = task.id;
+            TaskStore::instance().put(task).unwrap();
+            self.refresh();
+            self.select_task(id);
+        }
+    }
+
+    /// Move the selected task one row down or up, keeping it selected
+    pub fn move_selected(&mut self, down: bool) {
+        if let Some(idx) = self.order.move_task(&self.items, self.selector.idx, down) {
+            self.order = TaskStore::instance().put(self.order.clone()).unwrap();
+            self.refresh();
+            self.selector.idx = idx;
+        }
+    }
+
+    /// Sort by the next mode, the choice is kept in the store
+    pub fn cycle_sort(&mut self) {
+        self.order.mode = self.order.mode.next();
+        self.order = TaskStore::instance().put(self.order.clone()).unwrap();
+        self.refresh();
+    }
+
+    fn select_task(&mut self, id: uuid::Uuid) {
+        if let Some(idx) = self.items.iter().position(|t| t.id == id) {
+            self.selector.idx = idx;
+        }
+    }
+
+    /// Archive the selected task, or bring it back if it already is
+    pub fn archive_selected(&mut self) {
+        if let Some(task) = self.items.get(self.selector.idx) {
+            let mut task = task.clone();
+            task.archived = !task.archived;
+            TaskStore::instance().put(task).unwrap();
+            self.refresh();
+        }
+    }
+}
+
+/// Progress of each task towards its target, as of now
+pub fn task_progress(tasks: &[Task]) -> Vec<Progress> {
+    progress_all(TaskStore::instance(), tasks, Clock::get(), Utc::now()).unwrap()
+}
+
+/// Every pause on record
+pub fn all_pauses() -> Vec<Pause> {
+    TaskStore::instance()
+        .get_prefix(Pause::key_all())
+        .unwrap()
+}
+
+/// Paused days of a task over the last year, as far back as a heatmap reaches
+pub fn recent_paused_days(task: &Task) -> BTreeSet<NaiveDate> {
+    let today = Clock::get().today();
+    paused_days(&all_pauses(), task.id, today - chrono::Duration::weeks(53), today)
 }
 
 impl TaskViewState {
@@ -118,12 +333,21 @@ impl TaskViewState {
 
         TaskViewState {
             selector: SelectionState::new(task_entries.len()),
+            days: daily_totals(&task, &task_entries, Clock::get()),
+            paused: recent_paused_days(&task),
             items: task_entries,
             task,
-            poll_interval: Duration::from_millis(100),
+            poll_interval: Config::get().poll_interval(),
             last_poll_time: Instant::now(),
         }
     }
+
+    /// Put the selection on an entry, e.g. one found by a search
+    pub fn select_entry(&mut self, id: uuid::Uuid) {
+        if let Some(idx) = self.items.iter().position(|e| e.id == id) {
+            self.selector.idx = idx;
+        }
+    }
 }
 
 // View for task entries
@@ -132,7 +356,31 @@ impl EntryViewState {
 
         EntryViewState {
             task_entry: task,
-            in_editor: false
+            editor_error: None,
+        }
+    }
+}
+
+impl StatsViewState {
+    pub fn new(task: Task, entries: Vec<TaskEntry>) -> Self {
+
+        let clock = Clock::get();
+        let stats = TaskStats::compute(&task, &entries, &all_pauses(), clock, Utc::now());
+
+        // one bar of the sparkline per column of the terminal
+        let (width, _) = crossterm::terminal::size().unwrap_or((80, 24));
+        let activity = recent_days(
+            &daily_totals(&task, &entries, clock),
+            clock.today(),
+            width.saturating_sub(2) as usize,
+        );
+
+        let labels = ["Mon", "Tue", "Wed", "Thu", "Fri", "Sat", "Sun"];
+        StatsViewState {
+            activity: activity.into_iter().map(|v| v.round() as u64).collect(),
+            weekdays: labels.into_iter().zip(stats.weekdays).collect(),
+            stats,
+            task,
         }
     }
 }
@@ -140,7 +388,7 @@ impl EntryViewState {
 impl CreateTaskViewState {
     pub fn new(item: Task, is_edit: bool) -> Self {
         
-        let inputs = vec![item.name.clone(), item.desc.clone()];
+        let inputs = vec![item.name.clone(), item.desc.clone(), item.tags.join(", ")];
         CreateTaskViewState {
             item,
             inputs,
@@ -150,6 +398,120 @@ impl CreateTaskViewState {
     }
 }
 
+impl PauseViewState {
+    pub fn new(task: Option<Task>) -> Self {
+
+        // a week off starting today
+        let today = Clock::get().today();
+        let inputs = vec![
+            today.to_string(),
+            (today + chrono::Duration::days(6)).to_string(),
+            String::new(),
+        ];
+        PauseViewState {
+            task,
+            inputs,
+            active_input: 0,
+            error: None,
+        }
+    }
+}
+
+impl TimestampViewState {
+    pub fn new(task_entry: TaskEntry) -> Self {
+        TimestampViewState {
+            input: Clock::get().format(task_entry.timestamp, DEFAULT_TIMESTAMP_FORMAT),
+            task_entry,
+            error: None,
+        }
+    }
+}
+
+impl RoutineListViewState {
+    pub fn new() -> Self {
+        let mut state = RoutineListViewState {
+            selector: SelectionState::new(0),
+            items: vec![],
+            last_runs: vec![],
+            poll_interval: Config::get().poll_interval(),
+            last_poll_time: Instant::now(),
+        };
+        state.refresh();
+        state
+    }
+
+    /// Reload the routines and when each was last run
+    pub fn refresh(&mut self) {
+        let store = TaskStore::instance();
+        self.items = store.get_prefix(Routine::key_all()).unwrap();
+        self.last_runs = self.items.iter()
+            .map(|r| r.last_run(store).unwrap())
+            .collect();
+        self.selector.max_idx = self.items.len();
+        self.selector.idx = self.selector.idx.min(self.items.len().saturating_sub(1));
+    }
+}
+
+impl Default for RoutineListViewState {
+    fn default() -> Self { Self::new() }
+}
+
+impl RoutineRunViewState {
+    pub fn new(routine: Routine) -> Self {
+        RoutineRunViewState {
+            tasks: routine.tasks(TaskStore::instance()).unwrap(),
+            run: RoutineRun::new(routine.id),
+            routine,
+            input: String::new(),
+            error: None,
+        }
+    }
+}
+
+impl CreateRoutineViewState {
+    pub fn new() -> Self {
+        CreateRoutineViewState {
+            inputs: vec![String::new(), String::new(), String::new()],
+            active_input: 0,
+            error: None,
+        }
+    }
+}
+
+impl Default for CreateRoutineViewState {
+    fn default() -> Self { Self::new() }
+}
+
+impl SearchViewState {
+    pub fn new() -> Self {
+        SearchViewState {
+            selector: SelectionState::new(0),
+            input: String::new(),
+            hits: vec![],
+            error: None,
+        }
+    }
+
+    /// Search again for what has been typed so far
+    pub fn refresh(&mut self) {
+        match search(TaskStore::instance(), &self.input) {
+            Ok(hits) => {
+                self.hits = hits;
+                self.error = None;
+            }
+            Err(e) => {
+                self.hits = vec![];
+                self.error = Some(format!("Search failed: {}", e));
+            }
+        }
+        self.selector = SelectionState::new(self.hits.len());
+    }
+}
+
+impl Default for SearchViewState {
+    fn default() -> Self { Self::new() }
+}
+
 impl PopUpViewState {
     pub fn new(text: String) -> Self {
         PopUpViewState {
@@ -170,6 +532,7 @@ impl<T> ConfigViewState<T> {
     pub fn new(config_item: T) -> Self {
         ConfigViewState {
             config_item,
+            editor_error: None,
         }
     }
 }
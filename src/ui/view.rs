@@ -13,11 +13,18 @@ use std::fmt::{Display, Debug};
 /// TODO: Fix some confusing naming
 #[derive(Debug)]
 pub enum View {
-    MainView(MainViewState),                // list of tasks
-    TaskView(TaskViewState),                // list of task entries
+    MainView(Box<MainViewState>),           // list of tasks
+    TaskView(Box<TaskViewState>),           // list of task entries
     EntryView(EntryViewState),              // view an entry (vim)
     CreateTaskView(CreateTaskViewState),    // form for new Task
+    PauseView(PauseViewState),              // form for new Pause
+    TimestampView(TimestampViewState),      // form to move an entry in time
     PopUpView(PopUpViewState),
+    StatsView(Box<StatsViewState>),         // charts for a task
+    RoutineListView(Box<RoutineListViewState>),     // list of routines
+    RoutineRunView(Box<RoutineRunViewState>),       // step through a routine
+    CreateRoutineView(CreateRoutineViewState),      // form for new Routine
+    SearchView(Box<SearchViewState>),       // full-text search of entries
 
     // dynamic views
     ConfigView(Box<dyn DynView>),
@@ -52,7 +59,7 @@ impl App {
 
         let view_state = MainViewState::new();
         let mut view_stack = Vec::new();
-        view_stack.push(View::MainView(view_state));
+        view_stack.push(View::MainView(Box::new(view_state)));
 
         App { view_stack }
     }
@@ -68,9 +75,16 @@ impl App {
                 Some(View::TaskView(ts))        => ts.render()?,
                 Some(View::EntryView(es))       => es.render()?,
                 Some(View::CreateTaskView(cs))  => cs.render()?,
+                Some(View::PauseView(ps))       => ps.render()?,
+                Some(View::TimestampView(ts))   => ts.render()?,
                 Some(View::DeleteView(ds))      => ds.render()?,
                 Some(View::ConfigView(cs))      => cs.render()?,
                 Some(View::PopUpView(ps))       => ps.render()?,  
+                Some(View::StatsView(ss))       => ss.render()?,
+                Some(View::RoutineListView(rs)) => rs.render()?,
+                Some(View::RoutineRunView(rs))  => rs.render()?,
+                Some(View::CreateRoutineView(cs)) => cs.render()?,
+                Some(View::SearchView(ss))      => ss.render()?,
                 _ => panic!("This is a packrat bug!")
             };
             
@@ -1,6 +1,9 @@
+pub mod heatmap;
+
 use crate::{model::convert::Storable, ui::control::UserAction};
 use std::fmt::Display;
 use crate::ui::render::renderable::AnyWidget;
+use crate::config::Config;
 use tui::{ 
     layout::Constraint,
     style::{Color, Modifier, Style},
@@ -44,7 +47,7 @@ where
                     .title(title.into())
                     .borders(Borders::ALL)
                     .style(if highlight {
-                        Style::default().fg(Color::Yellow)
+                        Style::default().fg(Config::get().colors.accent.0)
                     } else {
                         Style::default()
                     }), 
@@ -54,25 +57,45 @@ where
 }
 
 ///////////////////////////////////////////////////////////
-pub fn control_widget<'a>() -> AnyWidget<'a> {
+pub fn control_widget<'a>(actions: Vec<UserAction>) -> AnyWidget<'a> {
     
-    let control_string = UserAction::all()
+    // wrapped between the actions only, never inside one
+    let control_string = control_labels(&actions)
         .iter()
-        .map(|action| action.to_string())
+        .map(|label| label.replace(' ', "\u{a0}"))
         .collect::<Vec<_>>()
-        .join(" | ");
-
-    let control_row = Row::new(vec![Cell::from(control_string)]);
-    let table = Table::new(vec![control_row])
-        .block(
-            Block::default()
-                .title("Controls")
-                .borders(Borders::ALL),
-        )
-        .widths(&[Constraint::Percentage(100)])
-        .column_spacing(1);
+        .join(CONTROL_GAP);
 
-    AnyWidget::Table(table)
+    AnyWidget::Paragraph(
+        Paragraph::new(control_string)
+            .block(
+                Block::default()
+                    .title("Controls")
+                    .borders(Borders::ALL),
+            )
+            .wrap(Wrap {trim: true})
+    )
+}
+
+/// Rows the controls take at `width`, borders included
+pub fn control_height(actions: &[UserAction], width: u16) -> u16 {
+    let inner = width.saturating_sub(2) as usize;
+    let mut lines = 1;
+    let mut used = 0;
+    for len in control_labels(actions).iter().map(|l| l.chars().count()) {
+        if used > 0 && used + CONTROL_GAP.len() + len > inner {
+            lines += 1;
+            used = 0;
+        }
+        used += if used > 0 { CONTROL_GAP.len() + len } else { len };
+    }
+    lines + 2
+}
+
+const CONTROL_GAP: &str = "  ";
+
+fn control_labels(actions: &[UserAction]) -> Vec<String> {
+    actions.iter().map(|action| action.to_string()).collect()
 }
 
 // pub fn item_table<'a, T>(
@@ -120,16 +143,29 @@ pub fn item_table<'a, T>(
 ) -> AnyWidget<'a>
 where
     T: Storable,
+{
+    let rows = tasks.iter().map(|t| t.get_display_fields()).collect();
+    row_table(rows, column_headers, constraints, select_idx, "Tasks")
+}
+
+/// Like `item_table`, for rows that need more than the item itself to display
+pub fn row_table<'a>(
+    rows: Vec<Vec<String>>,
+    column_headers: &'a [&'a str],
+    constraints: &'a [Constraint],
+    select_idx: usize,
+    title: impl Into<String>,
+) -> AnyWidget<'a>
 {
     let style = Style::default();
 
-    let task_rows: Vec<Row> = tasks
-        .iter()
+    let task_rows: Vec<Row> = rows
+        .into_iter()
         .enumerate()
-        .map(|(i, t)| {
-            Row::new(t.get_display_fields()).style(
+        .map(|(i, fields)| {
+            Row::new(fields).style(
                 if i == select_idx {
-                    style.fg(Color::Yellow).add_modifier(Modifier::BOLD)
+                    style.fg(Config::get().colors.accent.0).add_modifier(Modifier::BOLD)
                 } else {
                     style
                 },
@@ -143,7 +179,7 @@ where
         .collect();
 
     let table = Table::new(task_rows)
-        .block(Block::default().title("Tasks").borders(Borders::ALL))
+        .block(Block::default().title(title.into()).borders(Borders::ALL))
         .header(Row::new(column_labels))
         .widths(constraints) // references constraints up in the caller
         .column_spacing(2);
@@ -159,7 +195,7 @@ where
     items.iter().enumerate().map(|(i, t)| {
         if i == select_idx {
             Style::default()
-                .fg(Color::Yellow)
+                .fg(Config::get().colors.accent.0)
                 .add_modifier(Modifier::BOLD)
         } else {
             Style::default()
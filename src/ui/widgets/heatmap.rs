@@ -0,0 +1,140 @@
+// heatmap.rs
+
+use std::collections::{BTreeMap, BTreeSet};
+use chrono::{Datelike, Duration, NaiveDate};
+use crate::model::stats::shade_level;
+use tui::{
+    buffer::Buffer,
+    layout::Rect,
+    style::{Color, Style},
+    widgets::{Block, Widget},
+};
+
+///////////////////////////////////////////////////////////
+
+/// Columns taken by the weekday labels on the left
+const LABEL_WIDTH: u16 = 4;
+
+/// Columns taken by a single week
+const CELL_WIDTH: u16 = 2;
+
+/// A year is at most 53 weeks
+const MAX_WEEKS: u16 = 53;
+
+/// Shades from no activity to the busiest day
+const SHADES: [Color; 5] = [
+    Color::DarkGray,
+    Color::Rgb(14, 68, 41),
+    Color::Rgb(0, 109, 50),
+    Color::Rgb(38, 166, 65),
+    Color::Rgb(57, 211, 83),
+];
+
+/// Days a task was on hold for
+const PAUSED: Color = Color::Blue;
+
+/// A GitHub style calendar of daily activity: one column per week, one row
+/// per weekday, ending with the week of `end`. As many weeks are drawn as
+/// fit in the area, up to a year.
+#[derive(Debug, Clone)]
+pub struct Heatmap<'a> {
+    days: BTreeMap<NaiveDate, f64>,
+    end: NaiveDate,
+    paused: BTreeSet<NaiveDate>,
+    block: Option<Block<'a>>,
+}
+
+impl<'a> Heatmap<'a> {
+
+    pub fn new(days: BTreeMap<NaiveDate, f64>, end: NaiveDate) -> Self {
+        Heatmap { days, end, paused: BTreeSet::new(), block: None }
+    }
+
+    /// Days to shade as paused, unless something was logged anyway
+    pub fn paused(mut self, paused: BTreeSet<NaiveDate>) -> Self {
+        self.paused = paused;
+        self
+    }
+
+    pub fn block(mut self, block: Block<'a>) -> Self {
+        self.block = Some(block);
+        self
+    }
+
+    /// Rows needed to draw the heatmap, not counting the block
+    pub fn height() -> u16 { 8 }
+
+    /// Number of weeks that fit in `width` columns
+    pub fn weeks_for(width: u16) -> u16 {
+        (width.saturating_sub(LABEL_WIDTH) / CELL_WIDTH).min(MAX_WEEKS)
+    }
+
+    /// Which of the shades a day's value gets relative to the busiest day
+    pub fn level(value: f64, max: f64) -> usize {
+        shade_level(value, max, SHADES.len())
+    }
+}
+
+impl<'a> Widget for Heatmap<'a> {
+
+    fn render(mut self, area: Rect, buf: &mut Buffer) {
+
+        let area = match self.block.take() {
+            Some(block) => {
+                let inner = block.inner(area);
+                block.render(area, buf);
+                inner
+            }
+            None => area,
+        };
+
+        let weeks = Heatmap::weeks_for(area.width);
+        if area.height < Heatmap::height() || weeks == 0 {
+            return;
+        }
+
+        let last_monday = self.end
+            - Duration::days(self.end.weekday().num_days_from_monday() as i64);
+        let first_monday = last_monday - Duration::weeks(weeks as i64 - 1);
+
+        let max = self.days
+            .range(first_monday..=self.end)
+            .map(|(_, v)| *v)
+            .fold(0.0, f64::max);
+
+        let label_style = Style::default().fg(Color::Gray);
+        for (row, label) in [(0, "Mon"), (2, "Wed"), (4, "Fri"), (6, "Sun")] {
+            buf.set_string(area.x, area.y + 1 + row, label, label_style);
+        }
+
+        // month labels are wider than a week, don't let them overlap
+        let mut label_free_x = 0;
+        for week in 0..weeks {
+            let monday = first_monday + Duration::weeks(week as i64);
+            let x = area.x + LABEL_WIDTH + week * CELL_WIDTH;
+
+            // label the first column and every column a month starts in
+            let prev_monday = monday - Duration::weeks(1);
+            if (week == 0 || monday.month() != prev_monday.month()) && x >= label_free_x {
+                let month = monday.format("%b").to_string();
+                let room = (area.x + area.width).saturating_sub(x) as usize;
+                buf.set_stringn(x, area.y, &month, room, label_style);
+                label_free_x = x + month.len() as u16 + 1;
+            }
+
+            for weekday in 0..7 {
+                let day = monday + Duration::days(weekday as i64);
+                if day > self.end {
+                    break;
+                }
+                let value = self.days.get(&day).copied().unwrap_or(0.0);
+                let color = match Heatmap::level(value, max) {
+                    0 if self.paused.contains(&day) => PAUSED,
+                    level => SHADES[level],
+                };
+                let style = Style::default().fg(color);
+                buf.set_string(x, area.y + 1 + weekday, "■", style);
+            }
+        }
+    }
+}
@@ -0,0 +1,58 @@
+use packrat::cli::{exit_code, InvalidInput, EXIT_AMBIGUOUS, EXIT_FAILURE, EXIT_INVALID, EXIT_NOT_FOUND};
+use chrono::NaiveDate;
+use packrat::model::order::TaskOrder;
+use packrat::model::pause::Pause;
+use packrat::model::routine::Routine;
+use packrat::model::store::{StoreError, TaskStore};
+use packrat::model::convert::Storable;
+use packrat::model::task::{EvalPeriod, Task, TaskTy};
+use packrat::model::task_entry::TaskEntry;
+use tempfile::TempDir;
+
+#[test]
+fn test_lookups_and_exit_codes() {
+
+    let dir = TempDir::new().unwrap();
+    let ts = TaskStore::new(dir.path().join("test.db").to_str().unwrap());
+
+    let run = ts.put(Task::new("Run", "5k")).unwrap();
+    let swim = ts.put(Task::new("Swim", "")).unwrap();
+    let entry = ts.put(TaskEntry::new(run.id, "knee sore")).unwrap();
+    ts.put(TaskEntry::new(run.id, "easy")).unwrap();
+
+    assert_eq!(ts.find_task("RUN").unwrap().id, run.id);
+    assert_eq!(ts.find_entry(&entry.id.to_string()[..8]).unwrap(), entry);
+
+    let code = |e: StoreError| exit_code(&e);
+    assert_eq!(code(ts.find_task("walk").unwrap_err()), EXIT_NOT_FOUND);
+    assert_eq!(code(ts.find_entry("").unwrap_err()), EXIT_NOT_FOUND);
+    assert!(matches!(ts.find_entry("0123456789").unwrap_err(), StoreError::NotFound("entry", _)));
+
+    let ambiguous = StoreError::Ambiguous("task", String::from("s"), 2);
+    assert_eq!(ambiguous.to_string(), "'s' matches 2 tasks, use more of the name or ID");
+    assert_eq!(code(ambiguous), EXIT_AMBIGUOUS);
+    assert_eq!(exit_code(&InvalidInput(String::from("no"))), EXIT_INVALID);
+    assert_eq!(exit_code(&std::io::Error::other("disk")), EXIT_FAILURE);
+
+    // names clash whole and ignoring case, never by an ID prefix
+    assert!(ts.name_taken("rUN", None).unwrap());
+    assert!(!ts.name_taken("Run", Some(run.id)).unwrap());
+    assert!(!ts.name_taken(&run.id.to_string()[..4], None).unwrap());
+
+    // deleting a task takes its entries, pauses and references along
+    let day = NaiveDate::from_ymd_opt(2026, 3, 4).unwrap();
+    let pause = ts.put(Pause::new(Some(run.id), day, day, "sick")).unwrap();
+    let everything = ts.put(Pause::new(None, day, day, "holiday")).unwrap();
+    let routine = ts.put(Routine::new("Morning", "", vec![run.id, swim.id])).unwrap();
+    ts.put(TaskOrder { ids: vec![swim.id, run.id], ..TaskOrder::default() }).unwrap();
+    assert_eq!(ts.delete_task(&run).unwrap(), 2);
+    assert!(ts.find_entry(&entry.id.to_string()).is_err());
+    assert!(ts.get::<Pause>(pause.to_key()).unwrap().is_none());
+    assert!(ts.get::<Pause>(everything.to_key()).unwrap().is_some());
+    assert_eq!(ts.get::<Routine>(routine.to_key()).unwrap().unwrap().task_ids, vec![swim.id]);
+    assert_eq!(TaskOrder::load(&ts).unwrap().ids, vec![swim.id]);
+
+    assert_eq!("Weekly".parse::<EvalPeriod>(), Ok(EvalPeriod::Weekly));
+    assert_eq!("md".parse::<TaskTy>(), Ok(TaskTy::Markdown));
+    assert!("yearly".parse::<EvalPeriod>().is_err());
+}
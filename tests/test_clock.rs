@@ -0,0 +1,58 @@
+use chrono::{NaiveDate, TimeZone, Utc};
+use packrat::model::clock::{Clock, Zone};
+use packrat::model::task::EvalPeriod;
+
+fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+    NaiveDate::from_ymd_opt(y, m, d).unwrap()
+}
+
+#[test]
+fn test_local_day() {
+
+    let clock = Clock::from_settings(Some("America/Vancouver"), None).unwrap();
+    assert_eq!(clock.zone, Zone::Named(chrono_tz::America::Vancouver));
+
+    // 9pm in Vancouver is already the next day in UTC
+    let ts = Utc.with_ymd_and_hms(2026, 3, 3, 5, 0, 0).unwrap();
+    assert_eq!(clock.day_of(ts), date(2026, 3, 2));
+    assert_eq!(clock.format(ts, "%H:%M"), "21:00");
+}
+
+#[test]
+fn test_day_rollover() {
+
+    let clock = Clock::from_settings(Some("UTC"), Some(4)).unwrap();
+
+    let late = Utc.with_ymd_and_hms(2026, 3, 3, 3, 59, 0).unwrap();
+    let early = Utc.with_ymd_and_hms(2026, 3, 3, 4, 0, 0).unwrap();
+    assert_eq!(clock.day_of(late), date(2026, 3, 2));
+    assert_eq!(clock.day_of(early), date(2026, 3, 3));
+    assert_eq!(clock.start_of_day(date(2026, 3, 3)), early);
+
+    assert!(Clock::from_settings(Some("UTC"), Some(24)).is_err());
+    assert!(Clock::from_settings(Some("Mars/Olympus"), None).is_err());
+}
+
+#[test]
+fn test_period_bounds() {
+
+    // 2026-03-04 is a Wednesday
+    let day = date(2026, 3, 4);
+    assert_eq!(EvalPeriod::Daily.start_of(day), Some(day));
+    assert_eq!(EvalPeriod::Weekly.start_of(day), Some(date(2026, 3, 2)));
+    assert_eq!(EvalPeriod::Weekly.next_start(day), Some(date(2026, 3, 9)));
+    assert_eq!(EvalPeriod::Monthly.start_of(day), Some(date(2026, 3, 1)));
+    assert_eq!(EvalPeriod::Monthly.next_start(date(2026, 12, 31)), Some(date(2027, 1, 1)));
+    assert_eq!(EvalPeriod::Any.start_of(day), None);
+
+    // two week periods line up on Mondays and cover 14 days
+    let start = EvalPeriod::BiWeekly.start_of(day).unwrap();
+    assert_eq!(start.format("%a").to_string(), "Mon");
+    assert_eq!(EvalPeriod::BiWeekly.next_start(day).unwrap() - start, chrono::Duration::days(14));
+
+    let clock = Clock::from_settings(Some("America/Vancouver"), Some(4)).unwrap();
+    let ts = Utc.with_ymd_and_hms(2026, 3, 4, 20, 0, 0).unwrap();
+    let (from, to) = EvalPeriod::Weekly.bounds(&clock, ts).unwrap();
+    assert_eq!(from, Utc.with_ymd_and_hms(2026, 3, 2, 12, 0, 0).unwrap());
+    assert_eq!(to, Utc.with_ymd_and_hms(2026, 3, 9, 11, 0, 0).unwrap());
+}
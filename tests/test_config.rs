@@ -0,0 +1,97 @@
+use std::path::PathBuf;
+use crossterm::event::KeyCode;
+use packrat::cli::{exit_code, EXIT_INVALID};
+use packrat::config::{ColorName, Config, KeyView};
+use tempfile::TempDir;
+use tui::style::Color;
+
+#[test]
+fn test_config_parse() {
+
+    // nothing set is the defaults, as is a missing file where one is looked
+    // for, but not one asked for
+    assert_eq!(Config::parse("").unwrap(), Config::default());
+    let dir = TempDir::new().unwrap();
+    let missing = dir.path().join("config.toml");
+    assert_eq!(Config::load_or_default(&missing).unwrap(), Config::default());
+    let err = Config::load(&missing).unwrap_err();
+    assert_eq!(exit_code(&err), EXIT_INVALID);
+    assert!(err.to_string().starts_with(&format!("bad config {}", missing.display())));
+
+    let text = r##"
+        editor = "code --wait"
+        db_path = "/var/lib/packrat/db"
+        timestamp_format = "%d/%m %H:%M"
+        poll_interval_ms = 500
+        timezone = "Europe/Paris"
+        day_start = 4
+
+        [colors]
+        accent = "light-blue"
+        muted = "#808080"
+
+        [keys]
+        new = "c"
+        quit = "x"
+
+        [hooks]
+        on_entry_created = "notify-send packrat"
+        timeout_ms = 1000
+    "##;
+    let path = dir.path().join("config.toml");
+    std::fs::write(&path, text).unwrap();
+    let config = Config::load(&path).unwrap();
+    assert_eq!(config.editor.as_deref(), Some("code --wait"));
+    assert_eq!(config.db_path, PathBuf::from("/var/lib/packrat/db"));
+    assert_eq!(config.poll_interval().as_millis(), 500);
+    assert_eq!(config.clock().unwrap().day_start, 4);
+    assert_eq!(config.colors.accent, ColorName(Color::LightBlue));
+    assert_eq!(config.colors.muted, ColorName(Color::Rgb(128, 128, 128)));
+    assert_eq!(config.colors.good, ColorName(Color::Green));
+    assert_eq!(config.hooks.on_entry_created.as_deref(), Some("notify-send packrat"));
+    assert_eq!(config.hooks.milestones, vec![7, 30, 100, 365]);
+
+    // bound keys act as the built in ones, whose own keys then do nothing
+    let keys = &config.keys;
+    assert_eq!(keys.translate(KeyCode::Char('c'), KeyView::Tasks), KeyCode::Char('n'));
+    assert_eq!(keys.translate(KeyCode::Char('x'), KeyView::Stats), KeyCode::Char('q'));
+    assert_eq!(keys.translate(KeyCode::Char('n'), KeyView::Tasks), KeyCode::Null);
+    assert_eq!(keys.translate(KeyCode::Char('e'), KeyView::Tasks), KeyCode::Char('e'));
+    assert_eq!(keys.translate(KeyCode::Enter, KeyView::Tasks), KeyCode::Enter);
+
+    // tag and retime share 't' in different views, moving one leaves the other
+    let keys = Config::parse("[keys]\ntag = \"g\"").unwrap().keys;
+    assert_eq!(keys.translate(KeyCode::Char('g'), KeyView::Tasks), KeyCode::Char('t'));
+    assert_eq!(keys.translate(KeyCode::Char('t'), KeyView::Tasks), KeyCode::Null);
+    assert_eq!(keys.translate(KeyCode::Char('t'), KeyView::Task), KeyCode::Char('t'));
+    assert_eq!(keys.translate(KeyCode::Char('g'), KeyView::Task), KeyCode::Char('g'));
+}
+
+#[test]
+fn test_config_errors() {
+
+    let error = |text: &str| Config::parse(text).unwrap_err();
+
+    assert!(error("colour = 1").contains("unknown field `colour`"));
+    assert!(error("[keys]\nnwe = \"c\"").contains("unknown field `nwe`"));
+    assert!(error("[hooks]\non_entry_create = \"notify\"").contains("unknown field `on_entry_create`"));
+    assert!(error("poll_interval_ms = \"fast\"").contains("poll_interval_ms"));
+    assert!(error("poll_interval_ms = 0").contains("at least 1"));
+    assert!(error("timestamp_format = \"%Q\"").contains("not a valid date format"));
+    assert!(error("timezone = \"Mars/Olympus\"").contains("unknown timezone"));
+    assert!(error("day_start = 30").contains("between 0 and 23"));
+    assert!(error("[colors]\naccent = \"purplish\"").contains("unknown color 'purplish'"));
+    assert!(error("[keys]\nnew = \"ab\"").contains("new"));
+    assert_eq!(error("[keys]\nnew = \"q\""), "keys: 'q' is bound to both quit and new");
+
+    // actions of different views may share a key, the defaults do
+    assert!(Config::parse("[keys]\nrun = \"s\"\nselect = \"s\"").is_ok());
+    assert!(Config::parse("[keys]\nretime = \"a\"").is_ok());
+    assert_eq!(error("[keys]\nretime = \"S\""), "keys: 'S' is bound to both stats and retime");
+
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("config.toml");
+    std::fs::write(&path, "editor = \"\"").unwrap();
+    let message = Config::load(&path).unwrap_err().to_string();
+    assert!(message.starts_with(&format!("bad config {}: editor", path.display())));
+}
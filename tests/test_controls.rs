@@ -0,0 +1,27 @@
+use packrat::ui::control::UserAction;
+use packrat::ui::widgets::{control_height, control_widget};
+use tui::{buffer::Buffer, layout::Rect, widgets::Widget};
+
+#[test]
+fn test_controls_wrap() {
+
+    // the short lists fit on one line, the task list needs more
+    assert_eq!(control_height(&UserAction::routine_view(), 80), 3);
+    assert_eq!(control_height(&UserAction::stats_view(), 80), 3);
+    let height = control_height(&UserAction::main_view(), 80);
+    assert!(height > 3);
+    assert_eq!(control_height(&UserAction::main_view(), 1000), 3);
+
+    // every action shows whole on one of the lines
+    let area = Rect::new(0, 0, 80, height);
+    let mut buf = Buffer::empty(area);
+    control_widget(UserAction::main_view()).render(area, &mut buf);
+    let lines: Vec<String> = (1..height - 1)
+        .map(|y| (0..area.width).map(|x| buf.get(x, y).symbol.as_str()).collect::<String>().replace('\u{a0}', " "))
+        .collect();
+    assert!(lines.last().unwrap().trim().len() > 1, "{:?}", lines);
+    for action in UserAction::main_view() {
+        let label = action.to_string();
+        assert!(lines.iter().any(|l| l.contains(&label)), "{} in {:?}", label, lines);
+    }
+}
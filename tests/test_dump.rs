@@ -0,0 +1,74 @@
+use chrono::{NaiveDate, TimeZone, Utc};
+use packrat::model::dump::{Dump, DUMP_VERSION};
+use packrat::model::pause::Pause;
+use packrat::model::routine::Routine;
+use packrat::model::store::TaskStore;
+use packrat::model::task::{EvalPeriod, Task, TaskFilter, TaskTy};
+use packrat::model::task_entry::TaskEntry;
+use tempfile::TempDir;
+
+#[test]
+fn test_json_round_trip() {
+
+    let dir = TempDir::new().unwrap();
+    let ts = TaskStore::new(dir.path().join("test.db").to_str().unwrap());
+
+    let mut run = Task::new("Run", "Kilometres");
+    run.task_type = TaskTy::Numeric;
+    run.eval_period = EvalPeriod::Weekly;
+    run.target = 3;
+    run.tags = vec![String::from("health")];
+    run.pinned = true;
+    let run = ts.put(run).unwrap();
+    let read = ts.put(Task::new("Read", "A chapter")).unwrap();
+
+    let at = Utc.with_ymd_and_hms(2026, 3, 1, 7, 30, 0).unwrap();
+    let entry = ts.put(TaskEntry::new_at(run.id, "5.2", at)).unwrap();
+    ts.put(TaskEntry::new(read.id, "Dune, \"ch. 3\"\nnotes")).unwrap();
+    let day = NaiveDate::from_ymd_opt(2026, 3, 2).unwrap();
+    ts.put(Pause::new(Some(run.id), day, day, "rest")).unwrap();
+    ts.put(Routine::new("Morning", "", vec![run.id, read.id])).unwrap();
+
+    let mut out = vec![];
+    ts.write_json(&mut out, &TaskFilter::default()).unwrap();
+
+    // entry content is readable text, not a byte array
+    let json: serde_json::Value = serde_json::from_slice(&out).unwrap();
+    assert_eq!(json["version"], DUMP_VERSION);
+    let tasks = json["tasks"].as_array().unwrap();
+    let json_run = tasks.iter().find(|t| t["name"] == "Run").unwrap();
+    assert_eq!(json_run["entries"][0]["content"], "5.2");
+
+    // and everything comes back as it went in
+    let dump: Dump = serde_json::from_slice(&out).unwrap();
+    assert_eq!(dump.tasks.len(), 2);
+    assert_eq!(dump.pauses.len(), 1);
+    assert_eq!(dump.routines.len(), 1);
+    let back = dump.tasks.iter().find(|t| t.task.id == run.id).unwrap();
+    assert_eq!(back.task, run);
+    assert_eq!(back.entries[0].to_entry(run.id), entry);
+}
+
+#[test]
+fn test_json_filter() {
+
+    let dir = TempDir::new().unwrap();
+    let ts = TaskStore::new(dir.path().join("test.db").to_str().unwrap());
+
+    let mut run = Task::new("Run", "");
+    run.tags = vec![String::from("health")];
+    let run = ts.put(run).unwrap();
+    let read = ts.put(Task::new("Read", "")).unwrap();
+    ts.put(Pause::new(Some(read.id), NaiveDate::MIN, NaiveDate::MAX, "")).unwrap();
+    ts.put(Pause::new(None, NaiveDate::MIN, NaiveDate::MAX, "holiday")).unwrap();
+    ts.put(Routine::new("Evening", "", vec![read.id])).unwrap();
+
+    let filter = TaskFilter { tag: Some(String::from("health")), ..TaskFilter::default() };
+    let dump = Dump::collect(&ts, &filter).unwrap();
+    assert_eq!(dump.tasks.len(), 1);
+    assert_eq!(dump.tasks[0].task.id, run.id);
+    // only the global pause involves the run, and no routine does
+    assert_eq!(dump.pauses.len(), 1);
+    assert_eq!(dump.pauses[0].task_id, None);
+    assert!(dump.routines.is_empty());
+}
@@ -0,0 +1,46 @@
+use packrat::ui::editor::{editor_command, open_editor, split_command, EditorError};
+
+#[test]
+fn test_split_command() {
+    assert_eq!(split_command("nvim"), vec!["nvim"]);
+    assert_eq!(split_command("  code --wait  -n "), vec!["code", "--wait", "-n"]);
+    assert_eq!(
+        split_command(r#""/opt/My Editor/bin/edit" --title 'a b' x"y"z"#),
+        vec!["/opt/My Editor/bin/edit", "--title", "a b", "xyz"]
+    );
+    assert_eq!(split_command("edit ''"), vec!["edit", ""]);
+    assert!(split_command("   ").is_empty());
+}
+
+/// One test, as it changes the environment
+#[test]
+fn test_editor_resolution() {
+
+    // $VISUAL before $EDITOR, arguments included
+    std::env::set_var("VISUAL", "emacs -nw");
+    std::env::set_var("EDITOR", "nano");
+    assert_eq!(editor_command().unwrap(), vec!["emacs", "-nw"]);
+    std::env::set_var("VISUAL", "");
+    assert_eq!(editor_command().unwrap(), vec!["nano"]);
+
+    // the file is appended to the arguments, a clean exit keeps the edit
+    std::env::set_var("EDITOR", "sh -c 'printf edited > \"$0\"'");
+    assert_eq!(open_editor(b"original").unwrap(), "edited");
+
+    // a failing editor or a missing one is an error, not a panic
+    std::env::set_var("EDITOR", "sh -c 'exit 3'");
+    let err = open_editor(b"original").unwrap_err();
+    assert_eq!(err.to_string(), "'sh' exited with status 3, nothing was saved");
+    std::env::set_var("EDITOR", "no-such-editor-here --flag");
+    assert!(matches!(open_editor(b"original"), Err(EditorError::NotFound(p)) if p == "no-such-editor-here"));
+
+    // nothing set and nothing on the PATH
+    std::env::remove_var("VISUAL");
+    std::env::remove_var("EDITOR");
+    let path = std::env::var_os("PATH");
+    std::env::set_var("PATH", "");
+    assert!(matches!(editor_command(), Err(EditorError::NoEditor)));
+    if let Some(path) = path {
+        std::env::set_var("PATH", path);
+    }
+}
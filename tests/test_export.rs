@@ -0,0 +1,173 @@
+use std::io::Cursor;
+use std::collections::{BTreeMap, BTreeSet};
+use chrono::{NaiveDate, TimeZone, Utc};
+use packrat::model::clock::Clock;
+use packrat::model::ics::{escape, write_ics, IcsKind};
+use packrat::model::import::{apply, native, MergePolicy};
+use packrat::model::journal::{write_journal, JournalGroup};
+use packrat::model::report::{escape as html_escape, heatmap_svg, write_report};
+use packrat::model::query::{Clause, Query};
+use packrat::model::store::{CsvLayout, TaskStore};
+use packrat::model::task::{EvalPeriod, Task, TaskFilter, TaskTy};
+use packrat::model::task_entry::TaskEntry;
+use tempfile::TempDir;
+
+#[test]
+fn test_long_csv() {
+
+    utc_clock();
+    let dir = TempDir::new().unwrap();
+    let ts = TaskStore::new(dir.path().join("test.db").to_str().unwrap());
+
+    let mut run = Task::new("Run", "");
+    run.task_type = TaskTy::Numeric;
+    let run = ts.put(run).unwrap();
+    let mut sleep = Task::new("Sleep", "");
+    sleep.task_type = TaskTy::Json;
+    let sleep = ts.put(sleep).unwrap();
+
+    let at = |d: u32| Utc.with_ymd_and_hms(2026, 3, d, 7, 0, 0).unwrap();
+    let km = ts.put(TaskEntry::new_at(run.id, "5.5", at(2))).unwrap();
+    ts.put(TaskEntry::new_at(run.id, "3", at(9))).unwrap();
+    ts.put(TaskEntry::new_at(sleep.id, r#"{"hours": 7.5, "value": "ok", "note": "a\nb"}"#, at(2))).unwrap();
+
+    let export = |filter: &TaskFilter| {
+        let mut out = vec![];
+        ts.write_csv(&mut out, filter, CsvLayout::Long).unwrap();
+        String::from_utf8(out).unwrap()
+    };
+
+    // structured fields get columns, one that clashes gets a prefix
+    let csv = export(&TaskFilter::default());
+    let header = csv.lines().next().unwrap();
+    assert_eq!(header, "task_id,task_name,entry_id,timestamp,day,content,value,hours,note,data.value");
+    let row = format!("{},Run,{},2026-03-02T07:00:00+00:00,2026-03-02,5.5,5.5", run.id, km.id);
+    assert!(csv.contains(&format!("{},,,\n", row)));
+    assert!(csv.contains(",7.5,\"a\nb\",ok\n"));
+
+    // date and task filters
+    let query = Query {
+        clauses: vec![
+            Clause::Id(vec![run.id.to_string()]),
+            Clause::Before(NaiveDate::from_ymd_opt(2026, 3, 5).unwrap()),
+        ],
+    };
+    let csv = export(&TaskFilter { query, ..TaskFilter::default() });
+    assert_eq!(csv.lines().count(), 2);
+    assert!(csv.contains(&format!("{}\n", row)));
+
+    // the long layout imports with its IDs and times
+    let copy = TaskStore::new(dir.path().join("copy.db").to_str().unwrap());
+    let dump = native::read_csv(&copy, Cursor::new(export(&TaskFilter::default()))).unwrap();
+    assert_eq!(apply(&copy, &dump, MergePolicy::Skip, false).unwrap().entries.added, 3);
+    assert_eq!(copy.find_entry(&km.id.to_string()).unwrap(), km);
+    assert_eq!(copy.find_task(&run.id.to_string()).unwrap().name, "Run");
+}
+
+#[test]
+fn test_ics() {
+
+    utc_clock();
+    let dir = TempDir::new().unwrap();
+    let ts = TaskStore::new(dir.path().join("test.db").to_str().unwrap());
+
+    let mut gym = Task::new("Gym", "Lift, then stretch");
+    gym.eval_period = EvalPeriod::BiWeekly;
+    gym.target = 2;
+    gym.tags = vec![String::from("health")];
+    gym.timestamp = Utc.with_ymd_and_hms(2026, 3, 4, 9, 0, 0).unwrap();
+    let gym = ts.put(gym).unwrap();
+    let long = "x".repeat(100);
+    let entry = ts.put(TaskEntry::new_at(gym.id, long.as_str(), Utc.with_ymd_and_hms(2026, 3, 5, 18, 30, 0).unwrap())).unwrap();
+
+    let export = |kind, recurrence| {
+        let mut out = vec![];
+        write_ics(&ts, &mut out, &TaskFilter::default(), kind, recurrence).unwrap();
+        String::from_utf8(out).unwrap()
+    };
+
+    let ics = export(IcsKind::Event, false);
+    assert!(ics.starts_with("BEGIN:VCALENDAR\r\nVERSION:2.0\r\n"));
+    assert!(ics.ends_with("END:VCALENDAR\r\n"));
+    assert!(ics.contains(&format!("UID:{}@packrat\r\n", entry.id)));
+    assert!(ics.contains("DTSTART:20260305T183000Z\r\n"));
+    assert!(ics.contains("CATEGORIES:health\r\n"));
+    assert!(!ics.contains("RRULE"));
+    // long lines are folded and unfold to the original
+    assert!(ics.lines().all(|l| l.len() <= 75));
+    assert!(ics.replace("\r\n ", "").contains(&format!("DESCRIPTION:{}\r\n", long)));
+
+    let ics = export(IcsKind::Todo, true);
+    assert!(ics.contains("BEGIN:VTODO\r\nUID:"));
+    assert!(ics.contains("COMPLETED:20260305T183000Z\r\n"));
+    // fortnights are counted from a Monday
+    assert!(ics.contains("DTSTART;VALUE=DATE:20260302\r\nRRULE:FREQ=WEEKLY;INTERVAL=2\r\n"));
+    assert!(ics.contains("DESCRIPTION:Lift\\, then stretch\\n2 per fortnight\r\n"));
+
+    assert_eq!(escape("a;b\\c\nd"), "a\\;b\\\\c\\nd");
+}
+
+#[test]
+fn test_journal_and_report() {
+
+    utc_clock();
+    let dir = TempDir::new().unwrap();
+    let ts = TaskStore::new(dir.path().join("test.db").to_str().unwrap());
+
+    let mut diary = Task::new("Diary", "Evening notes");
+    diary.task_type = TaskTy::Markdown;
+    let diary = ts.put(diary).unwrap();
+    let mut sleep = Task::new("Sleep <b>", "");
+    sleep.task_type = TaskTy::Toml;
+    let sleep = ts.put(sleep).unwrap();
+
+    let at = |d: u32, h: u32| Utc.with_ymd_and_hms(2026, 3, d, h, 0, 0).unwrap();
+    ts.put(TaskEntry::new_at(diary.id, "# Rain\nStayed in.", at(4, 21))).unwrap();
+    ts.put(TaskEntry::new_at(sleep.id, "hours = 7", at(4, 7))).unwrap();
+    ts.put(TaskEntry::new_at(diary.id, "Sunny", at(5, 21))).unwrap();
+
+    let journal = |group| {
+        let mut out = vec![];
+        write_journal(&ts, &mut out, &TaskFilter::default(), group).unwrap();
+        String::from_utf8(out).unwrap()
+    };
+
+    let by_day = journal(JournalGroup::Day);
+    let expected = "# Journal\n\n## Wednesday, 4 March 2026\n\n### 07:00 · Sleep <b>\n\n```toml\nhours = 7\n```\n\n\
+                    ### 21:00 · Diary\n\n#### Rain\nStayed in.\n\n## Thursday, 5 March 2026\n\n### 21:00 · Diary\n\nSunny\n";
+    assert_eq!(by_day, expected);
+    let by_task = journal(JournalGroup::Task);
+    assert!(by_task.contains("## Diary\n\n_Evening notes_\n\n### 2026-03-04 21:00\n"));
+    assert!(by_task.find("2026-03-04 21:00").unwrap() < by_task.find("2026-03-05 21:00").unwrap());
+
+    let mut out = vec![];
+    write_report(&ts, &mut out, &TaskFilter::default()).unwrap();
+    let html = String::from_utf8(out).unwrap();
+    assert!(html.starts_with("<!DOCTYPE html>"));
+    assert!(html.contains("<h2>Sleep &lt;b&gt;</h2>"));
+    assert!(html.contains("<tr><td>Entries</td><td>2</td></tr>"));
+
+    // a date filter picks the tasks, their stats still cover every entry
+    let recent = Query { clauses: vec![Clause::After(NaiveDate::from_ymd_opt(2026, 3, 5).unwrap())] };
+    let mut out = vec![];
+    write_report(&ts, &mut out, &TaskFilter { query: recent, ..TaskFilter::default() }).unwrap();
+    let recent = String::from_utf8(out).unwrap();
+    assert!(!recent.contains("Sleep"));
+    assert!(recent.contains("<tr><td>Entries</td><td>2</td></tr>"));
+    // nothing is fetched from elsewhere
+    assert!(!html.contains("http"));
+    assert_eq!(html_escape("\"a&b\""), "&quot;a&amp;b&quot;");
+
+    // a year of weeks, the busiest day in the brightest shade
+    let end = NaiveDate::from_ymd_opt(2026, 3, 4).unwrap();
+    let days = BTreeMap::from([(end, 2.0), (end.pred_opt().unwrap(), 1.0)]);
+    let svg = heatmap_svg(&days, &BTreeSet::from([end.pred_opt().unwrap().pred_opt().unwrap()]), end);
+    assert_eq!(svg.matches("<rect").count(), 52 * 7 + 3);
+    assert!(svg.contains("fill=\"#39d353\"><title>2026-03-04: 2</title>"));
+    assert!(svg.contains("fill=\"#1f6feb\"><title>2026-03-02: 0</title>"));
+}
+
+/// Days and times come out in UTC whatever the machine's zone
+fn utc_clock() {
+    Clock::init(Clock::from_settings(Some("UTC"), None).unwrap());
+}
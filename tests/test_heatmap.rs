@@ -0,0 +1,49 @@
+use std::collections::BTreeMap;
+use chrono::NaiveDate;
+use packrat::ui::widgets::heatmap::Heatmap;
+use tui::{buffer::Buffer, layout::Rect, widgets::Widget};
+
+fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+    NaiveDate::from_ymd_opt(y, m, d).unwrap()
+}
+
+#[test]
+fn test_heatmap_levels() {
+    assert_eq!(Heatmap::level(0.0, 10.0), 0);
+    assert_eq!(Heatmap::level(0.1, 10.0), 1);
+    assert_eq!(Heatmap::level(5.0, 10.0), 2);
+    assert_eq!(Heatmap::level(10.0, 10.0), 4);
+}
+
+#[test]
+fn test_heatmap_render() {
+
+    // Wednesday, so the last column has three days drawn
+    let end = date(2026, 3, 4);
+    let mut days = BTreeMap::new();
+    days.insert(end, 3.0);
+    days.insert(date(2026, 2, 23), 1.0);
+
+    // room for the labels and 4 weeks
+    let area = Rect::new(0, 0, 4 + 9, Heatmap::height());
+    assert_eq!(Heatmap::weeks_for(area.width), 4);
+    assert_eq!(Heatmap::weeks_for(500), 53);
+
+    let mut buf = Buffer::empty(area);
+    Heatmap::new(days, end).render(area, &mut buf);
+
+    let row = |y: u16| -> String {
+        (0..area.width).map(|x| buf.get(x, y).symbol.clone()).collect()
+    };
+
+    // first column is the week of Feb 9, March starts in the last one
+    assert_eq!(row(0), "    Feb   Mar");
+    assert!(row(1).starts_with("Mon ■ ■ ■ ■"));
+    assert!(row(3).starts_with("Wed ■ ■ ■ ■"));
+    assert_eq!(row(4).trim_end(), "    ■ ■ ■");
+
+    // the busiest day gets the brightest shade
+    let shade = |x, y| buf.get(x, y).fg;
+    assert_ne!(shade(10, 3), shade(4, 3));
+    assert_ne!(shade(10, 1), shade(8, 1));
+}
@@ -0,0 +1,114 @@
+use std::fs;
+use std::time::{Duration, Instant};
+use chrono::Utc;
+use serde_json::{json, Value};
+use packrat::model::clock::Clock;
+use packrat::model::hooks::{run_hook, wait_for_hooks, Hooks};
+use packrat::model::store::TaskStore;
+use packrat::model::task::Task;
+use packrat::model::task_entry::TaskEntry;
+use tempfile::TempDir;
+
+#[test]
+fn test_hooks_fire() {
+
+    Clock::init(Clock::from_settings(Some("UTC"), None).unwrap());
+    let dir = TempDir::new().unwrap();
+    let out = |name: &str| dir.path().join(name);
+    let log = |name: &str| format!("cat >> '{}'; echo >> '{}'", out(name).display(), out(name).display());
+    let hooks = Hooks {
+        on_task_created: Some(log("tasks")),
+        on_entry_created: Some(log("created")),
+        on_entry_updated: Some(log("updated")),
+        on_delete: Some(format!("echo \"$PACKRAT_EVENT $PACKRAT_KIND\" >> '{}'", out("deleted").display())),
+        on_streak_milestone: Some(log("milestones")),
+        milestones: vec![2, 30],
+        ..Hooks::default()
+    };
+    let ts = TaskStore::new(out("test.db").to_str().unwrap()).with_hooks(hooks);
+
+    // hooks run in the background, in the order they fired
+    let read = |name: &str| -> Vec<Value> {
+        assert!(wait_for_hooks().is_empty());
+        fs::read_to_string(out(name)).unwrap_or_default()
+            .lines()
+            .map(|l| serde_json::from_str(l).unwrap())
+            .collect()
+    };
+
+    // creating fires once, updating the same task doesn't
+    let task = ts.put(Task::new("Run", "")).unwrap();
+    ts.put(task.clone()).unwrap();
+    assert_eq!(read("tasks").len(), 1);
+    assert_eq!(read("tasks")[0]["name"], "Run");
+
+    // the second day in a row reaches the first milestone
+    let yesterday = ts.put(TaskEntry::new_at(task.id, "5", Utc::now() - chrono::Duration::days(1))).unwrap();
+    assert!(read("milestones").is_empty());
+    let mut today = ts.put(TaskEntry::new(task.id, "3")).unwrap();
+    let created = read("created");
+    assert_eq!(created.len(), 2);
+    assert_eq!(created[1]["content"], "3");
+    assert_eq!(created[1]["task_id"], json!(task.id));
+    let milestones = read("milestones");
+    assert_eq!(milestones.len(), 1);
+    assert_eq!((milestones[0]["milestone"].as_u64(), milestones[0]["streak"].as_u64()), (Some(2), Some(2)));
+
+    today.content = b"4".to_vec();
+    ts.put(today).unwrap();
+    assert_eq!(read("updated")[0]["content"], "4");
+    assert_eq!(read("created").len(), 2);
+    assert_eq!(read("milestones").len(), 1);
+
+    ts.delete_item(&yesterday).unwrap();
+    ts.delete_task(&task).unwrap();
+    wait_for_hooks();
+    let deleted = fs::read_to_string(out("deleted")).unwrap();
+    assert_eq!(deleted, "on_delete entry\non_delete entry\non_delete task\n");
+
+    // a store without hooks leaves no trace
+    let quiet = TaskStore::new(out("quiet.db").to_str().unwrap());
+    quiet.put(Task::new("Read", "")).unwrap();
+    assert_eq!(read("tasks").len(), 1);
+
+    // a slow hook doesn't hold up the write, its failure is kept for later
+    let slow = Hooks { on_task_created: Some(String::from("sleep 1; exit 3")), ..Hooks::default() };
+    let slow = TaskStore::new(out("slow.db").to_str().unwrap()).with_hooks(slow);
+    let started = Instant::now();
+    slow.put(Task::new("Swim", "")).unwrap();
+    assert!(started.elapsed() < Duration::from_millis(500));
+    let failures = wait_for_hooks();
+    assert_eq!(failures.len(), 1);
+    assert!(failures[0].starts_with("hook on_task_created failed"), "{}", failures[0]);
+}
+
+#[test]
+fn test_hook_failures() {
+
+    let record = json!({"name": "Run"});
+
+    // the record arrives on stdin and the environment is set
+    let dir = TempDir::new().unwrap();
+    let file = dir.path().join("out");
+    let command = format!("cat > '{}'; echo \" $PACKRAT_EVENT\" >> '{}'", file.display(), file.display());
+    let env = [("PACKRAT_EVENT", String::from("on_task_created"))];
+    run_hook(&command, &record, &env, Duration::from_secs(5)).unwrap();
+    assert_eq!(fs::read_to_string(&file).unwrap(), "{\"name\":\"Run\"} on_task_created\n");
+
+    // failures come back with their stderr
+    let err = run_hook("echo oops >&2; exit 2", &record, &[], Duration::from_secs(5)).unwrap_err();
+    assert!(err.contains("exit status: 2") && err.ends_with("oops"), "{}", err);
+
+    // however much it writes to stderr
+    let started = Instant::now();
+    let err = run_hook("head -c 200000 /dev/zero | tr '\\0' x >&2; exit 1", &record, &[], Duration::from_secs(5)).unwrap_err();
+    assert!(err.contains("exit status: 1") && err.len() > 200000, "{}", &err[..100]);
+    assert!(started.elapsed() < Duration::from_secs(3));
+
+    // slow hooks are killed, and one that never reads its input doesn't block
+    let started = Instant::now();
+    let big = json!({"content": "x".repeat(1 << 20)});
+    let err = run_hook("sleep 5", &big, &[], Duration::from_millis(200)).unwrap_err();
+    assert!(err.contains("timed out after 200 ms"));
+    assert!(started.elapsed() < Duration::from_secs(3));
+}
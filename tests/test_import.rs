@@ -0,0 +1,187 @@
+use std::io::Cursor;
+use chrono::{Duration, NaiveDate, TimeZone, Utc};
+use packrat::model::clock::Clock;
+use packrat::model::dump::Dump;
+use packrat::model::import::{apply, generic, loop_habit, native, todotxt, ColumnMap, Counts, MergePolicy};
+use packrat::model::routine::Routine;
+use packrat::model::search::search;
+use packrat::model::store::TaskStore;
+use packrat::model::task::{EvalPeriod, Task, TaskFilter, TaskTy};
+use packrat::model::task_entry::TaskEntry;
+use tempfile::TempDir;
+
+#[test]
+fn test_import_json() {
+
+    let dir = TempDir::new().unwrap();
+    let from = TaskStore::new(dir.path().join("from.db").to_str().unwrap());
+    let to = TaskStore::new(dir.path().join("to.db").to_str().unwrap());
+
+    let run = from.put(Task::new("Run", "5k")).unwrap();
+    let entry = from.put(TaskEntry::new(run.id, "felt good")).unwrap();
+    from.put(TaskEntry::new(run.id, "rain")).unwrap();
+    from.put(Routine::new("Morning", "", vec![run.id])).unwrap();
+
+    let mut out = vec![];
+    from.write_json(&mut out, &TaskFilter::default()).unwrap();
+    let dump = native::read_json(Cursor::new(&out)).unwrap();
+
+    // a dry run only counts
+    let summary = apply(&to, &dump, MergePolicy::Skip, true).unwrap();
+    assert_eq!(summary.entries, Counts { added: 2, updated: 0, skipped: 0 });
+    assert!(to.get_prefix::<Task>(Task::key_all()).unwrap().is_empty());
+
+    let summary = apply(&to, &dump, MergePolicy::Skip, false).unwrap();
+    assert_eq!(summary.tasks.added, 1);
+    assert_eq!(summary.routines.added, 1);
+    assert_eq!(to.get_prefix::<TaskEntry>(TaskEntry::key_task(run.id)).unwrap().len(), 2);
+    assert_eq!(native::read_json(Cursor::new(&out)).unwrap(), dump);
+
+    // the imported entries are searchable
+    let hits = search_ids(&to, "felt");
+    assert_eq!(hits, vec![entry.id]);
+
+    // the same file again changes nothing
+    let summary = apply(&to, &dump, MergePolicy::Overwrite, false).unwrap();
+    assert_eq!(summary.changes(), 0);
+    assert_eq!(summary.entries.skipped, 2);
+
+    // an edit made in the store is kept or replaced depending on the policy
+    let mut edited = entry.clone();
+    edited.content = b"felt great".to_vec();
+    edited.timestamp = entry.timestamp + Duration::minutes(5);
+    to.put(edited.clone()).unwrap();
+    assert_eq!(apply(&to, &dump, MergePolicy::Skip, false).unwrap().changes(), 0);
+    assert_eq!(apply(&to, &dump, MergePolicy::Newest, false).unwrap().changes(), 0);
+    assert_eq!(to.find_entry(&entry.id.to_string()).unwrap(), edited);
+    let summary = apply(&to, &dump, MergePolicy::Overwrite, false).unwrap();
+    assert_eq!(summary.entries, Counts { added: 0, updated: 1, skipped: 1 });
+    assert_eq!(to.find_entry(&entry.id.to_string()).unwrap(), entry);
+    assert!(search_ids(&to, "great").is_empty());
+
+    // a task edited since the last import wins under newest, though it keeps
+    // its ID and creation time
+    let mut longer = run.clone();
+    longer.desc = String::from("10k");
+    from.put(longer).unwrap();
+    let mut out = vec![];
+    from.write_json(&mut out, &TaskFilter::default()).unwrap();
+    let dump = native::read_json(Cursor::new(&out)).unwrap();
+    let summary = apply(&to, &dump, MergePolicy::Newest, false).unwrap();
+    assert_eq!((summary.tasks.updated, summary.entries.updated), (1, 0));
+    assert_eq!(to.find_task("Run").unwrap().desc, "10k");
+
+    // archived tasks make it across too
+    let mut swim = Task::new("Swim", "");
+    swim.archived = true;
+    let swim = from.put(swim).unwrap();
+    from.put(TaskEntry::new(swim.id, "laps")).unwrap();
+    let mut out = vec![];
+    from.write_json(&mut out, &TaskFilter::default()).unwrap();
+    let dump = native::read_json(Cursor::new(&out)).unwrap();
+    let summary = apply(&to, &dump, MergePolicy::Skip, false).unwrap();
+    assert_eq!((summary.tasks.added, summary.entries.added), (1, 1));
+    assert!(to.find_task("Swim").unwrap().archived);
+}
+
+#[test]
+fn test_import_errors_and_csv() {
+
+    let dir = TempDir::new().unwrap();
+    let ts = TaskStore::new(dir.path().join("test.db").to_str().unwrap());
+    let run = ts.put(Task::new("Run", "")).unwrap();
+
+    assert!(native::read_json(Cursor::new("{\"tasks\": 3}")).is_err());
+    let future = r#"{"version": 99, "exported": "2026-01-01T00:00:00Z", "tasks": []}"#;
+    let err = native::read_json(Cursor::new(future)).unwrap_err();
+    assert!(err.to_string().contains("version 99"));
+    assert!(native::read_csv(&ts, Cursor::new("run,RUN\n1,2\n")).is_err());
+
+    // columns go to the task of the same name or a new one
+    let dump: Dump = native::read_csv(&ts, Cursor::new("run,Swim\n5,\"1km, slow\"\n3,\n")).unwrap();
+    let summary = apply(&ts, &dump, MergePolicy::Skip, false).unwrap();
+    assert_eq!(summary.tasks, Counts { added: 1, updated: 0, skipped: 1 });
+    assert_eq!(summary.entries.added, 3);
+
+    let entries: Vec<TaskEntry> = ts.get_prefix(TaskEntry::key_task(run.id)).unwrap();
+    let contents: Vec<String> = entries.iter().map(|e| e.get_content()).collect();
+    assert_eq!(contents, vec!["5", "3"]);
+    let swim = ts.find_task("swim").unwrap();
+    let entries: Vec<TaskEntry> = ts.get_prefix(TaskEntry::key_task(swim.id)).unwrap();
+    assert_eq!(entries[0].get_content(), "1km, slow");
+
+    // the same file again adds nothing, whatever the policy
+    for policy in [MergePolicy::Skip, MergePolicy::Overwrite] {
+        let dump: Dump = native::read_csv(&ts, Cursor::new("run,Swim\n5,\"1km, slow\"\n3,\n")).unwrap();
+        let summary = apply(&ts, &dump, policy, false).unwrap();
+        assert_eq!(summary.entries, Counts { added: 0, updated: 0, skipped: 3 });
+    }
+    assert_eq!(ts.get_prefix::<TaskEntry>(TaskEntry::key_all()).unwrap().len(), 3);
+}
+
+#[test]
+fn test_import_other_trackers() {
+
+    let dir = TempDir::new().unwrap();
+    let ts = TaskStore::new(dir.path().join("test.db").to_str().unwrap());
+    let clock = Clock::from_settings(Some("UTC"), None).unwrap();
+    let noon = |d: u32| Utc.with_ymd_and_hms(2026, 3, d, 12, 0, 0).unwrap();
+
+    // Loop: 3 times a week, a numeric habit in thousandths, 1 is only implied
+    let habits = "Position,Name,Type,Question,Description,FrequencyNumerator,FrequencyDenominator,Color,Unit,Archived?\n\
+                  001,Gym,0,Did you go?,,3,7,#fff,,false\n\
+                  002,Water,1,,Glasses,1,1,#fff,glasses,false\n";
+    let checkmarks = "Date,Gym,Water,\n2026-03-02,2,8000,\n2026-03-01,1,-1,\n";
+    let dump = loop_habit::read(&ts, Some(Cursor::new(habits)), Cursor::new(checkmarks), &clock).unwrap();
+    let gym = &dump.tasks[0];
+    assert_eq!((gym.task.desc.as_str(), &gym.task.eval_period, gym.task.target), ("Did you go?", &EvalPeriod::Weekly, 3));
+    assert_eq!(gym.entries.len(), 1);
+    assert_eq!(gym.entries[0].timestamp, noon(2));
+    let water = &dump.tasks[1];
+    assert_eq!(water.task.task_type, TaskTy::Numeric);
+    assert_eq!(water.entries[0].content, "8");
+    assert_eq!(loop_habit::period_of(1, 30), (EvalPeriod::Monthly, 1));
+
+    // importing twice finds the same tasks and entries
+    assert_eq!(apply(&ts, &dump, MergePolicy::Skip, false).unwrap().changes(), 4);
+    let again = loop_habit::read(&ts, Some(Cursor::new(habits)), Cursor::new(checkmarks), &clock).unwrap();
+    assert_eq!(apply(&ts, &again, MergePolicy::Skip, false).unwrap().changes(), 0);
+
+    // todo.txt
+    let line = todotxt::parse_line("x 2026-03-03 2026-03-01 Stretch +health @home rec:1w due:2026-03-08").unwrap();
+    assert_eq!(line.name, "Stretch");
+    assert_eq!(line.done, NaiveDate::from_ymd_opt(2026, 3, 3));
+    assert_eq!(line.tags, vec!["health", "home"]);
+    assert_eq!(line.period, Some(EvalPeriod::Weekly));
+    assert_eq!(todotxt::parse_line("(A) 2026-03-01 Read https://example.com").unwrap().name, "Read https://example.com");
+    assert_eq!(todotxt::parse_line("  +tag "), None);
+    let todo = "x 2026-03-01 Gym\nx 2026-03-03 Stretch +health\nStretch +health\n";
+    let dump = todotxt::read(&ts, Cursor::new(todo), &clock).unwrap();
+    assert_eq!(dump.tasks[0].task.id, gym.task.id);
+    assert_eq!(dump.tasks[1].entries[0].timestamp, noon(3));
+
+    // a long sheet with a column map and a wide one with ticks
+    let long = "When,Habit,Amount,Freq\n01/03/2026,Run,5.2,weekly\n02/03/2026,Run,3,\n";
+    let map = ColumnMap { date_format: Some(String::from("%d/%m/%Y")), ..ColumnMap::default() };
+    assert!(generic::read(&ts, Cursor::new(long), &map, &clock).is_err());
+    let map = ColumnMap { date: Some(String::from("When")), task: Some(String::from("Habit")), ..map };
+    let map = ColumnMap { content: Some(String::from("Amount")), period: Some(String::from("Freq")), ..map };
+    let parsed: ColumnMap = "date=When, task=Habit,content=Amount,period=Freq".parse().unwrap();
+    assert_eq!(ColumnMap { date_format: map.date_format.clone(), ..parsed }, map);
+    let dump = generic::read(&ts, Cursor::new(long), &map, &clock).unwrap();
+    assert_eq!(dump.tasks[0].task.eval_period, EvalPeriod::Weekly);
+    let contents: Vec<&str> = dump.tasks[0].entries.iter().map(|e| e.content.as_str()).collect();
+    assert_eq!(contents, vec!["5.2", "3"]);
+
+    let wide = "date,time,Gym,Mood\n2026-03-02,07:30,x,good\n2026-03-03,,no,\n";
+    let dump = generic::read(&ts, Cursor::new(wide), &ColumnMap::default(), &clock).unwrap();
+    assert_eq!(dump.tasks[0].entries.len(), 1);
+    assert_eq!(dump.tasks[0].entries[0].content, "");
+    assert_eq!(dump.tasks[0].entries[0].timestamp, Utc.with_ymd_and_hms(2026, 3, 2, 7, 30, 0).unwrap());
+    assert_eq!(dump.tasks[1].entries[0].content, "good");
+    assert!("task".parse::<ColumnMap>().is_err());
+}
+
+fn search_ids(store: &TaskStore, query: &str) -> Vec<uuid::Uuid> {
+    search(store, query).unwrap().into_iter().map(|hit| hit.entry.id).collect()
+}
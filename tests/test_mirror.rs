@@ -0,0 +1,175 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use chrono::{TimeZone, Utc};
+use packrat::model::clock::Clock;
+use packrat::model::convert::Storable;
+use packrat::model::mirror::{ConflictPolicy, Mirror, MANIFEST, TASK_FILE};
+use packrat::model::routine::Routine;
+use packrat::model::store::TaskStore;
+use packrat::model::task::Task;
+use packrat::model::task_entry::TaskEntry;
+use tempfile::TempDir;
+
+/// Every file in the mirror but the manifest, relative to it
+fn files(dir: &Path) -> Vec<PathBuf> {
+    let mut files: Vec<PathBuf> = fs::read_dir(dir).unwrap()
+        .flat_map(|d| {
+            let d = d.unwrap().path();
+            match d.is_dir() {
+                true => fs::read_dir(&d).unwrap().map(|f| f.unwrap().path()).collect(),
+                false => vec![],
+            }
+        })
+        .map(|f| f.strip_prefix(dir).unwrap().to_path_buf())
+        .collect();
+    files.sort();
+    files
+}
+
+fn entry_file(dir: &Path) -> PathBuf {
+    dir.join(files(dir).into_iter().find(|f| f.extension().is_some_and(|e| e == "md")).unwrap())
+}
+
+#[test]
+fn test_mirror_round_trip() {
+
+    Clock::init(Clock::from_settings(Some("UTC"), None).unwrap());
+    let tmp = TempDir::new().unwrap();
+    let ts = TaskStore::new(tmp.path().join("test.db").to_str().unwrap());
+    let dir = tmp.path().join("mirror");
+    let mirror = Mirror::new(&dir, ConflictPolicy::Report);
+
+    let task = ts.put(Task::new("Morning Run!", "")).unwrap();
+    let at = Utc.with_ymd_and_hms(2024, 3, 1, 7, 30, 0).unwrap();
+    let entry = ts.put(TaskEntry::new_at(task.id, "5 km", at)).unwrap();
+
+    // a dry run touches nothing
+    let report = mirror.sync(&ts, true).unwrap();
+    assert_eq!(report.written, 2);
+    assert!(!dir.exists());
+
+    let report = mirror.sync(&ts, false).unwrap();
+    assert_eq!((report.ingested, report.written), (0, 2));
+    let short = &task.id.simple().to_string()[..8];
+    let folder = PathBuf::from(format!("morning-run-{}", short));
+    assert_eq!(files(&dir), vec![
+        folder.join(format!("2024-03-01_073000-{}.md", &entry.id.simple().to_string()[..8])),
+        folder.join(TASK_FILE),
+    ]);
+    assert!(dir.join(MANIFEST).exists());
+    let text = fs::read_to_string(entry_file(&dir)).unwrap();
+    assert!(text.starts_with("+++\n") && text.ends_with("+++\n5 km"));
+
+    // nothing changed, nothing to do
+    let report = mirror.sync(&ts, false).unwrap();
+    assert_eq!((report.ingested, report.written, report.removed), (0, 0, 0));
+
+    // an edited file goes into the store, a dry run says so without
+    // calling it a conflict
+    fs::write(entry_file(&dir), text.replace("5 km", "7 km")).unwrap();
+    let report = mirror.sync(&ts, true).unwrap();
+    assert_eq!((report.ingested, report.written, report.conflicts.len()), (1, 0, 0));
+    assert_eq!(ts.find_entry(&entry.id.to_string()).unwrap().get_content(), "5 km");
+    let report = mirror.sync(&ts, false).unwrap();
+    assert_eq!((report.ingested, report.written), (1, 0));
+    assert_eq!(ts.find_entry(&entry.id.to_string()).unwrap().get_content(), "7 km");
+
+    // a new file without an ID is adopted and renamed after its record
+    let new = dir.join(&folder).join("later.md");
+    fs::write(&new, "+++\ntimestamp = \"2024-03-02T07:00:00Z\"\n+++\n3 km").unwrap();
+    let report = mirror.sync(&ts, false).unwrap();
+    assert_eq!((report.ingested, report.written), (1, 1));
+    assert!(!new.exists());
+    let entries = ts.get_prefix::<TaskEntry>(TaskEntry::key_task(task.id)).unwrap();
+    assert_eq!(entries.len(), 2);
+    assert_eq!(entries[0].get_content(), "3 km");
+    assert_eq!(files(&dir).len(), 3);
+
+    // a renamed file is not a deleted one, it gets its name back
+    let old = entry_file(&dir);
+    fs::rename(&old, dir.join(&folder).join("renamed.md")).unwrap();
+    let report = mirror.sync(&ts, false).unwrap();
+    assert_eq!((report.deleted, report.written), (0, 1));
+    assert_eq!(ts.get_prefix::<TaskEntry>(TaskEntry::key_task(task.id)).unwrap().len(), 2);
+    assert!(old.exists());
+    assert_eq!(files(&dir).len(), 3);
+
+    // a removed file deletes its record, and a removed record its file
+    fs::remove_file(entry_file(&dir)).unwrap();
+    let report = mirror.sync(&ts, false).unwrap();
+    assert_eq!(report.deleted, 1);
+    assert_eq!(ts.get_prefix::<TaskEntry>(TaskEntry::key_task(task.id)).unwrap().len(), 1);
+    let left = ts.get_prefix::<TaskEntry>(TaskEntry::key_task(task.id)).unwrap().remove(0);
+    ts.delete_item(&left).unwrap();
+    let report = mirror.sync(&ts, false).unwrap();
+    assert_eq!(report.removed, 1);
+    assert_eq!(files(&dir), vec![folder.join(TASK_FILE)]);
+
+    // a removed folder deletes its task, entries and references to it once
+    ts.put(TaskEntry::new_at(task.id, "1 km", at)).unwrap();
+    let routine = ts.put(Routine::new("Morning", "", vec![task.id])).unwrap();
+    mirror.sync(&ts, false).unwrap();
+    fs::remove_dir_all(dir.join(&folder)).unwrap();
+    let report = mirror.sync(&ts, true).unwrap();
+    assert_eq!((report.deleted, report.written), (2, 0));
+    let report = mirror.sync(&ts, false).unwrap();
+    assert_eq!((report.deleted, report.written), (2, 0));
+    assert!(ts.find_task(&task.id.to_string()).is_err());
+    assert!(ts.get::<Routine>(routine.to_key()).unwrap().unwrap().task_ids.is_empty());
+}
+
+#[test]
+fn test_mirror_conflicts() {
+
+    Clock::init(Clock::from_settings(Some("UTC"), None).unwrap());
+    let tmp = TempDir::new().unwrap();
+    let ts = TaskStore::new(tmp.path().join("test.db").to_str().unwrap());
+    let dir = tmp.path().join("mirror");
+
+    let task = ts.put(Task::new("Read", "")).unwrap();
+    let entry = ts.put(TaskEntry::new(task.id, "chapter 1")).unwrap();
+    Mirror::new(&dir, ConflictPolicy::Report).sync(&ts, false).unwrap();
+
+    // both sides changed since the last sync
+    let path = entry_file(&dir);
+    let edit = |content: &str| {
+        let text = fs::read_to_string(&path).unwrap();
+        let (meta, _) = text.rsplit_once("+++\n").unwrap();
+        fs::write(&path, format!("{}+++\n{}", meta, content)).unwrap();
+    };
+    let edited = TaskEntry { content: b"chapter 2".to_vec(), ..entry.clone() };
+    ts.put(edited.clone()).unwrap();
+    edit("chapter 3");
+
+    // reported and left alone
+    let report = Mirror::new(&dir, ConflictPolicy::Report).sync(&ts, false).unwrap();
+    assert_eq!(report.conflicts.len(), 1);
+    assert!(report.to_string().contains("conflict:"));
+    assert_eq!(ts.find_entry(&entry.id.to_string()).unwrap().get_content(), "chapter 2");
+    assert!(fs::read_to_string(&path).unwrap().ends_with("chapter 3"));
+    let report = Mirror::new(&dir, ConflictPolicy::Report).sync(&ts, false).unwrap();
+    assert_eq!(report.conflicts.len(), 1);
+
+    // the files win
+    let report = Mirror::new(&dir, ConflictPolicy::Files).sync(&ts, false).unwrap();
+    assert!(report.conflicts.is_empty());
+    assert_eq!(ts.find_entry(&entry.id.to_string()).unwrap().get_content(), "chapter 3");
+
+    // the store wins
+    ts.put(edited).unwrap();
+    edit("chapter 4");
+    let report = Mirror::new(&dir, ConflictPolicy::Store).sync(&ts, false).unwrap();
+    assert!(report.conflicts.is_empty());
+    assert!(fs::read_to_string(&path).unwrap().ends_with("chapter 2"));
+
+    // unreadable files are reported, not deleted
+    edit("chapter 5");
+    fs::write(&path, "no front matter").unwrap();
+    let report = Mirror::new(&dir, ConflictPolicy::Report).sync(&ts, false).unwrap();
+    assert_eq!(report.errors.len(), 1);
+    assert_eq!(report.deleted, 0);
+    assert!(ts.find_entry(&entry.id.to_string()).is_ok());
+
+    assert!("theirs".parse::<ConflictPolicy>().is_err());
+    assert_eq!("Files".parse::<ConflictPolicy>().unwrap(), ConflictPolicy::Files);
+}
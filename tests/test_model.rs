@@ -1,7 +1,11 @@
 use packrat::model::convert::Storable;
 use packrat::model::task::Task;
 use packrat::model::task_entry::TaskEntry;
-use packrat::model::store::TaskStore;
+use packrat::model::store::{CsvLayout, TaskStore};
+use packrat::model::task::{all_tags, parse_tags, TaskFilter};
+use packrat::model::progress::{progress_all, tag_rollups};
+use packrat::model::clock::Clock;
+use chrono::Utc;
 use tempfile::TempDir;
 
 fn get_empty_db() -> TaskStore {
@@ -103,3 +107,42 @@ fn test_task_manager() {
     assert_eq!(ts.get_prefix::<Task>(Task::key_all()).unwrap().len(), 0); 
 }
 
+
+#[test]
+fn test_task_tags() {
+
+    let ts = get_empty_db();
+
+    let mut run = Task::new("Run", "5k");
+    run.tags = parse_tags("Health, #outdoors health");
+    assert_eq!(run.tags, vec!["health", "outdoors"]);
+    let mut read = Task::new("Read", "A chapter");
+    read.tags = parse_tags("learning");
+    let run = ts.put(run).unwrap();
+    let read = ts.put(read).unwrap();
+    ts.put(TaskEntry::new(run.id, "done")).unwrap();
+
+    let filter = TaskFilter { tag: Some(String::from("#HEALTH")), ..Default::default() };
+    assert!(filter.matches(&run));
+    assert!(!filter.matches(&read));
+    assert!(TaskFilter::default().matches(&read));
+
+    // tags survive the round trip through the TOML config editor
+    let edited = Task::from_toml(run.to_toml().unwrap()).unwrap();
+    assert_eq!(edited.tags, run.tags);
+
+    let tasks = vec![run.clone(), read.clone()];
+    let progress = progress_all(&ts, &tasks, &Clock::default(), Utc::now()).unwrap();
+    let rollups = tag_rollups(&tasks, &progress);
+    assert_eq!(all_tags(&tasks), vec!["health", "learning", "outdoors"]);
+    assert_eq!(rollups.len(), 3);
+    assert_eq!(rollups[0].to_string(), "#health 1/1 done");
+    assert_eq!(rollups[1].to_string(), "#learning 0/1 done, 1 due");
+
+    // only the tagged task makes it into the export
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("out.csv");
+    ts.to_csv(path.to_str().unwrap(), &filter, CsvLayout::Wide).unwrap();
+    let csv = std::fs::read_to_string(&path).unwrap();
+    assert_eq!(csv, "Run\ndone\n");
+}
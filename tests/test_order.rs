@@ -0,0 +1,48 @@
+use chrono::{Duration, Utc};
+use packrat::model::clock::Clock;
+use packrat::model::order::{SortMode, TaskOrder};
+use packrat::model::progress::progress_all;
+use packrat::model::store::TaskStore;
+use packrat::model::task::Task;
+use tempfile::TempDir;
+
+fn names(tasks: &[Task], idxs: &[usize]) -> Vec<String> {
+    idxs.iter().map(|i| tasks[*i].name.clone()).collect()
+}
+
+#[test]
+fn test_sort_and_move() {
+
+    let dir = TempDir::new().unwrap();
+    let ts = TaskStore::new(dir.path().join("test.db").to_str().unwrap());
+
+    let mut tasks = vec![];
+    for (i, name) in ["walk", "Read", "code"].iter().enumerate() {
+        let mut task = Task::new(*name, "");
+        task.timestamp = Utc::now() - Duration::days(i as i64);
+        tasks.push(ts.put(task).unwrap());
+    }
+    let progress = progress_all(&ts, &tasks, &Clock::default(), Utc::now()).unwrap();
+
+    // nothing saved yet, so the tasks keep the order they came in
+    let mut order = TaskOrder::load(&ts).unwrap();
+    assert_eq!(order.mode, SortMode::Manual);
+    assert_eq!(names(&tasks, &order.sort(&tasks, &progress)), ["walk", "Read", "code"]);
+
+    order.mode = SortMode::Name;
+    assert_eq!(names(&tasks, &order.sort(&tasks, &progress)), ["code", "Read", "walk"]);
+
+    // moving adopts the shown order as the manual one
+    let shown: Vec<Task> = order.sort(&tasks, &progress).into_iter().map(|i| tasks[i].clone()).collect();
+    assert_eq!(order.move_task(&shown, 0, true), Some(1));
+    assert_eq!(order.move_task(&shown, 0, false), None);
+    assert_eq!(order.mode, SortMode::Manual);
+    ts.put(order).unwrap();
+
+    let order = TaskOrder::load(&ts).unwrap();
+    assert_eq!(names(&tasks, &order.sort(&tasks, &progress)), ["Read", "code", "walk"]);
+
+    // pinned tasks go first whatever the mode
+    tasks[2].pinned = true;
+    assert_eq!(names(&tasks, &order.sort(&tasks, &progress)), ["code", "Read", "walk"]);
+}
@@ -0,0 +1,119 @@
+use chrono::{DateTime, Duration, NaiveDate, TimeZone, Utc};
+use packrat::model::clock::Clock;
+use packrat::model::progress::Progress;
+use packrat::model::task::{EvalPeriod, Task, TaskFilter};
+use packrat::model::task_entry::TaskEntry;
+use packrat::model::pause::{paused_days, Pause};
+
+fn entry_at(task: &Task, ts: DateTime<Utc>) -> TaskEntry {
+    let mut entry = TaskEntry::new(task.id, "");
+    entry.timestamp = ts;
+    entry
+}
+
+#[test]
+fn test_weekly_target() {
+
+    let clock = Clock::from_settings(Some("UTC"), None).unwrap();
+    let mut task = Task::new("Gym", "3 times a week");
+    task.eval_period = EvalPeriod::Weekly;
+    task.target = 3;
+
+    // Wednesday 2026-03-04
+    let now = Utc.with_ymd_and_hms(2026, 3, 4, 12, 0, 0).unwrap();
+    let week = Duration::days(7);
+
+    let mut entries = vec![
+        entry_at(&task, now - Duration::days(1)),
+        entry_at(&task, now - Duration::days(2)),
+    ];
+    // the two previous weeks met the target, the one before didn't
+    for w in 1..=2 {
+        for _ in 0..3 {
+            entries.push(entry_at(&task, now - week * w));
+        }
+    }
+    entries.push(entry_at(&task, now - week * 3));
+
+    let progress = Progress::compute(&task, &entries, &[], &clock, now);
+    assert_eq!(progress.count, 2);
+    assert!(progress.is_due());
+    assert_eq!(progress.streak, 2);
+    assert_eq!(progress.to_string(), "2/3 this week");
+    assert_eq!(progress.streak_label(), "2 weeks");
+
+    // meeting the target extends the streak with the running week
+    entries.push(entry_at(&task, now));
+    let progress = Progress::compute(&task, &entries, &[], &clock, now);
+    assert!(!progress.is_due());
+    assert_eq!(progress.streak, 3);
+}
+
+#[test]
+fn test_daily_streak_broken() {
+
+    let clock = Clock::from_settings(Some("UTC"), None).unwrap();
+    let task = Task::new("Water", "Drink water");
+    let now = Utc.with_ymd_and_hms(2026, 3, 4, 12, 0, 0).unwrap();
+
+    let entries = vec![
+        entry_at(&task, now),
+        entry_at(&task, now - Duration::days(2)),
+    ];
+
+    let progress = Progress::compute(&task, &entries, &[], &clock, now);
+    assert_eq!(progress.to_string(), "1/1 today ✓");
+    assert_eq!(progress.streak, 1);
+}
+
+#[test]
+fn test_pause_freezes_streak() {
+
+    let clock = Clock::from_settings(Some("UTC"), None).unwrap();
+    let task = Task::new("Stretch", "Morning stretch");
+    let now = Utc.with_ymd_and_hms(2026, 3, 10, 12, 0, 0).unwrap();
+    let today = clock.day_of(now);
+
+    // stretched for two days, then away for four days up to and including today
+    let entries = vec![
+        entry_at(&task, now - Duration::days(6)),
+        entry_at(&task, now - Duration::days(5)),
+    ];
+    let trip = Pause::new(None, today - Duration::days(4), today, "travelling");
+    let other = Pause::new(Some(uuid::Uuid::new_v4()), today - Duration::days(4), today, "");
+
+    let progress = Progress::compute(&task, &entries, &[other.clone()], &clock, now);
+    assert_eq!(progress.streak, 0);
+    assert!(progress.is_due());
+
+    let progress = Progress::compute(&task, &entries, &[trip.clone(), other], &clock, now);
+    assert_eq!(progress.streak, 2);
+    assert!(progress.paused);
+    assert!(!progress.is_due());
+    assert_eq!(progress.to_string(), "0/1 today (paused)");
+
+    // a pause with no end in sight on either side stops at the first entry
+    let forever = Pause::new(None, NaiveDate::MIN, NaiveDate::MAX, "");
+    let progress = Progress::compute(&task, &entries, &[forever], &clock, now);
+    assert_eq!(progress.streak, 2);
+
+    assert!(trip.covers(task.id, today));
+    assert_eq!(paused_days(&[trip], task.id, today - Duration::days(1), today + Duration::days(9)).len(), 2);
+}
+
+#[test]
+fn test_archived_never_due() {
+
+    let clock = Clock::from_settings(Some("UTC"), None).unwrap();
+    let mut task = Task::new("Guitar", "Practice scales");
+    task.archived = true;
+    let now = Utc.with_ymd_and_hms(2026, 3, 4, 12, 0, 0).unwrap();
+
+    let progress = Progress::compute(&task, &[entry_at(&task, now - Duration::days(1))], &[], &clock, now);
+    assert!(!progress.is_due());
+    assert_eq!(progress.streak, 1);
+    assert_eq!(progress.to_string(), "0/1 today (archived)");
+
+    assert!(!TaskFilter::default().matches(&task));
+    assert!(TaskFilter { include_archived: true, ..Default::default() }.matches(&task));
+}
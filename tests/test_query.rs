@@ -0,0 +1,62 @@
+use chrono::{NaiveDate, TimeZone, Utc};
+use packrat::model::clock::Clock;
+use packrat::model::query::{Clause, Cmp, Query};
+use packrat::model::task::{Task, TaskFilter};
+use packrat::model::task_entry::TaskEntry;
+
+#[test]
+fn test_parse_query() {
+
+    let today = NaiveDate::from_ymd_opt(2026, 3, 10).unwrap();
+    let query = Query::parse(r#"task:Run after:7d value>=5 -tag:#Work "sore knee""#, today).unwrap();
+    assert_eq!(query.clauses, vec![
+        Clause::Task(String::from("run")),
+        Clause::After(NaiveDate::from_ymd_opt(2026, 3, 3).unwrap()),
+        Clause::Value(Cmp::Ge, 5.0),
+        Clause::Not(Box::new(Clause::Tag(String::from("work")))),
+        Clause::Text(String::from("sore knee")),
+    ]);
+    assert_eq!(query.to_string(), r#"task:run after:2026-03-03 value>=5 -tag:work "sore knee""#);
+    assert!(Query::parse("", today).unwrap().is_empty());
+
+    assert!(Query::parse("when:today", today).is_err());
+    assert!(Query::parse("after:someday", today).is_err());
+    assert!(Query::parse("value>lots", today).is_err());
+    assert!(Query::parse("task:", today).is_err());
+    assert!(Query::parse("\"open", today).is_err());
+    assert!(Query::parse("after:99999999999999d", today).is_err());
+    assert!(Query::parse("before:9999999999999w", today).is_err());
+}
+
+#[test]
+fn test_filter_entries() {
+
+    let clock = Clock::from_settings(Some("UTC"), None).unwrap();
+    let today = NaiveDate::from_ymd_opt(2026, 3, 10).unwrap();
+    let mut run = Task::new("Morning run", "km");
+    run.tags = vec![String::from("health")];
+    let read = Task::new("Read", "pages");
+
+    let at = |d: u32| Utc.with_ymd_and_hms(2026, 3, d, 8, 0, 0).unwrap();
+    let entries = vec![
+        TaskEntry::new_at(run.id, "7.5", at(9)),
+        TaskEntry::new_at(run.id, "3", at(8)),
+        TaskEntry::new_at(run.id, "6", at(1)),
+    ];
+
+    let filter = |q: &str| TaskFilter {
+        query: Query::parse(q, today).unwrap(),
+        ..Default::default()
+    };
+
+    let kept = filter("task:run after:2026-03-05 value>5 tag:health")
+        .select(&run, &entries, &clock)
+        .unwrap();
+    assert_eq!(kept, vec![&entries[0]]);
+
+    // a task with no matching entries is left out, unless only tasks were asked about
+    assert!(filter("value>100").select(&run, &entries, &clock).is_none());
+    assert_eq!(filter("-task:read").select(&run, &entries, &clock).unwrap().len(), 3);
+    assert!(filter("-task:read").select(&read, &[], &clock).is_none());
+    assert_eq!(filter("on:2026-03-08").select(&run, &entries, &clock).unwrap(), vec![&entries[1]]);
+}
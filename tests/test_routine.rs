@@ -0,0 +1,59 @@
+use packrat::model::routine::{parse_members, Routine, RoutineRun};
+use packrat::model::store::TaskStore;
+use packrat::model::task::Task;
+use packrat::model::task_entry::TaskEntry;
+use tempfile::TempDir;
+
+#[test]
+fn test_run_routine() {
+
+    let dir = TempDir::new().unwrap();
+    let ts = TaskStore::new(dir.path().join("test.db").to_str().unwrap());
+
+    let water = ts.put(Task::new("Water", "A glass")).unwrap();
+    let stretch = ts.put(Task::new("Stretch", "Ten minutes")).unwrap();
+    let journal = ts.put(Task::new("Journal", "One page")).unwrap();
+
+    let members = parse_members(&ts, "water, Stretch ,journal").unwrap();
+    assert_eq!(members, vec![water.id, stretch.id, journal.id]);
+    assert!(parse_members(&ts, "water, swim").is_err());
+
+    let routine = ts.put(Routine::new("Morning", "", members)).unwrap();
+    let tasks = routine.tasks(&ts).unwrap();
+    assert_eq!(tasks, vec![water.clone(), stretch.clone(), journal.clone()]);
+    assert_eq!(routine.last_run(&ts).unwrap(), None);
+
+    // log the water, skip stretching, log the journal
+    let mut run = RoutineRun::new(routine.id);
+    assert_eq!(run.next_step(&tasks), Some(&water));
+    run.log_step(&ts, &water, "500ml").unwrap();
+    run.skip_step(&stretch);
+    assert_eq!(run.next_step(&tasks), Some(&journal));
+    run.log_step(&ts, &journal, "").unwrap();
+    assert_eq!(run.next_step(&tasks), None);
+    run.finish(&ts).unwrap();
+
+    let last = routine.last_run(&ts).unwrap().unwrap();
+    assert_eq!(last.to_string(), "2 of 3 steps done");
+    assert!(!last.is_complete());
+
+    // stopping keeps what was done, unless nothing was
+    let mut run = RoutineRun::new(routine.id);
+    assert!(!run.stop(&ts, &tasks).unwrap());
+    run.log_step(&ts, &water, "").unwrap();
+    assert!(run.stop(&ts, &tasks).unwrap());
+    assert_eq!(run.skipped, vec![stretch.id, journal.id]);
+    let last = routine.last_run(&ts).unwrap().unwrap();
+    assert_eq!((last.id, last.to_string()), (run.id, String::from("1 of 3 steps done")));
+
+    let entries: Vec<TaskEntry> = ts.get_prefix(TaskEntry::key_task(water.id)).unwrap();
+    assert_eq!(entries.len(), 2);
+    let entries: Vec<TaskEntry> = ts.get_prefix(TaskEntry::key_task(stretch.id)).unwrap();
+    assert!(entries.is_empty());
+
+    // routines and their runs don't leak into the task listing
+    let all: Vec<Task> = ts.get_prefix(Task::key_all()).unwrap();
+    assert_eq!(all.len(), 3);
+    let routines: Vec<Routine> = ts.get_prefix(Routine::key_all()).unwrap();
+    assert_eq!(routines.len(), 1);
+}
@@ -0,0 +1,56 @@
+use packrat::model::search::{search, snippet, tokenize};
+use packrat::model::store::TaskStore;
+use packrat::model::task::Task;
+use packrat::model::task_entry::TaskEntry;
+use tempfile::TempDir;
+
+#[test]
+fn test_index_follows_entries() {
+
+    let dir = TempDir::new().unwrap();
+    let ts = TaskStore::new(dir.path().join("test.db").to_str().unwrap());
+
+    let run = ts.put(Task::new("Run", "5k")).unwrap();
+    let mut sore = ts.put(TaskEntry::new(run.id, "Knee felt sore, knee brace helped")).unwrap();
+    let fine = ts.put(TaskEntry::new(run.id, "Easy pace, my knee was fine")).unwrap();
+    ts.put(TaskEntry::new(run.id, "Rainy")).unwrap();
+
+    // more mentions rank higher, a partial last word still matches
+    let hits = search(&ts, "KNEE").unwrap();
+    assert_eq!(hits.iter().map(|h| h.entry.id).collect::<Vec<_>>(), vec![sore.id, fine.id]);
+    assert_eq!(hits[0].task.name, "Run");
+    assert_eq!(search(&ts, "knee bra").unwrap().len(), 1);
+    assert!(search(&ts, "knee rainy").unwrap().is_empty());
+
+    // edits replace the old words
+    sore.content = "Ankle this time".into();
+    let sore = ts.put(sore).unwrap();
+    assert_eq!(search(&ts, "knee").unwrap().len(), 1);
+    assert_eq!(search(&ts, "ankle").unwrap()[0].entry.id, sore.id);
+
+    ts.delete_item(&fine).unwrap();
+    assert!(search(&ts, "knee").unwrap().is_empty());
+    assert!(search(&ts, "").unwrap().is_empty());
+
+    // deleting an old copy drops the words of what is stored
+    let mut stale = sore.clone();
+    stale.content = "Knee felt sore".into();
+    ts.delete_item(&stale).unwrap();
+    assert!(ts.postings("ankle").unwrap().is_empty());
+}
+
+#[test]
+fn test_snippet() {
+
+    assert_eq!(tokenize("It's a 5k-run!"), vec!["it", "s", "a", "5k", "run"]);
+
+    let text = "Warmed up for a while.\nThen the knee started to ache";
+    let pieces = snippet(text, "knee", 20);
+    assert_eq!(pieces, vec![
+        (String::from("…"), false),
+        (String::from(" the "), false),
+        (String::from("knee"), true),
+        (String::from(" started to"), false),
+        (String::from("…"), false),
+    ]);
+}
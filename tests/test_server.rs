@@ -0,0 +1,134 @@
+use std::io::{Read, Write};
+use std::net::{SocketAddr, TcpStream};
+use std::thread;
+use std::time::Duration;
+use serde_json::{json, Value};
+use packrat::model::clock::Clock;
+use packrat::model::store::TaskStore;
+use packrat::server::{decode, decode_query, ApiServer};
+use tempfile::TempDir;
+
+const TOKEN: &str = "s3cret";
+
+/// Send one request and return the status and JSON body
+fn call(addr: SocketAddr, method: &str, path: &str, token: Option<&str>, body: Option<Value>) -> (u16, Value) {
+    let body = body.map(|b| b.to_string()).unwrap_or_default();
+    let auth = token.map(|t| format!("Authorization: Bearer {}\r\n", t)).unwrap_or_default();
+    let mut stream = TcpStream::connect(addr).unwrap();
+    stream.set_read_timeout(Some(Duration::from_secs(10))).unwrap();
+    write!(
+        stream,
+        "{} {} HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n{}Content-Type: application/json\r\nContent-Length: {}\r\n\r\n{}",
+        method, path, auth, body.len(), body
+    ).unwrap();
+    let mut response = String::new();
+    stream.read_to_string(&mut response).unwrap();
+
+    let status = response[9..12].parse().unwrap();
+    let (_, body) = response.split_once("\r\n\r\n").unwrap();
+    (status, serde_json::from_str(body).unwrap())
+}
+
+#[test]
+fn test_server() {
+
+    Clock::init(Clock::from_settings(Some("UTC"), None).unwrap());
+    let dir = TempDir::new().unwrap();
+    let ts = TaskStore::new(dir.path().join("test.db").to_str().unwrap());
+    let server = ApiServer::bind("127.0.0.1:0", TOKEN).unwrap();
+    let addr = server.addr().unwrap();
+
+    thread::scope(|s| {
+        s.spawn(|| server.run(&ts));
+        let _stop = Stop(&server);
+
+        // writes need the token, reads don't
+        let run = json!({"name": "Morning run", "period": "weekly", "type": "numeric", "target": 3, "tags": ["Health"]});
+        let (status, body) = call(addr, "POST", "/tasks", None, Some(run.clone()));
+        assert_eq!((status, body["error"].is_string()), (401, true));
+        assert_eq!(call(addr, "POST", "/tasks", Some("wrong"), Some(run.clone())).0, 401);
+
+        let (status, task) = call(addr, "POST", "/tasks", Some(TOKEN), Some(run.clone()));
+        assert_eq!(status, 201);
+        assert_eq!(task["eval_period"], "Weekly");
+        assert_eq!(task["tags"], json!(["health"]));
+        assert_eq!(call(addr, "POST", "/tasks", Some(TOKEN), Some(run)).0, 409);
+        // names are compared whole, a name that is an ID prefix doesn't clash
+        assert_eq!(call(addr, "POST", "/tasks", Some(TOKEN), Some(json!({"name": "MORNING RUN"}))).0, 409);
+        let prefix = &task["id"].as_str().unwrap()[..4];
+        let (status, clash) = call(addr, "POST", "/tasks", Some(TOKEN), Some(json!({"name": prefix})));
+        assert_eq!(status, 201);
+        call(addr, "DELETE", &format!("/tasks/{}", clash["id"].as_str().unwrap()), Some(TOKEN), None);
+        let (status, body) = call(addr, "POST", "/tasks", Some(TOKEN), Some(json!({"name": "x", "colour": 1})));
+        assert_eq!(status, 400);
+        assert!(body["error"].as_str().unwrap().contains("colour"));
+        call(addr, "POST", "/tasks", Some(TOKEN), Some(json!({"name": "Read", "tags": ["mind"]})));
+        // a + in the path is itself
+        call(addr, "POST", "/tasks", Some(TOKEN), Some(json!({"name": "C++"})));
+        let (status, cpp) = call(addr, "GET", "/tasks/C++", None, None);
+        assert_eq!((status, cpp["name"].as_str()), (200, Some("C++")));
+        call(addr, "DELETE", "/tasks/C++", Some(TOKEN), None);
+
+        // entries, by name in the path
+        let (status, entry) = call(addr, "POST", "/tasks/morning%20run/entries", Some(TOKEN),
+                                   Some(json!({"content": "5.5", "at": "2026-03-04 07:00"})));
+        assert_eq!(status, 201);
+        assert_eq!(entry["content"], "5.5");
+        assert_eq!(entry["timestamp"], "2026-03-04T07:00:00Z");
+        assert_eq!(entry["task_id"], task["id"]);
+        call(addr, "POST", "/tasks/morning%20run/entries", Some(TOKEN), Some(json!({"content": "3"})));
+        let huge = json!({"content": "1", "at": "99999999999999 days ago"});
+        assert_eq!(call(addr, "POST", "/tasks/morning%20run/entries", Some(TOKEN), Some(huge)).0, 400);
+        let id = entry["id"].as_str().unwrap();
+
+        let (_, entries) = call(addr, "GET", "/tasks/morning%20run/entries", None, None);
+        assert_eq!(entries.as_array().unwrap().len(), 2);
+        let (_, entries) = call(addr, "GET", "/tasks/morning%20run/entries?q=value%3E4", None, None);
+        assert_eq!(entries.as_array().unwrap().len(), 1);
+
+        let (status, updated) = call(addr, "PATCH", &format!("/entries/{}", &id[..8]), Some(TOKEN),
+                                     Some(json!({"content": "6"})));
+        assert_eq!((status, updated["content"].as_str()), (200, Some("6")));
+        assert_eq!(call(addr, "GET", &format!("/entries/{}", id), None, None).1["content"], "6");
+
+        // listing and filtering
+        let (_, tasks) = call(addr, "GET", "/tasks", None, None);
+        assert_eq!(tasks.as_array().unwrap().len(), 2);
+        let (_, tasks) = call(addr, "GET", "/tasks?tag=health", None, None);
+        assert_eq!(tasks[0]["name"], "Morning run");
+        assert_eq!(tasks[0]["progress"]["target"], 3);
+        assert_eq!(tasks.as_array().unwrap().len(), 1);
+        assert_eq!(call(addr, "GET", "/tasks?q=after:", None, None).0, 400);
+
+        // stats
+        let (status, stats) = call(addr, "GET", "/tasks/Morning%20Run/stats", None, None);
+        assert_eq!(status, 200);
+        assert_eq!(stats["entries"], 2);
+        assert_eq!(stats["max"], 6.0);
+        assert_eq!(call(addr, "GET", "/stats", None, None).1.as_array().unwrap().len(), 2);
+
+        // updates, deletes and the errors on the way
+        let (_, patched) = call(addr, "PATCH", "/tasks/read", Some(TOKEN), Some(json!({"archived": true})));
+        assert_eq!(patched["archived"], true);
+        assert_eq!(call(addr, "PATCH", "/tasks/read", Some(TOKEN), Some(json!({"period": "yearly"}))).0, 400);
+        assert_eq!(call(addr, "GET", "/tasks", None, None).1.as_array().unwrap().len(), 1);
+        assert_eq!(call(addr, "GET", "/tasks/nothing", None, None).0, 404);
+        assert_eq!(call(addr, "GET", "/nowhere", None, None).0, 404);
+
+        let (status, deleted) = call(addr, "DELETE", "/tasks/morning%20run", Some(TOKEN), None);
+        assert_eq!((status, deleted["entries"].as_u64()), (200, Some(2)));
+        assert_eq!(call(addr, "GET", &format!("/entries/{}", id), None, None).0, 404);
+    });
+
+    assert_eq!(decode("a%20b+c%2Fd%zz"), "a b+c/d%zz");
+    assert_eq!(decode_query("a%20b+c%2B"), "a b c+");
+}
+
+/// Stops the server even when an assertion fails, so the test can't hang
+struct Stop<'a>(&'a ApiServer);
+
+impl Drop for Stop<'_> {
+    fn drop(&mut self) {
+        self.0.stop();
+    }
+}
@@ -0,0 +1,50 @@
+use chrono::{DateTime, Duration, NaiveDate, TimeZone, Utc};
+use packrat::model::clock::Clock;
+use packrat::model::stats::{daily_totals, recent_days, TaskStats};
+use packrat::model::task::{EvalPeriod, Task, TaskTy};
+use packrat::model::task_entry::TaskEntry;
+
+fn entry_at(task: &Task, content: &str, ts: DateTime<Utc>) -> TaskEntry {
+    let mut entry = TaskEntry::new(task.id, content);
+    entry.timestamp = ts;
+    entry
+}
+
+#[test]
+fn test_task_stats() {
+
+    let clock = Clock::from_settings(Some("UTC"), None).unwrap();
+    let now = Utc.with_ymd_and_hms(2026, 3, 4, 12, 0, 0).unwrap();
+
+    let mut task = Task::new("Run", "km per run");
+    task.task_type = TaskTy::Numeric;
+    task.eval_period = EvalPeriod::Daily;
+    task.timestamp = now - Duration::days(4);
+
+    // four finished days, two of which have a run
+    let entries = vec![
+        entry_at(&task, "5", now - Duration::days(4)),
+        entry_at(&task, "10.5", now - Duration::days(2)),
+        entry_at(&task, "2", now - Duration::days(2)),
+        entry_at(&task, "not a number", now),
+    ];
+
+    let stats = TaskStats::compute(&task, &entries, &[], &clock, now);
+    assert_eq!(stats.entries, 4);
+    assert_eq!(stats.periods, 4);
+    assert_eq!(stats.periods_met, 2);
+    assert_eq!(stats.completion_rate(), Some(0.5));
+    assert_eq!(stats.min, Some(2.0));
+    assert_eq!(stats.max, Some(10.5));
+    assert_eq!(stats.mean, Some(17.5 / 3.0));
+
+    // 2026-03-04 is a Wednesday
+    assert_eq!(stats.weekdays, [2, 0, 1, 0, 0, 1, 0]);
+
+    let totals = daily_totals(&task, &entries, &clock);
+    assert_eq!(totals.get(&NaiveDate::from_ymd_opt(2026, 3, 2).unwrap()), Some(&12.5));
+    assert_eq!(
+        recent_days(&totals, clock.day_of(now), 3),
+        vec![12.5, 0.0, 0.0]
+    );
+}
@@ -0,0 +1,200 @@
+use std::io::Cursor;
+use chrono::{Duration, Utc};
+use packrat::model::convert::Storable;
+use packrat::model::dump::Dump;
+use packrat::model::import::native;
+use packrat::model::store::{TaskStore, WriteBatch};
+use packrat::model::sync::{newest, sync, Conflict, Peer, Side, State, SyncReport};
+use packrat::model::task::{Task, TaskFilter};
+use packrat::model::task_entry::TaskEntry;
+use packrat::model::routine::Routine;
+use tempfile::TempDir;
+
+fn entries(ts: &TaskStore, task: &Task) -> Vec<String> {
+    let mut contents: Vec<String> = ts.get_prefix::<TaskEntry>(TaskEntry::key_task(task.id)).unwrap()
+        .iter()
+        .map(|e| e.get_content())
+        .collect();
+    contents.sort();
+    contents
+}
+
+/// Put `entry` as if it was edited `minutes` from now
+fn edit_at(ts: &TaskStore, entry: &TaskEntry, content: &str, minutes: i64) -> TaskEntry {
+    let edited = TaskEntry { content: content.as_bytes().to_vec(), ..entry.clone() };
+    let mut batch = WriteBatch::default();
+    ts.stage_at(&mut batch, &edited, Utc::now() + Duration::minutes(minutes)).unwrap();
+    ts.commit(batch).unwrap();
+    edited
+}
+
+#[test]
+fn test_sync_stores() {
+
+    let dir = TempDir::new().unwrap();
+    let laptop = TaskStore::new(dir.path().join("laptop.db").to_str().unwrap());
+    let desktop = TaskStore::new(dir.path().join("desktop.db").to_str().unwrap());
+    let mut never = |c: &Conflict| panic!("unexpected conflict {}", c);
+
+    let run = laptop.put(Task::new("Run", "")).unwrap();
+    let first = laptop.put(TaskEntry::new(run.id, "5 km")).unwrap();
+    laptop.put(TaskEntry::new(run.id, "3 km")).unwrap();
+    desktop.put(TaskEntry::new(run.id, "10 km")).unwrap();
+
+    // a dry run only counts
+    let report = sync(&laptop, &Peer::Store(&desktop), &mut never, true).unwrap();
+    assert_eq!((report.pulled, report.pushed), (1, 3));
+    assert!(desktop.get::<Task>(Task::key_task(run.id)).unwrap().is_none());
+
+    // new records go both ways
+    sync(&laptop, &Peer::Store(&desktop), &mut never, false).unwrap();
+    assert_eq!(entries(&laptop, &run), vec!["10 km", "3 km", "5 km"]);
+    assert_eq!(entries(&desktop, &run), entries(&laptop, &run));
+    let report = sync(&laptop, &Peer::Store(&desktop), &mut never, false).unwrap();
+    assert_eq!(report, SyncReport::default());
+
+    // an edit on one side only is taken without asking
+    edit_at(&desktop, &first, "6 km", 0);
+    let report = sync(&laptop, &Peer::Store(&desktop), &mut never, false).unwrap();
+    assert_eq!((report.pulled, report.conflicts), (1, 0));
+    assert_eq!(entries(&laptop, &run), vec!["10 km", "3 km", "6 km"]);
+
+    // deletions propagate and stay deleted, from either end
+    let gone = laptop.find_entry(&first.id.to_string()).unwrap();
+    laptop.delete_item(&gone).unwrap();
+    let report = sync(&desktop, &Peer::Store(&laptop), &mut never, false).unwrap();
+    assert_eq!(report.pulled_deleted, 1);
+    assert_eq!(entries(&desktop, &run), vec!["10 km", "3 km"]);
+    let report = sync(&laptop, &Peer::Store(&desktop), &mut never, false).unwrap();
+    assert_eq!(report, SyncReport::default());
+    assert_eq!(entries(&laptop, &run), vec!["10 km", "3 km"]);
+    assert!(laptop.deleted(TaskEntry::key_all()).unwrap().contains_key(&gone.to_key()));
+}
+
+#[test]
+fn test_sync_conflicts() {
+
+    let dir = TempDir::new().unwrap();
+    let laptop = TaskStore::new(dir.path().join("laptop.db").to_str().unwrap());
+    let desktop = TaskStore::new(dir.path().join("desktop.db").to_str().unwrap());
+
+    let read = laptop.put(Task::new("Read", "")).unwrap();
+    let entry = laptop.put(TaskEntry::new(read.id, "chapter 1")).unwrap();
+    sync(&laptop, &Peer::Store(&desktop), &mut newest, false).unwrap();
+
+    // the last writer wins
+    edit_at(&laptop, &entry, "chapter 2", 1);
+    edit_at(&desktop, &entry, "chapter 3", 2);
+    let report = sync(&laptop, &Peer::Store(&desktop), &mut newest, false).unwrap();
+    assert_eq!((report.pulled, report.conflicts), (1, 1));
+    assert_eq!(entries(&laptop, &read), vec!["chapter 3"]);
+    assert_eq!(entries(&desktop, &read), vec!["chapter 3"]);
+
+    // or whichever side is picked, the other store follows
+    edit_at(&laptop, &entry, "chapter 4", 3);
+    edit_at(&desktop, &entry, "chapter 5", 4);
+    let mut asked = vec![];
+    let mut here = |c: &Conflict| {
+        asked.push(c.clone());
+        Side::Local
+    };
+    let report = sync(&laptop, &Peer::Store(&desktop), &mut here, false).unwrap();
+    assert_eq!((report.pushed, report.conflicts), (1, 1));
+    assert_eq!(asked.len(), 1);
+    assert!(asked[0].to_string().contains("entry 'chapter 4'"));
+    assert_eq!(entries(&desktop, &read), vec!["chapter 4"]);
+
+    // an edit against a deletion is a conflict too
+    desktop.delete_item(&desktop.find_entry(&entry.id.to_string()).unwrap()).unwrap();
+    edit_at(&laptop, &entry, "chapter 6", 10);
+    let mut asked = vec![];
+    let mut there = |c: &Conflict| {
+        asked.push(c.remote.clone());
+        Side::Remote
+    };
+    sync(&laptop, &Peer::Store(&desktop), &mut there, false).unwrap();
+    assert_eq!(asked, vec![State::Deleted]);
+    assert!(entries(&laptop, &read).is_empty());
+}
+
+#[test]
+fn test_sync_export() {
+
+    let dir = TempDir::new().unwrap();
+    let laptop = TaskStore::new(dir.path().join("laptop.db").to_str().unwrap());
+    let desktop = TaskStore::new(dir.path().join("desktop.db").to_str().unwrap());
+    let export = |ts: &TaskStore| {
+        let mut out = vec![];
+        ts.write_json(&mut out, &TaskFilter::default()).unwrap();
+        native::read_json(Cursor::new(out)).unwrap()
+    };
+
+    let run = desktop.put(Task::new("Run", "")).unwrap();
+    let entry = desktop.put(TaskEntry::new(run.id, "5 km")).unwrap();
+    desktop.put(TaskEntry::new(run.id, "3 km")).unwrap();
+
+    let dump = export(&desktop);
+    assert_eq!(dump.store_id, Some(desktop.store_id()));
+    let report = sync(&laptop, &Peer::Export(dump), &mut newest, false).unwrap();
+    assert_eq!((report.pulled, report.pushed), (3, 0));
+    assert_eq!(entries(&laptop, &run), vec!["3 km", "5 km"]);
+
+    // an export carries its deletions
+    desktop.delete_item(&entry).unwrap();
+    let report = sync(&laptop, &Peer::Export(export(&desktop)), &mut newest, false).unwrap();
+    assert_eq!(report.pulled_deleted, 1);
+    assert_eq!(entries(&laptop, &run), vec!["3 km"]);
+
+    // and an older export doesn't undo newer changes here
+    let old = export(&desktop);
+    let kept = laptop.get_prefix::<TaskEntry>(TaskEntry::key_task(run.id)).unwrap().remove(0);
+    edit_at(&laptop, &kept, "4 km", 1);
+    let report = sync(&laptop, &Peer::Export(old.clone()), &mut newest, false).unwrap();
+    assert_eq!(report, SyncReport::default());
+    assert_eq!(entries(&laptop, &run), vec!["4 km"]);
+
+    // an export from before store IDs has no common base the first time only
+    let anonymous = Dump { store_id: None, ..old };
+    let report = sync(&laptop, &Peer::Export(anonymous.clone()), &mut newest, false).unwrap();
+    assert_eq!(report.conflicts, 1);
+    let report = sync(&laptop, &Peer::Export(anonymous), &mut newest, false).unwrap();
+    assert_eq!(report, SyncReport::default());
+    assert_eq!(entries(&laptop, &run), vec!["4 km"]);
+}
+
+#[test]
+fn test_sync_deleted_task() {
+
+    let dir = TempDir::new().unwrap();
+    let laptop = TaskStore::new(dir.path().join("laptop.db").to_str().unwrap());
+    let desktop = TaskStore::new(dir.path().join("desktop.db").to_str().unwrap());
+    let mut never = |c: &Conflict| panic!("unexpected conflict {}", c);
+
+    let run = laptop.put(Task::new("Run", "")).unwrap();
+    let swim = laptop.put(Task::new("Swim", "")).unwrap();
+    laptop.put(TaskEntry::new(run.id, "5 km")).unwrap();
+    let routine = laptop.put(Routine::new("Morning", "", vec![run.id, swim.id])).unwrap();
+    sync(&laptop, &Peer::Store(&desktop), &mut never, false).unwrap();
+
+    // the task goes on one side while the other still logs it and edits
+    // the routine it is in
+    desktop.delete_task(&desktop.find_task("Run").unwrap()).unwrap();
+    laptop.put(TaskEntry::new(run.id, "3 km")).unwrap();
+    let mut renamed = routine.clone();
+    renamed.name = String::from("Early");
+    let mut batch = WriteBatch::default();
+    laptop.stage_at(&mut batch, &renamed, Utc::now() + Duration::minutes(1)).unwrap();
+    laptop.commit(batch).unwrap();
+
+    // both changed the routine, the rename wins without bringing the task back
+    let report = sync(&laptop, &Peer::Store(&desktop), &mut newest, false).unwrap();
+    assert_eq!((report.pulled_deleted, report.pushed, report.conflicts), (3, 1, 1));
+    for ts in [&laptop, &desktop] {
+        assert!(ts.find_task("Run").is_err());
+        assert!(ts.get_prefix::<TaskEntry>(TaskEntry::key_all()).unwrap().is_empty());
+        let routine = ts.get::<Routine>(routine.to_key()).unwrap().unwrap();
+        assert_eq!((routine.name.as_str(), routine.task_ids), ("Early", vec![swim.id]));
+    }
+    let report = sync(&laptop, &Peer::Store(&desktop), &mut never, false).unwrap();
+    assert_eq!(report, SyncReport::default());
+}
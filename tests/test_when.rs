@@ -0,0 +1,70 @@
+use chrono::{TimeZone, Utc};
+use packrat::model::clock::Clock;
+use packrat::model::when::parse_when;
+
+#[test]
+fn test_parse_absolute() {
+
+    let clock = Clock::from_settings(Some("America/Vancouver"), None).unwrap();
+    let now = Utc.with_ymd_and_hms(2026, 3, 4, 20, 0, 0).unwrap();
+
+    assert_eq!(
+        parse_when("2026-03-01 19:30", &clock, now).unwrap(),
+        Utc.with_ymd_and_hms(2026, 3, 2, 3, 30, 0).unwrap()
+    );
+    assert_eq!(
+        parse_when("2026-03-01T19:30:00Z", &clock, now).unwrap(),
+        Utc.with_ymd_and_hms(2026, 3, 1, 19, 30, 0).unwrap()
+    );
+    // a date alone lands at noon
+    assert_eq!(
+        parse_when("2026-03-01", &clock, now).unwrap(),
+        Utc.with_ymd_and_hms(2026, 3, 1, 20, 0, 0).unwrap()
+    );
+}
+
+#[test]
+fn test_parse_relative() {
+
+    let clock = Clock::from_settings(Some("UTC"), Some(4)).unwrap();
+    // Wednesday
+    let now = Utc.with_ymd_and_hms(2026, 3, 4, 20, 0, 0).unwrap();
+
+    assert_eq!(parse_when("now", &clock, now).unwrap(), now);
+    assert_eq!(parse_when("today", &clock, now).unwrap(), now);
+    assert_eq!(
+        parse_when("yesterday 7pm", &clock, now).unwrap(),
+        Utc.with_ymd_and_hms(2026, 3, 3, 19, 0, 0).unwrap()
+    );
+    assert_eq!(
+        parse_when("Yesterday at 7:30 PM", &clock, now).unwrap(),
+        Utc.with_ymd_and_hms(2026, 3, 3, 19, 30, 0).unwrap()
+    );
+    // before the rollover hour is still the same logical day
+    assert_eq!(
+        parse_when("yesterday 2am", &clock, now).unwrap(),
+        Utc.with_ymd_and_hms(2026, 3, 4, 2, 0, 0).unwrap()
+    );
+    assert_eq!(
+        parse_when("monday noon", &clock, now).unwrap(),
+        Utc.with_ymd_and_hms(2026, 3, 2, 12, 0, 0).unwrap()
+    );
+    assert_eq!(
+        parse_when("last wednesday", &clock, now).unwrap(),
+        Utc.with_ymd_and_hms(2026, 2, 25, 12, 0, 0).unwrap()
+    );
+    assert_eq!(
+        parse_when("3 days ago", &clock, now).unwrap(),
+        Utc.with_ymd_and_hms(2026, 3, 1, 20, 0, 0).unwrap()
+    );
+    assert_eq!(
+        parse_when("9:15", &clock, now).unwrap(),
+        Utc.with_ymd_and_hms(2026, 3, 4, 9, 15, 0).unwrap()
+    );
+
+    assert!(parse_when("", &clock, now).is_err());
+    assert!(parse_when("someday", &clock, now).is_err());
+    assert!(parse_when("yesterday 13pm", &clock, now).is_err());
+    assert!(parse_when("99999999999999 days ago", &clock, now).is_err());
+    assert!(parse_when("9223372036854775807 minutes ago", &clock, now).is_err());
+}